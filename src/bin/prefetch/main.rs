@@ -0,0 +1,238 @@
+// Computes the hash of a source in the format `hash!` expects,
+// downloading it like fetchurl or checking it out like fetchgit
+
+mod nar;
+mod rewrite;
+
+use base64::{Engine, engine::general_purpose::URL_SAFE_NO_PAD};
+use oxide_pkgs::build::fetchurl::MIRRORS;
+use sha2::{Digest, Sha512};
+use std::{
+    env, fs, io,
+    path::{Path, PathBuf},
+    process::{self, Command},
+};
+
+const USAGE: &str = "usage: prefetch [options] <url>
+       prefetch [options] --git <url> (--rev <rev> | --tag <tag>)
+
+Prints the hash of the file at <url>, mirror:// urls are expanded
+like fetchurl does, OXIDE_MIRRORS_<site> overrides the mirrors of a site.
+With --git prints the hash of the checkout of <rev> or <tag> without .git.
+
+options:
+  --fetch-submodules        also check out the submodules
+  --sparse-checkout <path>  only check out <path>, can be repeated
+  --rewrite <file>          replace the hash! literal or the fake_hash() of <file>
+                            with the hash
+  --old <hash>              the literal to replace when <file> has several,
+                            fake_hash() for a fake_hash()";
+
+#[derive(Default)]
+struct Args {
+    url: Option<String>,
+    git: bool,
+    rev: Option<String>,
+    tag: Option<String>,
+    fetch_submodules: bool,
+    sparse_checkout: Vec<String>,
+    rewrite: Option<PathBuf>,
+    old: Option<String>,
+}
+
+fn parse_args() -> Result<Args, String> {
+    let mut args = Args::default();
+    let mut argv = env::args().skip(1);
+    while let Some(arg) = argv.next() {
+        let mut value = || argv.next().ok_or(format!("{arg} needs a value"));
+        match arg.as_str() {
+            "-h" | "--help" => {
+                println!("{USAGE}");
+                process::exit(0);
+            }
+            "--git" => args.git = true,
+            "--rev" => args.rev = Some(value()?),
+            "--tag" => args.tag = Some(value()?),
+            "--fetch-submodules" => args.fetch_submodules = true,
+            "--sparse-checkout" => args.sparse_checkout.push(value()?),
+            "--rewrite" => args.rewrite = Some(value()?.into()),
+            "--old" => args.old = Some(value()?),
+            _ if arg.starts_with('-') => return Err(format!("unknown option {arg}")),
+            _ if args.url.is_none() => args.url = Some(arg),
+            _ => return Err(format!("unexpected argument {arg}")),
+        }
+    }
+    if args.url.is_none() {
+        return Err("missing url".to_string());
+    }
+    if args.git != (args.rev.is_some() != args.tag.is_some()) {
+        return Err("--git needs exactly one of --rev and --tag".to_string());
+    }
+    if !args.git && (args.fetch_submodules || !args.sparse_checkout.is_empty()) {
+        return Err("--fetch-submodules and --sparse-checkout need --git".to_string());
+    }
+    Ok(args)
+}
+
+// removed when the prefetch is done, even when it fails
+struct TempDir(PathBuf);
+
+impl TempDir {
+    fn new() -> io::Result<Self> {
+        let dir = env::temp_dir().join(format!("oxide-prefetch-{}", process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir)?;
+        Ok(Self(dir))
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.0);
+    }
+}
+
+fn run(cmd: &mut Command) -> Result<bool, String> {
+    cmd.status()
+        .map(|status| status.success())
+        .map_err(|e| format!("cannot run {:?}: {e}", cmd.get_program()))
+}
+
+// the urls the fetchurl builder tries, in the same order
+fn urls(url: &str) -> Result<Vec<String>, String> {
+    if let Some((site, _)) = MIRRORS.site_of(url)
+        && let Ok(mirrors) = env::var(format!("OXIDE_MIRRORS_{site}"))
+    {
+        let file = &url["mirror://".len() + site.len() + 1..];
+        return Ok(mirrors
+            .split_whitespace()
+            .map(|mirror| format!("{mirror}{file}"))
+            .collect());
+    }
+    MIRRORS
+        .resolve(url)
+        .ok_or(format!("unknown mirror:// site in {url}"))
+}
+
+fn download(url: &str, dir: &Path) -> Result<PathBuf, String> {
+    let file = dir.join("file");
+    for url in urls(url)? {
+        eprintln!("trying {url}");
+        let success = run(Command::new("curl")
+            .args(["--location", "--max-redirs", "20", "--retry", "3"])
+            .args(["--fail", "--silent", "--show-error", "--output"])
+            .arg(&file)
+            .arg(&url))?;
+        if success {
+            return Ok(file);
+        }
+    }
+    Err(format!("cannot download {url} from any mirror"))
+}
+
+fn checkout(url: &str, args: &Args, dir: &Path) -> Result<PathBuf, String> {
+    let source = dir.join("source");
+    fs::create_dir_all(&source).map_err(|e| e.to_string())?;
+    let git = |args: &[&str]| {
+        let mut cmd = Command::new("git");
+        cmd.arg("-C").arg(&source).args(args);
+        run(&mut cmd)
+    };
+    let git_ok = |args: &[&str]| match git(args)? {
+        true => Ok(()),
+        false => Err(format!("git {} failed", args.join(" "))),
+    };
+    git_ok(&["init", "--quiet"])?;
+    git_ok(&["remote", "add", "origin", url])?;
+    if !args.sparse_checkout.is_empty() {
+        let mut sparse = vec!["sparse-checkout", "set", "--no-cone"];
+        sparse.extend(args.sparse_checkout.iter().map(String::as_str));
+        git_ok(&sparse)?;
+    }
+    let reference = match (&args.rev, &args.tag) {
+        (_, Some(tag)) => format!("refs/tags/{tag}"),
+        (Some(rev), None) => rev.clone(),
+        (None, None) => unreachable!(),
+    };
+    let commit = if git(&["fetch", "--quiet", "--depth", "1", "origin", &reference])? {
+        "FETCH_HEAD^{commit}".to_string()
+    } else {
+        // servers may refuse to serve a commit that is not a ref
+        git_ok(&[
+            "fetch",
+            "--quiet",
+            "origin",
+            "+refs/heads/*:refs/remotes/origin/*",
+            "+refs/tags/*:refs/tags/*",
+        ])?;
+        format!("{reference}^{{commit}}")
+    };
+    git_ok(&["checkout", "--quiet", &commit])?;
+    if args.fetch_submodules {
+        git_ok(&["submodule", "update", "--init", "--recursive", "--quiet"])?;
+    }
+    remove_dot_git(&source).map_err(|e| e.to_string())?;
+    Ok(source)
+}
+
+fn remove_dot_git(dir: &Path) -> io::Result<()> {
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        let file_type = entry.file_type()?;
+        if entry.file_name() == ".git" {
+            if file_type.is_dir() {
+                fs::remove_dir_all(entry.path())?;
+            } else {
+                fs::remove_file(entry.path())?;
+            }
+        } else if file_type.is_dir() {
+            remove_dot_git(&entry.path())?;
+        }
+    }
+    Ok(())
+}
+
+// files are hashed flat, directories through their NAR serialisation
+fn hash(path: &Path) -> io::Result<String> {
+    let mut hasher = Sha512::new();
+    if path.is_dir() {
+        nar::dump(path, &mut hasher)?;
+    } else {
+        io::copy(&mut fs::File::open(path)?, &mut hasher)?;
+    }
+    Ok(format!(
+        "sha512:{}",
+        URL_SAFE_NO_PAD.encode(hasher.finalize())
+    ))
+}
+
+fn prefetch(args: &Args) -> Result<String, String> {
+    let url = args.url.as_deref().unwrap();
+    let dir = TempDir::new().map_err(|e| format!("cannot create temp dir: {e}"))?;
+    let path = if args.git {
+        checkout(url, args, &dir.0)?
+    } else {
+        download(url, &dir.0)?
+    };
+    hash(&path).map_err(|e| format!("cannot hash {url}: {e}"))
+}
+
+fn main() {
+    let result = parse_args()
+        .map_err(|e| format!("{e}\n\n{USAGE}"))
+        .and_then(|args| {
+            let hash = prefetch(&args)?;
+            if let Some(file) = &args.rewrite {
+                rewrite::rewrite(file, args.old.as_deref(), &hash)?;
+                eprintln!("updated {}", file.display());
+            }
+            Ok(hash)
+        });
+    match result {
+        Ok(hash) => println!("{hash}"),
+        Err(e) => {
+            eprintln!("error: {e}");
+            process::exit(1);
+        }
+    }
+}
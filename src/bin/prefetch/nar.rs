@@ -0,0 +1,63 @@
+// Serialises a path in the NAR format, the hash of a fixed output
+// directory is the hash of this serialisation. Only the executable bit
+// survives from the permissions and timestamps are not recorded.
+
+use std::{
+    fs,
+    io::{self, Write},
+    os::unix::fs::PermissionsExt,
+    path::Path,
+};
+
+pub fn dump<W: Write>(path: &Path, out: &mut W) -> io::Result<()> {
+    write_str(out, b"nix-archive-1")?;
+    dump_node(path, out)
+}
+
+fn dump_node<W: Write>(path: &Path, out: &mut W) -> io::Result<()> {
+    let metadata = fs::symlink_metadata(path)?;
+    write_str(out, b"(")?;
+    write_str(out, b"type")?;
+    if metadata.is_symlink() {
+        write_str(out, b"symlink")?;
+        write_str(out, b"target")?;
+        write_str(out, fs::read_link(path)?.as_os_str().as_encoded_bytes())?;
+    } else if metadata.is_dir() {
+        write_str(out, b"directory")?;
+        let mut entries = fs::read_dir(path)?
+            .map(|entry| entry.map(|entry| entry.file_name()))
+            .collect::<io::Result<Vec<_>>>()?;
+        entries.sort();
+        for entry in entries {
+            write_str(out, b"entry")?;
+            write_str(out, b"(")?;
+            write_str(out, b"name")?;
+            write_str(out, entry.as_encoded_bytes())?;
+            write_str(out, b"node")?;
+            dump_node(&path.join(&entry), out)?;
+            write_str(out, b")")?;
+        }
+    } else {
+        write_str(out, b"regular")?;
+        if metadata.permissions().mode() & 0o111 != 0 {
+            write_str(out, b"executable")?;
+            write_str(out, b"")?;
+        }
+        write_str(out, b"contents")?;
+        out.write_all(&metadata.len().to_le_bytes())?;
+        io::copy(&mut fs::File::open(path)?, out)?;
+        write_padding(out, metadata.len())?;
+    }
+    write_str(out, b")")
+}
+
+fn write_str<W: Write>(out: &mut W, s: &[u8]) -> io::Result<()> {
+    out.write_all(&(s.len() as u64).to_le_bytes())?;
+    out.write_all(s)?;
+    write_padding(out, s.len() as u64)
+}
+
+fn write_padding<W: Write>(out: &mut W, len: u64) -> io::Result<()> {
+    let padding = (8 - len % 8) % 8;
+    out.write_all(&[0; 8][..padding as usize])
+}
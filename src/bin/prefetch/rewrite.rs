@@ -0,0 +1,112 @@
+// Replaces a `hash!("...")` literal or a `fake_hash()` of a recipe in place
+
+use std::{fs, path::Path};
+
+const FAKE_HASH: &str = "fake_hash()";
+
+// A hash of the recipe: `value` is what --old matches, the contents of
+// a hash! literal or `fake_hash()` itself, `start..end` is replaced by
+// the new hash and `quote` tells to wrap it in hash!
+struct Literal<'a> {
+    value: &'a str,
+    start: usize,
+    end: usize,
+    quote: bool,
+}
+
+fn hash_literals(source: &str) -> Vec<Literal<'_>> {
+    let mut literals = Vec::new();
+    let mut pos = 0;
+    while let Some(found) = source[pos..].find("hash!(") {
+        pos += found + "hash!(".len();
+        let rest = &source[pos..];
+        let start = pos + rest.len() - rest.trim_start().len();
+        if !source[start..].starts_with('"') {
+            continue;
+        }
+        if let Some(len) = source[start + 1..].find('"') {
+            let (start, end) = (start + 1, start + 1 + len);
+            literals.push(Literal {
+                value: &source[start..end],
+                start,
+                end,
+                quote: false,
+            });
+            pos = end;
+        }
+    }
+    literals
+}
+
+// calls of fake_hash(), not its definition nor other functions ending
+// with the same name
+fn fake_hashes(source: &str) -> Vec<Literal<'_>> {
+    source
+        .match_indices(FAKE_HASH)
+        .filter(|&(start, _)| {
+            let before =
+                source[..start].trim_end_matches(|c: char| c.is_ascii_alphanumeric() || c == '_');
+            before.len() == start && !before.trim_end().ends_with("fn")
+        })
+        .map(|(start, value)| Literal {
+            value,
+            start,
+            end: start + value.len(),
+            quote: true,
+        })
+        .collect()
+}
+
+fn literals(source: &str) -> Vec<Literal<'_>> {
+    let mut literals = hash_literals(source);
+    literals.extend(fake_hashes(source));
+    literals.sort_by_key(|literal| literal.start);
+    literals
+}
+
+fn shown(old: &str) -> String {
+    match old {
+        FAKE_HASH => FAKE_HASH.to_string(),
+        _ => format!("hash!(\"{old}\")"),
+    }
+}
+
+// `old` picks the literal to replace, it can be omitted when the recipe
+// has a single one
+pub fn rewrite(path: &Path, old: Option<&str>, new: &str) -> Result<(), String> {
+    let source =
+        fs::read_to_string(path).map_err(|e| format!("cannot read {}: {e}", path.display()))?;
+    let candidates: Vec<_> = literals(&source)
+        .into_iter()
+        .filter(|literal| old.is_none_or(|old| literal.value == old))
+        .collect();
+    let literal = match (candidates.as_slice(), old) {
+        ([literal], _) => literal,
+        ([], Some(old)) => return Err(format!("{} has no {}", path.display(), shown(old))),
+        ([], None) => {
+            return Err(format!(
+                "{} has no hash! literal nor fake_hash()",
+                path.display()
+            ));
+        }
+        (_, Some(old)) => {
+            return Err(format!("{} has several {}", path.display(), shown(old)));
+        }
+        (_, None) => {
+            return Err(format!(
+                "{} has several hash! literals or fake_hash(), pick one with --old",
+                path.display()
+            ));
+        }
+    };
+    let new = match literal.quote {
+        true => format!("hash!(\"{new}\")"),
+        false => new.to_string(),
+    };
+    let rewritten = format!(
+        "{}{new}{}",
+        &source[..literal.start],
+        &source[literal.end..]
+    );
+    fs::write(path, rewritten).map_err(|e| format!("cannot write {}: {e}", path.display()))
+}
@@ -0,0 +1,301 @@
+// Explores the packages of `all_pkgs` from their evaluation alone,
+// nothing is built
+
+use oxide_core::prelude::*;
+use oxide_pkgs::top_level::{
+    all_meta::all_meta,
+    all_packages::all_pkgs,
+    all_tests::split_test_attr,
+    eval_all::drv_hash,
+    graph::{GraphArgs, build_graph, runtime_graph},
+    registry::{PkgInfo, evaluate, evaluate_all, tests_of},
+};
+use regex::Regex;
+use serde_json::{Value, json};
+use std::{env, io, path::Path, process};
+
+const USAGE: &str = "usage: registry [--json] list
+       registry [--json] search <regex>
+       registry [--json] show <attr>
+       registry [--json] graph [--depth <n>] [--by-output] [--why <package>] <attr|path>
+       registry [--json] test [<attr>]
+
+list    prints every package with its version and description
+search  prints the packages whose attribute, name or description match <regex>
+show    prints the version, outputs, meta and direct dependencies of <attr>
+graph   prints the build time dependency graph of <attr> in the dot language,
+        or the runtime one of a built output given by its store <path>
+test    builds the tests of <attr>, or <attr> itself when it is a test, or
+        every test of the set, and prints which ones passed
+
+environment:
+  OXIDE_BUILD       the command building an attribute, \"oxide build\" by default
+
+options:
+  --json            print json instead of a table or a dot graph
+  --depth <n>       graph the dependencies up to <n> references away
+  --by-output       graph the outputs used of each dependency
+  --why <package>   highlight the shortest path to <package>";
+
+enum Command {
+    List,
+    Search(String),
+    Show(String),
+    Graph(String),
+    Test(Option<String>),
+}
+
+struct Args {
+    json: bool,
+    graph: GraphArgs,
+    command: Command,
+}
+
+fn parse_args() -> Result<Args, String> {
+    let mut json = false;
+    let mut graph = GraphArgs::new();
+    let mut positional = Vec::new();
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "-h" | "--help" => {
+                println!("{USAGE}");
+                process::exit(0);
+            }
+            "--json" => json = true,
+            "--depth" => {
+                let depth = args.next().ok_or("--depth needs a number")?;
+                let depth = depth
+                    .parse()
+                    .map_err(|_| format!("invalid depth {depth}"))?;
+                graph = graph.depth(depth);
+            }
+            "--by-output" => graph = graph.by_output(),
+            "--why" => graph = graph.why(args.next().ok_or("--why needs a package")?),
+            _ if arg.starts_with('-') => return Err(format!("unknown option {arg}")),
+            _ => positional.push(arg),
+        }
+    }
+    let mut positional = positional.into_iter();
+    let command = match positional.next().as_deref() {
+        Some("list") => Command::List,
+        Some("search") => Command::Search(positional.next().ok_or("search needs a regex")?),
+        Some("show") => Command::Show(positional.next().ok_or("show needs an attribute")?),
+        Some("graph") => Command::Graph(
+            positional
+                .next()
+                .ok_or("graph needs an attribute or a path")?,
+        ),
+        Some("test") => Command::Test(positional.next()),
+        Some(command) => return Err(format!("unknown command {command}")),
+        None => return Err("missing command".to_string()),
+    };
+    if let Some(arg) = positional.next() {
+        return Err(format!("unexpected argument {arg}"));
+    }
+    Ok(Args {
+        json,
+        graph,
+        command,
+    })
+}
+
+fn matches(regex: &Regex, pkg: &PkgInfo) -> bool {
+    regex.is_match(&pkg.attr)
+        || regex.is_match(&pkg.name)
+        || pkg
+            .meta
+            .is_some_and(|meta| regex.is_match(meta.description))
+}
+
+fn table(pkgs: &[PkgInfo]) -> String {
+    let rows: Vec<[&str; 3]> = pkgs
+        .iter()
+        .map(|pkg| {
+            [
+                pkg.attr.as_str(),
+                pkg.version.as_deref().unwrap_or("-"),
+                pkg.meta.map_or("-", |meta| meta.description),
+            ]
+        })
+        .collect();
+    let header = ["ATTR", "VERSION", "DESCRIPTION"];
+    let width = |column: usize| {
+        rows.iter()
+            .map(|row| row[column].len())
+            .chain([header[column].len()])
+            .max()
+            .unwrap()
+    };
+    let (attr_width, version_width) = (width(0), width(1));
+    [header]
+        .iter()
+        .chain(rows.iter())
+        .map(|[attr, version, description]| {
+            format!("{attr:attr_width$}  {version:version_width$}  {description}\n")
+        })
+        .collect()
+}
+
+fn details(pkg: &PkgInfo) -> String {
+    let mut lines = vec![
+        format!("attr:        {}", pkg.attr),
+        format!("name:        {}", pkg.name),
+        format!("version:     {}", pkg.version.as_deref().unwrap_or("-")),
+        format!("outputs:     {}", pkg.outputs.join(" ")),
+        format!("system:      {}", pkg.system.as_deref().unwrap_or("-")),
+    ];
+    if let Some(meta) = pkg.meta {
+        lines.push(format!("description: {}", meta.description));
+        lines.push(format!("homepage:    {}", meta.homepage));
+        lines.push(format!("license:     {}", meta.license));
+    }
+    if !pkg.tests.is_empty() {
+        lines.push(format!("tests:       {}", pkg.tests.join(" ")));
+    }
+    lines.push("deps:".to_string());
+    for dep in &pkg.deps {
+        match &dep.output {
+            Some(output) => lines.push(format!("  {} ({output})", dep.name)),
+            None => lines.push(format!("  {}", dep.name)),
+        }
+    }
+    lines.iter().map(|line| format!("{line}\n")).collect()
+}
+
+fn results(results: &[(String, bool)]) -> String {
+    let width = results
+        .iter()
+        .map(|(test, _)| test.len())
+        .chain([4])
+        .max()
+        .unwrap();
+    let rows = results.iter().map(|(test, passed)| {
+        let result = if *passed { "passed" } else { "FAILED" };
+        format!("{test:width$}  {result}\n")
+    });
+    [format!("{:width$}  RESULT\n", "TEST")]
+        .into_iter()
+        .chain(rows)
+        .collect()
+}
+
+// Whether `attr` built with the command of `OXIDE_BUILD`
+fn build(attr: &str) -> Result<bool, String> {
+    let command = env::var("OXIDE_BUILD").unwrap_or("oxide build".to_string());
+    let mut command = command.split_whitespace();
+    let program = command.next().ok_or("OXIDE_BUILD is empty")?;
+    // what the builds print goes to stderr, stdout is for the results
+    let status = process::Command::new(program)
+        .args(command)
+        .arg(attr)
+        .stdout(io::stderr())
+        .status()
+        .map_err(|e| format!("cannot run {program}: {e}"))?;
+    Ok(status.success())
+}
+
+fn run(args: Args) -> Result<String, String> {
+    let (pkgs, _) = all_pkgs();
+    let json_lines = |value: Value| format!("{value:#}\n");
+    let list = |pkgs: Vec<PkgInfo>| match args.json {
+        true => json_lines(Value::Array(pkgs.iter().map(PkgInfo::to_json).collect())),
+        false => table(&pkgs),
+    };
+    match &args.command {
+        Command::List => Ok(list(evaluate_all(&pkgs))),
+        Command::Search(regex) => {
+            let regex = Regex::new(regex).map_err(|e| format!("invalid regex: {e}"))?;
+            let found: Vec<PkgInfo> = evaluate_all(&pkgs)
+                .into_iter()
+                .filter(|pkg| matches(&regex, pkg))
+                .collect();
+            Ok(list(found))
+        }
+        Command::Show(attr) => {
+            let drv = pkgs.get(attr).ok_or(format!("no package named {attr}"))?;
+            let pkg = PkgInfo {
+                tests: tests_of(&pkgs, attr),
+                ..evaluate(attr, drv, all_meta().get(attr.as_str()).copied())
+            };
+            match args.json {
+                true => Ok(json_lines(pkg.to_json())),
+                false => Ok(details(&pkg)),
+            }
+        }
+        Command::Graph(root) => {
+            let mut graph_args = args.graph;
+            let why = graph_args.why.clone();
+            // packages given by attribute are found by their derivation
+            if let Some(drv) = graph_args.why.as_ref().and_then(|why| pkgs.get(why)) {
+                graph_args.why = Some(drv_hash(&LazyDrv::clone(drv).into_drv()));
+            }
+            let graph = match pkgs.get(root) {
+                Some(drv) => build_graph(drv, &graph_args),
+                None if Path::new(root).is_absolute() => {
+                    runtime_graph(Path::new(root), &graph_args)
+                        .map_err(|e| format!("cannot read {root}: {e}"))?
+                }
+                None => return Err(format!("no package named {root}")),
+            };
+            if let Some(why) = why.filter(|_| graph.why.is_empty()) {
+                eprintln!("{} does not depend on {why}", graph.label(&graph.root));
+            }
+            match args.json {
+                true => Ok(json_lines(graph.to_json())),
+                false => Ok(graph.to_dot()),
+            }
+        }
+        Command::Test(attr) => {
+            let tests = match attr {
+                Some(attr) if !pkgs.contains_key(attr) => {
+                    return Err(format!("no package named {attr}"));
+                }
+                Some(attr) if split_test_attr(attr).is_some() => vec![attr.clone()],
+                Some(attr) => tests_of(&pkgs, attr),
+                None => {
+                    let mut tests: Vec<String> = pkgs
+                        .into_keys()
+                        .filter(|attr| split_test_attr(attr).is_some())
+                        .collect();
+                    tests.sort();
+                    tests
+                }
+            };
+            let mut passed = Vec::new();
+            for test in tests {
+                eprintln!("building {test}");
+                let result = build(&test)?;
+                passed.push((test, result));
+            }
+            let output = match args.json {
+                true => json_lines(
+                    passed
+                        .iter()
+                        .map(|(test, passed)| json!({"test": test, "passed": passed}))
+                        .collect(),
+                ),
+                false => results(&passed),
+            };
+            let failed = passed.iter().filter(|(_, passed)| !passed).count();
+            if failed == 0 {
+                return Ok(output);
+            }
+            print!("{output}");
+            Err(format!("{failed} of {} tests failed", passed.len()))
+        }
+    }
+}
+
+fn main() {
+    let result = parse_args()
+        .map_err(|e| format!("{e}\n\n{USAGE}"))
+        .and_then(run);
+    match result {
+        Ok(output) => print!("{output}"),
+        Err(e) => {
+            eprintln!("error: {e}");
+            process::exit(1);
+        }
+    }
+}
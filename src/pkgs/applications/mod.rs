@@ -0,0 +1,2 @@
+pub mod package_management;
+pub mod version_management;
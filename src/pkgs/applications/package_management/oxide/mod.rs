@@ -0,0 +1,27 @@
+use crate::{
+    build::{
+        forges::{FetchFromForge, ForgeArgs},
+        rust::{RustPackageArgs, RustPlatform},
+    },
+    lib::fake_hash,
+};
+use oxide_core::prelude::*;
+
+pub struct Oxide {
+    pub rust_platform: RustPlatform,
+    pub forges: FetchFromForge,
+}
+
+impl IntoDrv for Oxide {
+    fn into_drv(self) -> Drv {
+        let version = "0.1.0";
+        // the commit oxide_core is locked to, oxide has no tagged release yet
+        let rev = "04870ccd637776cc43ec0ea4e4311ef134bd06a1";
+        let src =
+            self.forges
+                .fetch_from_github(ForgeArgs::new("OxidePM", "oxide", rev, fake_hash()));
+        self.rust_platform
+            .build_rust_package(RustPackageArgs::new("oxide", version, src, fake_hash()))
+            .build()
+    }
+}
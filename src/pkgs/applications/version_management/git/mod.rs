@@ -0,0 +1,48 @@
+use crate::{build::fetchurl::FetchUrl, lib::fake_hash, stdenv::Stdenv};
+use oxide_core::prelude::*;
+
+pub struct Git {
+    pub stdenv: Stdenv,
+    pub fetchurl: FetchUrl,
+    pub zlib: LazyDrv,
+    pub curl: LazyDrv,
+}
+
+impl IntoDrv for Git {
+    fn into_drv(self) -> Drv {
+        let version = "2.51.0";
+        self.stdenv
+            .make_derivation()
+            .name("git")
+            .version(version)
+            .src(self.fetchurl.fetch(
+                format!("mirror://kernel/software/scm/git/git-{version}.tar.xz"),
+                fake_hash(),
+            ))
+            .input_bool("STRICT_DEPS", true)
+            .input_bool("ENABLE_PARALLEL_BUILDING", true)
+            .dep_build_host(LazyDrv::clone(&self.curl))
+            // TODO: hacks to get around missing cc wrapper
+            .input("zlibDev", self.zlib.out("dev"))
+            .input("zlibOut", self.zlib.out("out"))
+            .input("curlDev", self.curl.out("dev"))
+            .input("curlOut", self.curl.out("out"))
+            .pre_configure(
+                r#"export CPPFLAGS="-I$zlibDev/include -I$curlDev/include"
+export LDFLAGS="-L$zlibOut/lib -L$curlOut/lib""#,
+            )
+            .configure_flags("--without-tcltk")
+            .make_flags(
+                [
+                    "NO_GETTEXT=1",
+                    "NO_PYTHON=1",
+                    "NO_PERL=1",
+                    "NO_EXPAT=1",
+                    "NO_OPENSSL=1",
+                    "INSTALL_SYMLINKS=1",
+                ]
+                .join(" "),
+            )
+            .build()
+    }
+}
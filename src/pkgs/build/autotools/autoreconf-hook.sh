@@ -0,0 +1,18 @@
+# shellcheck shell=bash disable=SC2154
+#
+# Regenerates configure and the Makefile.in files with autoreconf before the
+# configure phase, AUTORECONF_FLAGS replaces the default -fi.
+
+autoreconf_phase() {
+    run_hook PRE_AUTORECONF
+
+    local -a flags_array=()
+    concatTo flags_array AUTORECONF_FLAGS=-fi
+    echo "autoreconf flags: ${flags_array[*]}"
+    autoreconf "${flags_array[@]}"
+    unset flags_array
+
+    run_hook POST_AUTORECONF
+}
+
+PRE_CONFIGURE_PHASES+=(AUTORECONF_PHASE)
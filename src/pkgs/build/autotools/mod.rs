@@ -0,0 +1,49 @@
+use crate::stdenv::Stdenv;
+use oxide_core::prelude::*;
+
+// Runs autoreconf before configure, for sources without a generated
+// configure or patched build files. Brings autoconf, automake and libtool
+pub struct AutoreconfHook {
+    pub stdenv: Stdenv,
+    pub autoconf: LazyDrv,
+    pub automake: LazyDrv,
+    pub libtool: LazyDrv,
+}
+
+impl IntoDrv for AutoreconfHook {
+    fn into_drv(self) -> Drv {
+        self.stdenv
+            .make_derivation()
+            .name("autoreconf-hook")
+            .dont_unpack()
+            .input("SETUP_HOOK", local_file!("autoreconf-hook.sh"))
+            .propagated_host_target(self.autoconf)
+            .propagated_host_target(self.automake)
+            .propagated_host_target(self.libtool)
+            .build()
+    }
+}
+
+// Refreshes the config.sub and config.guess of the source before configure
+pub struct UpdateAutotoolsGnuConfigScriptsHook {
+    pub stdenv: Stdenv,
+    pub gnu_config: LazyDrv,
+}
+
+impl IntoDrv for UpdateAutotoolsGnuConfigScriptsHook {
+    fn into_drv(self) -> Drv {
+        self.stdenv
+            .make_derivation()
+            .name("update-autotools-gnu-config-scripts-hook")
+            .dont_unpack()
+            .input(
+                "SETUP_HOOK",
+                local_file!("update-autotools-gnu-config-scripts-hook.sh"),
+            )
+            .input("GNU_CONFIG", self.gnu_config)
+            .post_fix(
+                r#"substituteInPlace "$out/nix-support/setup-hook" --replace-fail @gnu_config@ "$GNU_CONFIG""#,
+            )
+            .build()
+    }
+}
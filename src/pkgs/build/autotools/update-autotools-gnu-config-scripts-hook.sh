@@ -0,0 +1,19 @@
+# shellcheck shell=bash
+#
+# Replaces every config.sub and config.guess of the source tree with the ones
+# of gnu-config before the configure phase, so that ./configure knows the
+# newer architectures. Set DONT_UPDATE_AUTOTOOLS_GNU_CONFIG_SCRIPTS to opt out.
+
+update_autotools_gnu_config_scripts_phase() {
+    if [ -n "${DONT_UPDATE_AUTOTOOLS_GNU_CONFIG_SCRIPTS:-}" ]; then return; fi
+
+    local script file
+    for script in config.sub config.guess; do
+        while IFS= read -r -d '' file; do
+            echo "updating $file"
+            cp -f "@gnu_config@/$script" "$file"
+        done < <(find . -type f -name "$script" -print0)
+    done
+}
+
+PRE_CONFIGURE_PHASES+=(UPDATE_AUTOTOOLS_GNU_CONFIG_SCRIPTS_PHASE)
@@ -0,0 +1,59 @@
+# shellcheck shell=bash disable=SC2154
+#
+# Links the files of $PATHS under $PATHS_TO_LINK into $out, directories are
+# merged and the first path providing a file wins. Collisions between
+# different files are ignored, reported or fatal following $COLLISIONS.
+
+# whether $1 is one of $PATHS_TO_LINK or below one of them
+is_linked() {
+    local rel="$1" path
+    for path in $PATHS_TO_LINK; do
+        path="${path%/}"
+        if [ -z "$path" ] || [ "$rel" = "$path" ] || [[ "$rel" == "$path"/* ]]; then
+            return 0
+        fi
+    done
+    return 1
+}
+
+declare -A provider
+collided=
+
+mkdir -p "$out"
+for path in $PATHS; do
+    while IFS= read -r -d '' file; do
+        rel="${file#"$path"}"
+        if { [ -d "$file" ] && [ ! -L "$file" ]; } || ! is_linked "$rel"; then
+            continue
+        fi
+
+        if [ -n "${provider[$rel]:-}" ]; then
+            existing="${provider[$rel]}$rel"
+            if [ "$(readlink -f "$existing")" = "$(readlink -f "$file")" ] \
+                || cmp -s "$existing" "$file"; then
+                continue
+            fi
+            case "${COLLISIONS:-error}" in
+                ignore) ;;
+                warn) echo "warning: collision between '$existing' and '$file'" ;;
+                *)
+                    echo "error: collision between '$existing' and '$file'"
+                    collided=1
+                    ;;
+            esac
+            continue
+        fi
+
+        mkdir -p "$(dirname "$out$rel")"
+        ln -s "$file" "$out$rel"
+        provider[$rel]="$path"
+    done < <(find "$path/" -mindepth 1 -print0 | sort -z)
+done
+
+if [ -n "$collided" ]; then
+    echo "hint: set collisions to Collisions::Warn or Collisions::Ignore to keep the first file"
+    exit 1
+fi
+
+cd "$out"
+run_hook POST_BUILD
@@ -0,0 +1,113 @@
+use crate::{build::trivial_builders::TrivialBuilders, stdenv::Stdenv};
+use oxide_core::prelude::*;
+
+// What to do when two paths provide the same file, the first one is kept
+#[derive(Clone, Copy, Default)]
+pub enum Collisions {
+    Ignore,
+    Warn,
+    #[default]
+    Error,
+}
+
+impl Collisions {
+    fn as_str(self) -> &'static str {
+        match self {
+            Collisions::Ignore => "ignore",
+            Collisions::Warn => "warn",
+            Collisions::Error => "error",
+        }
+    }
+}
+
+// The outputs of `paths` merged into a single tree of symlinks, like a
+// user profile or the root of a container
+pub struct BuildEnv {
+    pub stdenv: Stdenv,
+    pub name: Cow<str>,
+    pub paths: Vec<LazyDrv>,
+    // e.g. /bin or /share/man, everything when empty
+    pub paths_to_link: Vec<Cow<str>>,
+    // installed for every path besides out, all of them must have these outputs
+    pub extra_outputs_to_install: Vec<Cow<str>>,
+    pub collisions: Collisions,
+    pub post_build: Option<Cow<str>>,
+}
+
+impl BuildEnv {
+    pub fn new<N>(stdenv: Stdenv, name: N) -> Self
+    where
+        N: Into<Cow<str>>,
+    {
+        Self {
+            stdenv,
+            name: name.into(),
+            paths: Vec::new(),
+            paths_to_link: Vec::new(),
+            extra_outputs_to_install: Vec::new(),
+            collisions: Collisions::default(),
+            post_build: None,
+        }
+    }
+
+    // earlier paths win the collisions
+    pub fn path(mut self, path: LazyDrv) -> Self {
+        self.paths.push(path);
+        self
+    }
+
+    pub fn path_to_link<T>(mut self, path: T) -> Self
+    where
+        T: Into<Cow<str>>,
+    {
+        self.paths_to_link.push(path.into());
+        self
+    }
+
+    pub fn extra_output_to_install<T>(mut self, output: T) -> Self
+    where
+        T: Into<Cow<str>>,
+    {
+        self.extra_outputs_to_install.push(output.into());
+        self
+    }
+
+    pub fn collisions(mut self, collisions: Collisions) -> Self {
+        self.collisions = collisions;
+        self
+    }
+
+    // runs in $out once everything is linked
+    pub fn post_build<T>(mut self, post_build: T) -> Self
+    where
+        T: Into<Cow<str>>,
+    {
+        self.post_build = Some(post_build.into());
+        self
+    }
+}
+
+impl IntoDrv for BuildEnv {
+    fn into_drv(self) -> Drv {
+        let mut paths: Vec<Expr> = Vec::new();
+        for path in &self.paths {
+            paths.push(LazyDrv::clone(path).into());
+            for output in &self.extra_outputs_to_install {
+                paths.push(path.out(output.to_string()));
+            }
+        }
+        let paths_to_link = if self.paths_to_link.is_empty() {
+            "/".to_string()
+        } else {
+            self.paths_to_link.join(" ")
+        };
+        TrivialBuilders::new(self.stdenv)
+            .make_derivation(self.name)
+            .builder(local_file!("builder.sh"))
+            .input("PATHS", paths)
+            .input("PATHS_TO_LINK", paths_to_link)
+            .input("COLLISIONS", self.collisions.as_str())
+            .input_if("POST_BUILD", self.post_build)
+            .build()
+    }
+}
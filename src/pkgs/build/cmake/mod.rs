@@ -0,0 +1,29 @@
+use crate::{build::fetchurl::FetchUrl, lib::fake_hash, stdenv::Stdenv};
+use oxide_core::prelude::*;
+
+pub struct CMake {
+    pub stdenv: Stdenv,
+    pub fetchurl: FetchUrl,
+}
+
+impl IntoDrv for CMake {
+    fn into_drv(self) -> Drv {
+        let version = "4.1.1";
+        self.stdenv
+            .make_derivation()
+            .name("cmake")
+            .version(version)
+            .src(self.fetchurl.fetch(
+                format!(
+                    "https://github.com/Kitware/CMake/releases/download/v{version}/cmake-{version}.tar.gz"
+                ),
+                fake_hash(),
+            ))
+            // ./configure is cmake's bootstrap script, the flags after -- go to cmake
+            // TODO: build with openssl once it is packaged
+            .configure_flags("--no-system-libs -- -DCMAKE_USE_OPENSSL=OFF -DBUILD_TESTING=OFF")
+            .input_bool("ENABLE_PARALLEL_BUILDING", true)
+            .input("SETUP_HOOK", local_file!("setup-hook.sh"))
+            .build()
+    }
+}
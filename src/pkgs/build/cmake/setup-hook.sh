@@ -0,0 +1,58 @@
+# shellcheck shell=bash disable=SC2154
+#
+# Configure out of tree with `cmake -G Ninja` instead of ./configure,
+# set DONT_USE_CMAKE_CONFIGURE to opt out.
+# Needs ninja in dep_build_host to build the generated project.
+
+cmake_configure_phase() {
+    run_hook PRE_CONFIGURE
+
+    local source_dir="$PWD${CMAKE_DIR:+/$CMAKE_DIR}"
+    : "${CMAKE_BUILD_DIR:=build}"
+    mkdir -p "$CMAKE_BUILD_DIR"
+    cd "$CMAKE_BUILD_DIR"
+
+    local bin_dir dev_dir lib_dir doc_dir man_dir info_dir
+    bin_dir=$(get_output_dir OUTPUT_BIN bin)
+    dev_dir=$(get_output_dir OUTPUT_DEV dev)
+    lib_dir=$(get_output_dir OUTPUT_LIB lib)
+    doc_dir=$(get_output_dir OUTPUT_DOC doc)
+    man_dir=$(get_output_dir OUTPUT_MAN man doc)
+    info_dir=$(get_output_dir OUTPUT_INFO info doc)
+
+    local -a flags_array=(
+        -G Ninja
+        -DCMAKE_BUILD_TYPE="${CMAKE_BUILD_TYPE:-Release}"
+        -DCMAKE_INSTALL_PREFIX="$prefix"
+        -DCMAKE_INSTALL_BINDIR="$bin_dir/bin"
+        -DCMAKE_INSTALL_SBINDIR="$bin_dir/sbin"
+        -DCMAKE_INSTALL_INCLUDEDIR="$dev_dir/include"
+        -DCMAKE_INSTALL_LIBDIR="$lib_dir/lib"
+        -DCMAKE_INSTALL_LIBEXECDIR="$lib_dir/libexec"
+        -DCMAKE_INSTALL_DOCDIR="$doc_dir/share/doc/$name"
+        -DCMAKE_INSTALL_MANDIR="$man_dir/share/man"
+        -DCMAKE_INSTALL_INFODIR="$info_dir/share/info"
+        -DCMAKE_INSTALL_LOCALEDIR="$lib_dir/share/locale"
+        # the store has no /usr or /opt to look into
+        -DCMAKE_FIND_USE_SYSTEM_ENVIRONMENT_PATH=OFF
+        -DCMAKE_INSTALL_RPATH_USE_LINK_PATH=ON
+        -DCMAKE_EXPORT_NO_PACKAGE_REGISTRY=ON
+        -DCMAKE_FIND_USE_PACKAGE_REGISTRY=OFF
+    )
+    concatTo flags_array CMAKE_CROSS_FLAGS CMAKE_FLAGS
+
+    echo "cmake flags: ${flags_array[*]}"
+    cmake "$source_dir" "${flags_array[@]}"
+
+    run_hook POST_CONFIGURE
+}
+
+if [ -z "${DONT_USE_CMAKE_CONFIGURE:-}" ] && [ -z "${CONFIGURE_PHASE:-}" ]; then
+    CONFIGURE_PHASE=cmake_configure_phase
+fi
+
+_cmake_add_prefix_path() {
+    addToSearchPath CMAKE_PREFIX_PATH "$1"
+}
+
+addEnvHooks "$targetOffset" _cmake_add_prefix_path
@@ -1,33 +1,36 @@
 use crate::build::fetchurl::FetchUrl;
+use crate::lib::fake_hash;
 use crate::stdenv::Stdenv;
-use oxide_core::{
-    drv::{Drv, IntoDrv, LazyDrv},
-    hash,
-};
+use oxide_core::drv::{Drv, IntoDrv, LazyDrv};
 
 pub struct Curl {
     pub stdenv: Stdenv,
     pub fetchurl: FetchUrl,
-    // TODO: pkg-config
     pub pkg_config: LazyDrv,
     pub perl: LazyDrv,
+    // None keeps the config scripts of the tarball, the bootstrap of the
+    // fetchurl gnu-config is fetched with needs it
+    pub update_autotools_gnu_config_scripts_hook: Option<LazyDrv>,
 }
 
 impl IntoDrv for Curl {
     fn into_drv(self) -> Drv {
         let version = "8.14.1";
+        // without the hook the config.guess of the tarball is kept, it
+        // calls /usr/bin/uname which is not in the sandbox
+        let uname = match self.update_autotools_gnu_config_scripts_hook {
+            Some(_) => "",
+            None => "substituteInPlace ./config.guess --replace-fail /usr/bin/uname uname\n",
+        };
         self.stdenv
             .make_derivation()
             .name("curl")
             .version(version)
             .src(self.fetchurl.fetch(
                 format!("https://curl.haxx.se/download/curl-{version}.tar.xz"),
-                hash!("sha512:YzJSc1lXcHFabXhyWVhOa2FtWnNZWE5xWm14ellXcG1jMjNSc2FtWnNjMlJxYkdaaGFuTnNhMlpoYzJSclphcw"),
+                fake_hash(),
             ))
-            // TODO: use indoc
-            .post_patch(
-r"substituteInPlace ./config.guess --replace-fail /usr/bin/uname uname
-patchShebangs scripts")
+            .post_patch(format!("{uname}patchShebangs scripts"))
             .out("bin")
             .out("dev")
             .out("out")
@@ -37,22 +40,22 @@ patchShebangs scripts")
             .input("STRICT_DEPS", "1")
             .dep_build_host(self.pkg_config)
             .dep_build_host(self.perl)
+            .dep_build_host_if(self.update_autotools_gnu_config_scripts_hook)
             // TODO: add all optionals dep_build_host
             .pre_configure(
-r"sed -e 's|/usr/bin|/no-such-path|g' -i.bak configure
-rm src/tool_hugehelp.c")
-            .configure_flags([
-                "--enable-versioned-symbols",
-                "--disable-manual"
-            ].join(" "))
+                r"sed -e 's|/usr/bin|/no-such-path|g' -i.bak configure
+rm src/tool_hugehelp.c",
+            )
+            .configure_flags(["--enable-versioned-symbols", "--disable-manual"].join(" "))
             .input("CXX", format!("{}c++", "TODO"))
             .input("CXXCPP", format!("{}c++ -E", "TODO"))
             .do_check()
             .pre_check("patchShebangs tests/")
             .post_install(
-r#"moveToOutput bin/curl-config "$dev"
+                r#"moveToOutput bin/curl-config "$dev"
 # Install completions
-make -C scripts install"#)
+make -C scripts install"#,
+            )
             .build()
     }
 }
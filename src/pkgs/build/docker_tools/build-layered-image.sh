@@ -0,0 +1,207 @@
+# shellcheck shell=bash disable=SC2154
+#
+# Writes an OCI image archive of the runtime closure of $CONTENTS to $out.
+# The most popular store paths get a layer of their own and the rest share
+# the last store layer, the root symlinks of the contents come on top.
+
+# fixed so that the same closure gives the same image
+created="1970-01-01T00:00:01Z"
+tar_flags=(--sort=name --mtime=@1 --owner=0 --group=0 --numeric-owner --format=gnu)
+
+json_string() {
+    local s="$1"
+    s="${s//\\/\\\\}"
+    s="${s//\"/\\\"}"
+    s="${s//$'\n'/\\n}"
+    s="${s//$'\r'/\\r}"
+    s="${s//$'\t'/\\t}"
+    printf '"%s"' "$s"
+}
+
+# the arguments as a json array of strings
+json_array() {
+    local sep= item
+    printf '['
+    for item in "$@"; do
+        printf '%s' "$sep"
+        json_string "$item"
+        sep=,
+    done
+    printf ']'
+}
+
+# writes $1 to blobs/sha256 and prints its descriptor with media type $2
+add_blob() {
+    local digest size
+    digest="$(sha256sum "$1" | cut -d' ' -f1)"
+    size="$(stat -c %s "$1")"
+    mv "$1" "image/blobs/sha256/$digest"
+    printf '{"mediaType":"%s","digest":"sha256:%s","size":%s}' "$2" "$digest" "$size"
+}
+
+read -ra contents <<< "$CONTENTS"
+if [ "${#contents[@]}" -eq 0 ]; then
+    echo "error: $name has no contents"
+    exit 1
+fi
+if ((MAX_LAYERS < 2)); then
+    echo "error: max_layers of $name is $MAX_LAYERS, it needs at least 2"
+    echo "hint: one layer for the store paths and one for the contents"
+    exit 1
+fi
+store_dir="$(dirname "${contents[0]}")"
+store_pattern="$(printf '%s' "$store_dir" | sed 's/[][\.*^$+?(){}|]/\\&/g')/[^/[:space:]\"'():;,<>=]+"
+
+# the store paths named by the files and symlinks of $1
+references() {
+    {
+        find "$1" -type f -print0 | xargs -0r grep -aohE "$store_pattern" || true
+        find "$1" -type l -printf '%l\n' | grep -oE "$store_pattern" || true
+    } | sort -u | while read -r ref; do
+        if [ "$ref" != "$1" ] && [ -e "$ref" ]; then
+            echo "$ref"
+        fi
+    done
+}
+
+declare -A refs
+closure=()
+queue=("${contents[@]}")
+while [ "${#queue[@]}" -gt 0 ]; do
+    path="${queue[0]}"
+    queue=("${queue[@]:1}")
+    if [ -n "${refs[$path]+set}" ]; then
+        continue
+    fi
+    refs[$path]="$(references "$path" | tr '\n' ' ')"
+    closure+=("$path")
+    # shellcheck disable=SC2206
+    queue+=(${refs[$path]})
+done
+
+# the popularity of a path is the number of paths of the closure
+# depending on it, itself included
+declare -A popularity
+for path in "${closure[@]}"; do
+    unset seen
+    declare -A seen
+    stack=("$path")
+    while [ "${#stack[@]}" -gt 0 ]; do
+        dep="${stack[-1]}"
+        unset 'stack[-1]'
+        if [ -n "${seen[$dep]:-}" ]; then
+            continue
+        fi
+        seen[$dep]=1
+        popularity[$dep]=$((${popularity[$dep]:-0} + 1))
+        # shellcheck disable=SC2206
+        stack+=(${refs[$dep]})
+    done
+done
+
+mkdir -p layers image/blobs/sha256
+store_layers=$((MAX_LAYERS - 1))
+i=0
+for path in "${closure[@]}"; do
+    printf '%s %s\n' "${popularity[$path]}" "$path"
+done | LC_ALL=C sort -k1,1nr -k2 | cut -d' ' -f2- | while read -r path; do
+    layer=$((i < store_layers ? i : store_layers - 1))
+    echo "$path" >> "layers/$layer"
+    i=$((i + 1))
+done
+
+# the directories leading to the store
+parents=()
+dir="$store_dir"
+while [ "$dir" != / ]; do
+    parents+=("${dir#/}")
+    dir="$(dirname "$dir")"
+done
+
+descriptors=()
+diff_ids=()
+add_layer() {
+    local descriptor
+    descriptor="$(add_blob "$1" application/vnd.oci.image.layer.v1.tar)"
+    descriptors+=("$descriptor")
+    diff_ids+=("$(json_string "$(echo "$descriptor" | grep -o 'sha256:[0-9a-f]*')")")
+}
+
+for layer in $(find layers -type f -printf '%f\n' | sort -n); do
+    {
+        printf '%s\n' "${parents[@]}"
+        while read -r path; do
+            find "$path" -printf '%P\n' | sed "s|^|${path#/}/|; s|/$||"
+        done < "layers/$layer"
+    } | LC_ALL=C sort -u \
+        | tar -C / --create --file "layer.tar" --no-recursion --verbatim-files-from \
+            --files-from - "${tar_flags[@]}"
+    add_layer layer.tar
+done
+
+# the contents linked at the root of the image
+mkdir root
+for path in "${contents[@]}"; do
+    while IFS= read -r -d '' file; do
+        rel="${file#"$path"/}"
+        if [ -d "$file" ] && [ ! -L "$file" ]; then
+            mkdir -p "root/$rel"
+        elif [ ! -e "root/$rel" ] && [ ! -L "root/$rel" ]; then
+            ln -s "$file" "root/$rel"
+        fi
+    done < <(find "$path/" -mindepth 1 -print0)
+done
+find root -mindepth 1 -printf '%P\n' | LC_ALL=C sort \
+    | tar -C root --create --file layer.tar --no-recursion --verbatim-files-from \
+        --files-from - "${tar_flags[@]}"
+add_layer layer.tar
+
+entrypoint=()
+for ((i = 0; ; i++)); do
+    var="ENTRYPOINT_$i"
+    [ -n "${!var+set}" ] || break
+    entrypoint+=("${!var}")
+done
+env=()
+for ((i = 0; ; i++)); do
+    var="ENV_NAME_$i"
+    value="ENV_VALUE_$i"
+    [ -n "${!var+set}" ] || break
+    env+=("${!var}=${!value}")
+done
+
+config="\"Env\":$(json_array "${env[@]}")"
+if [ "${#entrypoint[@]}" -gt 0 ]; then
+    config+=",\"Entrypoint\":$(json_array "${entrypoint[@]}")"
+fi
+if [ -n "${IMAGE_USER:-}" ]; then
+    config+=",\"User\":$(json_string "$IMAGE_USER")"
+fi
+if [ -n "${EXPOSED_PORTS:-}" ]; then
+    ports=()
+    for port in $EXPOSED_PORTS; do
+        ports+=("$(json_string "$port"):{}")
+    done
+    config+=",\"ExposedPorts\":{$(IFS=,; echo "${ports[*]}")}"
+fi
+
+history=()
+for _ in "${diff_ids[@]}"; do
+    history+=("{\"created\":\"$created\",\"created_by\":\"oxide build_layered_image\"}")
+done
+
+printf '{"created":"%s","architecture":"%s","os":"linux","config":{%s},"rootfs":{"type":"layers","diff_ids":[%s]},"history":[%s]}' \
+    "$created" "$ARCHITECTURE" "$config" \
+    "$(IFS=,; echo "${diff_ids[*]}")" "$(IFS=,; echo "${history[*]}")" > config.json
+config_descriptor="$(add_blob config.json application/vnd.oci.image.config.v1+json)"
+
+printf '{"schemaVersion":2,"mediaType":"application/vnd.oci.image.manifest.v1+json","config":%s,"layers":[%s]}' \
+    "$config_descriptor" "$(IFS=,; echo "${descriptors[*]}")" > manifest.json
+manifest_descriptor="$(add_blob manifest.json application/vnd.oci.image.manifest.v1+json)"
+
+printf '{"schemaVersion":2,"manifests":[%s,"annotations":{"io.containerd.image.name":%s,"org.opencontainers.image.ref.name":%s}}]}' \
+    "${manifest_descriptor%\}}" "$(json_string "$IMAGE_NAME:$IMAGE_TAG")" \
+    "$(json_string "$IMAGE_TAG")" > image/index.json
+printf '{"imageLayoutVersion":"1.0.0"}' > image/oci-layout
+
+tar -C image --create --file "$out" "${tar_flags[@]}" oci-layout index.json blobs
@@ -0,0 +1,134 @@
+use crate::stdenv::Stdenv;
+use oxide_core::prelude::*;
+
+// The architecture as named by the OCI image config
+pub fn oci_arch(system: System) -> &'static str {
+    match system {
+        System::x86_64_linux => "amd64",
+        System::i686_linux => "386",
+        System::aarch64_linux => "arm64",
+        _ => unimplemented!(),
+    }
+}
+
+#[derive(Clone)]
+pub struct DockerTools {
+    pub stdenv: Stdenv,
+}
+
+impl DockerTools {
+    pub fn new(stdenv: Stdenv) -> Self {
+        Self { stdenv }
+    }
+
+    // An OCI image archive, to be loaded with e.g. `podman load` or
+    // `skopeo copy oci-archive:...`, of the runtime closure of the contents
+    pub fn build_layered_image(&self, args: LayeredImageArgs) -> LazyDrv {
+        let mut builder = self
+            .stdenv
+            .make_derivation()
+            .name(format!("docker-image-{}.tar", args.name))
+            .builder(local_file!("build-layered-image.sh"))
+            .input("IMAGE_NAME", args.name)
+            .input("IMAGE_TAG", args.tag)
+            .input("CONTENTS", args.contents)
+            .input("MAX_LAYERS", args.max_layers.to_string())
+            .input("ARCHITECTURE", oci_arch(self.stdenv.host_platform))
+            .input_if("IMAGE_USER", args.user)
+            .input("EXPOSED_PORTS", args.exposed_ports.join(" "));
+        // one input each, the arguments may contain spaces
+        for (i, arg) in args.entrypoint.into_iter().enumerate() {
+            builder = builder.input(format!("ENTRYPOINT_{i}"), arg);
+        }
+        for (i, (var, value)) in args.env.into_iter().enumerate() {
+            builder = builder
+                .input(format!("ENV_NAME_{i}"), var)
+                .input(format!("ENV_VALUE_{i}"), value);
+        }
+        builder.lazy()
+    }
+}
+
+pub struct LayeredImageArgs {
+    pub name: Cow<str>,
+    pub tag: Cow<str>,
+    // linked at the root of the image, e.g. /bin
+    pub contents: Vec<LazyDrv>,
+    // the last layer holds the root links, the one before every store path
+    // not popular enough to have its own
+    pub max_layers: usize,
+    pub entrypoint: Vec<Expr>,
+    pub env: Vec<(Cow<str>, Expr)>,
+    pub user: Option<Cow<str>>,
+    // e.g. 80/tcp
+    pub exposed_ports: Vec<Cow<str>>,
+}
+
+impl LayeredImageArgs {
+    pub fn new<N>(name: N) -> Self
+    where
+        N: Into<Cow<str>>,
+    {
+        Self {
+            name: name.into(),
+            tag: "latest".into(),
+            contents: Vec::new(),
+            max_layers: 100,
+            entrypoint: Vec::new(),
+            env: Vec::new(),
+            user: None,
+            exposed_ports: Vec::new(),
+        }
+    }
+
+    pub fn tag<T>(mut self, tag: T) -> Self
+    where
+        T: Into<Cow<str>>,
+    {
+        self.tag = tag.into();
+        self
+    }
+
+    pub fn content(mut self, content: LazyDrv) -> Self {
+        self.contents.push(content);
+        self
+    }
+
+    pub fn max_layers(mut self, max_layers: usize) -> Self {
+        self.max_layers = max_layers;
+        self
+    }
+
+    pub fn entrypoint<T>(mut self, arg: T) -> Self
+    where
+        T: Into<Expr>,
+    {
+        self.entrypoint.push(arg.into());
+        self
+    }
+
+    pub fn env<K, V>(mut self, var: K, value: V) -> Self
+    where
+        K: Into<Cow<str>>,
+        V: Into<Expr>,
+    {
+        self.env.push((var.into(), value.into()));
+        self
+    }
+
+    pub fn user<T>(mut self, user: T) -> Self
+    where
+        T: Into<Cow<str>>,
+    {
+        self.user = Some(user.into());
+        self
+    }
+
+    pub fn exposed_port<T>(mut self, port: T) -> Self
+    where
+        T: Into<Cow<str>>,
+    {
+        self.exposed_ports.push(port.into());
+        self
+    }
+}
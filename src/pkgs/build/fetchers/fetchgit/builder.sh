@@ -0,0 +1,78 @@
+# shellcheck shell=bash disable=SC2154
+#
+# Check out a single revision of a git repository into $out so that the
+# result only depends on the revision: no .git unless asked for and
+# normalised permissions and timestamps.
+
+source "$NORMALISE"
+
+export HOME="$TMPDIR"
+export GIT_CONFIG_NOSYSTEM=1
+
+# git reads the credentials of private repositories from ~/.netrc
+if [ -n "${NETRC_PHASE:-}" ]; then
+    eval "$NETRC_PHASE"
+    mv netrc "$HOME/.netrc"
+fi
+
+# we check the hash of the output anyway
+if ! [ -f "${SSL_CERT_FILE:-}" ]; then
+    export GIT_SSL_NO_VERIFY=1
+else
+    export GIT_SSL_CAINFO="$SSL_CERT_FILE"
+fi
+
+if [ -n "${TAG:-}" ]; then
+    ref="refs/tags/$TAG"
+else
+    ref="$REV"
+fi
+
+depth=()
+if [ -z "${DEEP_CLONE:-}" ]; then
+    depth=(--depth 1)
+fi
+
+mkdir -p "$out"
+cd "$out"
+git init -q
+git remote add origin "$url"
+
+if [ -n "${SPARSE_CHECKOUT:-}" ]; then
+    # shellcheck disable=SC2086
+    git sparse-checkout set --no-cone $SPARSE_CHECKOUT
+fi
+
+echo "fetching $ref from $url"
+# not every server lets us fetch a commit by hash,
+# fall back to fetching every branch and tag
+if git fetch -q "${depth[@]}" origin "$ref"; then
+    commit=$(git rev-parse "FETCH_HEAD^{commit}")
+else
+    git fetch -q --tags origin "+refs/heads/*:refs/remotes/origin/*"
+    commit=$(git rev-parse "$ref^{commit}")
+fi
+git checkout -q -b fetchgit "$commit"
+
+if [ -n "${FETCH_SUBMODULES:-}" ]; then
+    git submodule update -q --init --recursive "${depth[@]}"
+fi
+
+if [ -n "${LEAVE_DOT_GIT:-}" ]; then
+    # only keep what is needed to use the repository and repack it
+    # so that the object store does not depend on how it was fetched
+    git for-each-ref --format='%(refname)' | while read -r name; do
+        if [ "$name" != refs/heads/fetchgit ]; then
+            git update-ref -d "$name"
+        fi
+    done
+    git config --unset-all remote.origin.fetch || true
+    rm -rf .git/logs .git/hooks .git/index .git/FETCH_HEAD .git/ORIG_HEAD
+    git -c pack.threads=1 repack -q -A -d -f
+    git -c gc.auto=0 prune --expire now
+    git reflog expire --expire=all --all
+else
+    find "$out" -name .git -prune -exec rm -rf {} +
+fi
+
+normalise_tree "$out"
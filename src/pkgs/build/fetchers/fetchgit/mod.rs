@@ -0,0 +1,158 @@
+use crate::{build::fetchurl::Netrc, stdenv::Stdenv};
+use oxide_core::prelude::*;
+
+#[derive(Clone)]
+pub struct FetchGit {
+    pub stdenv_no_cc: Stdenv,
+    pub git: LazyDrv,
+}
+
+impl FetchGit {
+    pub fn new(stdenv_no_cc: Stdenv, git: LazyDrv) -> Self {
+        Self { stdenv_no_cc, git }
+    }
+
+    pub fn fetch<U, R>(&self, url: U, rev: R, hash: Hash) -> LazyDrv
+    where
+        U: Into<Cow<str>>,
+        R: Into<Cow<str>>,
+    {
+        self.fetch_with(FetchGitArgs::new(url, hash).rev(rev))
+    }
+
+    pub fn fetch_with(&self, args: FetchGitArgs) -> LazyDrv {
+        assert!(
+            args.rev.is_some() != args.tag.is_some(),
+            "fetchgit {} needs exactly one of rev or tag",
+            args.url
+        );
+        LazyDrv::new(FetchGitParam {
+            stdenv_no_cc: self.stdenv_no_cc.clone(),
+            git: LazyDrv::clone(&self.git),
+            args,
+        })
+    }
+}
+
+pub struct FetchGitArgs {
+    pub name: Option<Cow<str>>,
+    pub url: Cow<str>,
+    pub rev: Option<Cow<str>>,
+    pub tag: Option<Cow<str>>,
+    pub hash: Hash,
+    pub fetch_submodules: bool,
+    pub deep_clone: bool,
+    pub leave_dot_git: bool,
+    pub sparse_checkout: Vec<Cow<str>>,
+    pub netrc: Option<Netrc>,
+}
+
+impl FetchGitArgs {
+    pub fn new<T>(url: T, hash: Hash) -> Self
+    where
+        T: Into<Cow<str>>,
+    {
+        Self {
+            name: None,
+            url: url.into(),
+            rev: None,
+            tag: None,
+            hash,
+            fetch_submodules: false,
+            deep_clone: false,
+            leave_dot_git: false,
+            sparse_checkout: Vec::new(),
+            netrc: None,
+        }
+    }
+
+    pub fn name<T>(mut self, name: T) -> Self
+    where
+        T: Into<Cow<str>>,
+    {
+        self.name = Some(name.into());
+        self
+    }
+
+    pub fn rev<T>(mut self, rev: T) -> Self
+    where
+        T: Into<Cow<str>>,
+    {
+        self.rev = Some(rev.into());
+        self
+    }
+
+    pub fn tag<T>(mut self, tag: T) -> Self
+    where
+        T: Into<Cow<str>>,
+    {
+        self.tag = Some(tag.into());
+        self
+    }
+
+    pub fn fetch_submodules(mut self) -> Self {
+        self.fetch_submodules = true;
+        self
+    }
+
+    pub fn deep_clone(mut self) -> Self {
+        self.deep_clone = true;
+        self
+    }
+
+    // keeping .git makes the hash depend on how git packs objects,
+    // only use it when the build really needs the history
+    pub fn leave_dot_git(mut self) -> Self {
+        self.leave_dot_git = true;
+        self
+    }
+
+    pub fn sparse_checkout<T>(mut self, path: T) -> Self
+    where
+        T: Into<Cow<str>>,
+    {
+        self.sparse_checkout.push(path.into());
+        self
+    }
+
+    pub fn netrc(mut self, netrc: Netrc) -> Self {
+        self.netrc = Some(netrc);
+        self
+    }
+}
+
+struct FetchGitParam {
+    stdenv_no_cc: Stdenv,
+    git: LazyDrv,
+    args: FetchGitArgs,
+}
+
+impl IntoDrv for FetchGitParam {
+    fn into_drv(self) -> Drv {
+        let args = self.args;
+        let sparse_checkout = args.sparse_checkout.join(" ");
+        let builder = self
+            .stdenv_no_cc
+            .make_derivation()
+            .name(args.name.unwrap_or("source".into()))
+            .builder(local_file!("builder.sh"))
+            .fixed_hash(args.hash)
+            .dep_build_host(self.git)
+            .input("url", args.url)
+            .input_if("REV", args.rev)
+            .input_if("TAG", args.tag)
+            .input("NORMALISE", local_file!("../normalise.sh"))
+            .input_bool("FETCH_SUBMODULES", args.fetch_submodules)
+            .input_bool("DEEP_CLONE", args.deep_clone)
+            .input_bool("LEAVE_DOT_GIT", args.leave_dot_git)
+            .input_if(
+                "SPARSE_CHECKOUT",
+                (!sparse_checkout.is_empty()).then_some(sparse_checkout),
+            );
+        match args.netrc {
+            Some(netrc) => netrc.build(builder),
+            None => builder,
+        }
+        .build()
+    }
+}
@@ -0,0 +1,134 @@
+use crate::build::fetchurl::{FetchUrl, FetchUrlArgs};
+use oxide_core::prelude::*;
+
+#[derive(Clone)]
+pub struct FetchPatch {
+    pub fetchurl: FetchUrl,
+}
+
+impl FetchPatch {
+    pub fn new(fetchurl: FetchUrl) -> Self {
+        Self { fetchurl }
+    }
+
+    pub fn fetch<T>(&self, url: T, hash: Hash) -> LazyDrv
+    where
+        T: Into<Cow<str>>,
+    {
+        self.fetch_with(FetchPatchArgs::new(url, hash))
+    }
+
+    pub fn fetch_with(&self, args: FetchPatchArgs) -> LazyDrv {
+        let mut strip_len = args.strip_len;
+        let mut extra_prefix = args.extra_prefix;
+        let mut includes = args.includes;
+        if let Some(relative) = args.relative {
+            // relative already strips the components it adds up to
+            assert!(
+                args.strip_len == 0,
+                "{}: relative and strip_len cannot be used together",
+                args.url
+            );
+            let relative = relative.trim_matches('/');
+            // drop a/ or b/ and the components of relative
+            strip_len = 1 + relative.split('/').count();
+            extra_prefix = Some(extra_prefix.unwrap_or_default());
+            includes.push(format!("{relative}/*").into());
+        }
+        let fetchurl_args = FetchUrlArgs::new(args.url, args.hash)
+            .download_to_temp()
+            .input("NORMALISE_PATCH", local_file!("normalise.awk"))
+            .input("STRIP_LEN", strip_len.to_string())
+            .input("INCLUDES", includes.join("\n"))
+            .input("EXCLUDES", args.excludes.join("\n"))
+            .post_fetch(r#"source "$FETCHPATCH""#)
+            .input("FETCHPATCH", local_file!("post-fetch.sh"));
+        let fetchurl_args = match args.name {
+            Some(name) => fetchurl_args.name(name),
+            None => fetchurl_args,
+        };
+        let fetchurl_args = match extra_prefix {
+            Some(extra_prefix) => fetchurl_args.input("EXTRA_PREFIX", extra_prefix),
+            None => fetchurl_args,
+        };
+        self.fetchurl.fetch_with(fetchurl_args)
+    }
+}
+
+pub struct FetchPatchArgs {
+    pub name: Option<Cow<str>>,
+    pub url: Cow<str>,
+    pub hash: Hash,
+    pub strip_len: usize,
+    pub extra_prefix: Option<Cow<str>>,
+    pub relative: Option<Cow<str>>,
+    pub includes: Vec<Cow<str>>,
+    pub excludes: Vec<Cow<str>>,
+}
+
+impl FetchPatchArgs {
+    pub fn new<T>(url: T, hash: Hash) -> Self
+    where
+        T: Into<Cow<str>>,
+    {
+        Self {
+            name: None,
+            url: url.into(),
+            hash,
+            strip_len: 0,
+            extra_prefix: None,
+            relative: None,
+            includes: Vec::new(),
+            excludes: Vec::new(),
+        }
+    }
+
+    pub fn name<T>(mut self, name: T) -> Self
+    where
+        T: Into<Cow<str>>,
+    {
+        self.name = Some(name.into());
+        self
+    }
+
+    pub fn strip_len(mut self, strip_len: usize) -> Self {
+        self.strip_len = strip_len;
+        self
+    }
+
+    // added after a/ and b/, usually together with strip_len
+    pub fn extra_prefix<T>(mut self, extra_prefix: T) -> Self
+    where
+        T: Into<Cow<str>>,
+    {
+        self.extra_prefix = Some(extra_prefix.into());
+        self
+    }
+
+    // only keep the files under `relative` with paths relative to it,
+    // for patches made against a monorepo that ships subdirectories,
+    // sets the strip_len so it cannot be combined with it
+    pub fn relative<T>(mut self, relative: T) -> Self
+    where
+        T: Into<Cow<str>>,
+    {
+        self.relative = Some(relative.into());
+        self
+    }
+
+    pub fn include<T>(mut self, glob: T) -> Self
+    where
+        T: Into<Cow<str>>,
+    {
+        self.includes.push(glob.into());
+        self
+    }
+
+    pub fn exclude<T>(mut self, glob: T) -> Self
+    where
+        T: Into<Cow<str>>,
+    {
+        self.excludes.push(glob.into());
+        self
+    }
+}
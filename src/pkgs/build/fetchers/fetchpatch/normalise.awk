@@ -0,0 +1,173 @@
+# Normalise a patch so that its hash does not depend on how it was generated:
+# only the ---/+++ headers and the hunks of the selected files are kept,
+# commit messages, git extended headers and index lines are dropped.
+#
+# Configured through the environment:
+#   STRIP_LEN     number of leading path components to remove
+#   EXTRA_PREFIX  prepended to paths (after a/ and b/) when set
+#   INCLUDES      newline separated globs, keep only matching files
+#   EXCLUDES      newline separated globs, drop matching files
+# globs are matched against paths without their a/ or b/ component
+#
+# Renames, copies and binary changes only exist in git extended headers,
+# they are dropped with a warning as patch cannot apply them anyway.
+
+function glob_to_regex(glob,    re, i, c) {
+    re = "^"
+    for (i = 1; i <= length(glob); i++) {
+        c = substr(glob, i, 1)
+        if (c == "*") {
+            re = re ".*"
+        } else if (c == "?") {
+            re = re "."
+        } else if (index("\\.+^$(){}|", c)) {
+            re = re "\\" c
+        } else {
+            re = re c
+        }
+    }
+    return re "$"
+}
+
+function parse_globs(globs, regexes,    raw, n, i) {
+    n = split(globs, raw, "\n")
+    for (i = 1; i <= n; i++) {
+        regexes[i] = glob_to_regex(raw[i])
+    }
+    return n
+}
+
+function matches(path, regexes, n,    i) {
+    for (i = 1; i <= n; i++) {
+        if (path ~ regexes[i]) {
+            return 1
+        }
+    }
+    return 0
+}
+
+function selected(path) {
+    sub(/^[^\/]*\//, "", path)
+    if (n_includes && !matches(path, includes, n_includes)) {
+        return 0
+    }
+    if (n_excludes && matches(path, excludes, n_excludes)) {
+        return 0
+    }
+    return 1
+}
+
+function rewrite(path, prefix,    i) {
+    if (path == "/dev/null") {
+        return path
+    }
+    for (i = 0; i < strip_len; i++) {
+        sub(/^[^\/]*\//, "", path)
+    }
+    if (has_extra_prefix) {
+        path = prefix extra_prefix path
+    }
+    return path
+}
+
+# drop the "--- " or "+++ " marker and the optional timestamp
+function path_of(line) {
+    line = substr(line, 5)
+    sub(/\t.*$/, "", line)
+    return line
+}
+
+BEGIN {
+    strip_len = ENVIRON["STRIP_LEN"] + 0
+    has_extra_prefix = ("EXTRA_PREFIX" in ENVIRON)
+    extra_prefix = ENVIRON["EXTRA_PREFIX"]
+    n_includes = parse_globs(ENVIRON["INCLUDES"], includes)
+    n_excludes = parse_globs(ENVIRON["EXCLUDES"], excludes)
+    in_file = 0
+    pending = 0
+    in_hunk = 0
+    keep = 0
+    old_path = ""
+    git_header = ""
+}
+
+# hunks announce their length, consuming exactly that many lines
+# keeps trailing mail signatures like "-- " out of the patch
+in_hunk {
+    c = substr($0, 1, 1)
+    if (c == "-") {
+        old_left--
+    } else if (c == "+") {
+        new_left--
+    } else if (c != "\\") {
+        # context, mailers sometimes eat the space of empty lines
+        old_left--
+        new_left--
+    }
+    if (keep) {
+        print
+    }
+    if (old_left <= 0 && new_left <= 0) {
+        in_hunk = 0
+    }
+    next
+}
+
+/^diff --git / {
+    git_header = substr($0, 12)
+}
+
+git_header != "" && /^(rename from |copy from |GIT binary patch|Binary files )/ {
+    kind = $1 == "rename" ? "rename" : $1 == "copy" ? "copy" : "binary change"
+    print "warning: dropping the " kind " of " git_header > "/dev/stderr"
+}
+
+/^--- / {
+    old_path = path_of($0)
+    git_header = ""
+    next
+}
+
+/^\+\+\+ / && old_path != "" {
+    new_path = path_of($0)
+    keep = selected(new_path == "/dev/null" ? old_path : new_path)
+    header = "--- " rewrite(old_path, "a/") "\n+++ " rewrite(new_path, "b/")
+    # only a header followed by a hunk is part of the diff, a commit
+    # message may quote one
+    pending = 1
+    in_file = 0
+    old_path = ""
+    next
+}
+
+/^@@ -[0-9]/ && (pending || in_file) {
+    if (pending && keep) {
+        print header
+    }
+    pending = 0
+    in_file = 1
+    n = split(substr($2, 2), range, ",")
+    old_left = n > 1 ? range[2] + 0 : 1
+    n = split(substr($3, 2), range, ",")
+    new_left = n > 1 ? range[2] + 0 : 1
+    in_hunk = old_left > 0 || new_left > 0
+    # the function name after the range depends on the diff tool
+    if (keep) {
+        print "@@ " $2 " " $3 " @@"
+    }
+    next
+}
+
+# "\ No newline at end of file" after the last line of a hunk
+/^\\/ && in_file {
+    if (keep) {
+        print
+    }
+    next
+}
+
+{
+    old_path = ""
+    pending = 0
+    in_file = 0
+}
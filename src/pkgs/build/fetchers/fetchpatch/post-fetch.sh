@@ -0,0 +1,16 @@
+# shellcheck shell=bash disable=SC2154
+#
+# Post fetch hook of fetchpatch: normalise the downloaded patch into $out.
+
+if [ ! -s "$downloadedFile" ]; then
+    echo "error: fetched patch $url is empty"
+    exit 1
+fi
+
+awk -f "$NORMALISE_PATCH" "$downloadedFile" > "$out"
+
+if [ ! -s "$out" ]; then
+    echo "error: normalised patch $url is empty"
+    echo "hint: check the includes, excludes and relative arguments"
+    exit 1
+fi
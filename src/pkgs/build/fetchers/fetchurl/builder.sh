@@ -1,5 +1,7 @@
 # source $mirrorsFile
 
+urls="$url"
+
 curlVersion=$(curl -V | head -1 | cut -d' ' -f2)
 
 # Curl flags to handle redirects, not use EPSV, handle cookies for
@@ -15,22 +17,27 @@ curl=(
     --continue-at -
     --disable-epsv
     --cookie-jar cookies
-    --user-agent "curl/$curlVersion Nixpkgs/$nixpkgsVersion"
+    --user-agent "curl/$curlVersion oxide-pkgs"
 )
 
-if ! [ -f "$SSL_CERT_FILE" ]; then
+if ! [ -f "${SSL_CERT_FILE:-}" ]; then
     curl+=(--insecure)
 fi
 
-eval "curl+=($curlOptsList)"
-
 curl+=(
-    $curlOpts
-    $NIX_CURL_FLAGS
+    ${CURL_OPTS:-}
+    ${OXIDE_CURL_FLAGS:-}
 )
 
+# credentials of private sources come from impure env vars
+# and are written to a netrc file by the netrc phase
+if [ -n "${NETRC_PHASE:-}" ]; then
+    eval "$NETRC_PHASE"
+    curl+=(--netrc-file "$PWD/netrc")
+fi
+
 downloadedFile="$out"
-if [ -n "$downloadToTemp" ]; then downloadedFile="$TMPDIR/file"; fi
+if [ -n "${DOWNLOAD_TO_TEMP:-}" ]; then downloadedFile="$TMPDIR/file"; fi
 
 
 tryDownload() {
@@ -54,29 +61,127 @@ tryDownload() {
 }
 
 
+# The NAR serialisation of a path, the hash of a fixed output
+# directory is the hash of it. Strings are prefixed with their
+# length in 8 bytes little endian and padded with zeros to 8 bytes.
+nar_int() {
+    local n="$1" i
+    for ((i = 0; i < 8; i++)); do
+        printf "\\x$(printf %02x $((n & 255)))"
+        n=$((n >> 8))
+    done
+}
+
+nar_padding() {
+    head -c $(((8 - $1 % 8) % 8)) /dev/zero
+}
+
+nar_str() {
+    local LC_ALL=C
+    nar_int "${#1}"
+    printf '%s' "$1"
+    nar_padding "${#1}"
+}
+
+nar_node() {
+    local path="$1" entry size
+    nar_str "("
+    nar_str type
+    if [ -L "$path" ]; then
+        nar_str symlink
+        nar_str target
+        nar_str "$(readlink "$path")"
+    elif [ -d "$path" ]; then
+        nar_str directory
+        # entries in byte order, dot files included
+        local LC_ALL=C
+        shopt -s dotglob nullglob
+        for entry in "$path"/*; do
+            nar_str entry
+            nar_str "("
+            nar_str name
+            nar_str "${entry##*/}"
+            nar_str node
+            nar_node "$entry"
+            nar_str ")"
+        done
+    else
+        nar_str regular
+        if [ -n "$(find "$path" -maxdepth 0 -perm /111)" ]; then
+            nar_str executable
+            nar_str ""
+        fi
+        nar_str contents
+        size=$(wc -c < "$path")
+        nar_int "$size"
+        cat "$path"
+        nar_padding "$size"
+    fi
+    nar_str ")"
+}
+
+nar_dump() {
+    nar_str nix-archive-1
+    nar_node "$1"
+}
+
+
+# Report a wrong hash in hash! syntax with the derivation name and url,
+# oxide would only tell that the output does not match.
+check_hash() {
+    local expected="${EXPECTED_HASH:-}"
+    local fake
+    fake="sha512:$(printf 'A%.0s' {1..86})"
+
+    if [ -z "$expected" ] || [ "${expected%%:*}" != sha512 ]; then return 0; fi
+
+    local hex actual
+    if [ -d "$out" ]; then
+        hex=$(nar_dump "$out" | sha512sum | cut -d' ' -f1)
+    else
+        hex=$(sha512sum "$out" | cut -d' ' -f1)
+    fi
+    actual="sha512:$(printf "$(echo "$hex" | sed 's/../\\x&/g')" | base64 -w0 | tr '+/' '-_' | tr -d '=')"
+    if [ "$actual" = "$expected" ]; then return 0; fi
+
+    if [ "$expected" = "$fake" ]; then
+        echo "error: ${name:-$url} uses fake_hash(), the hash of $url is"
+        echo "    hash!(\"$actual\")"
+    else
+        echo "error: hash mismatch in fixed output derivation ${name:-$url}"
+        echo "  url:       $url"
+        echo "  specified: hash!(\"$expected\")"
+        echo "  got:       hash!(\"$actual\")"
+    fi
+    exit 1
+}
+
+
 finish() {
-    local skipPostFetch="$1"
+    local skipPostFetch="${1:-}"
 
     set +o noglob
 
-    if [[ $executable == "1" ]]; then
-      chmod +x $downloadedFile
+    if [[ ${EXECUTABLE:-} == "1" ]]; then
+      chmod +x "$downloadedFile"
     fi
 
     if [ -z "$skipPostFetch" ]; then
-        runHook postFetch
+        run_hook POST_FETCH
     fi
 
+    check_hash
+
     exit 0
 }
 
 
 tryHashedMirrors() {
-    if test -n "$NIX_HASHED_MIRRORS"; then
-        hashedMirrors="$NIX_HASHED_MIRRORS"
+    if test -n "${OXIDE_HASHED_MIRRORS:-}"; then
+        hashedMirrors="$OXIDE_HASHED_MIRRORS"
     fi
 
-    for mirror in $hashedMirrors; do
+    for mirror in ${hashedMirrors:-}; do
         url="$mirror/$outputHashAlgo/$outputHash"
         if "${curl[@]}" --retry 0 --connect-timeout "${NIX_CONNECT_TIMEOUT:-15}" \
             --fail --silent --show-error --head "$url" \
@@ -112,14 +217,14 @@ for url in $urls; do
         url2="${url:9}"; echo "${url2/\// }" > split; read site fileName < split
         #varName="mirror_$site"
         varName="$site" # !!! danger of name clash, fix this
-        if test -z "${!varName}"; then
+        if test -z "${!varName:-}"; then
             echo "warning: unknown mirror:// site \`$site'"
         else
             mirrors=${!varName}
 
             # Allow command-line override by setting NIX_MIRRORS_$site.
-            varName="NIX_MIRRORS_$site"
-            if test -n "${!varName}"; then mirrors="${!varName}"; fi
+            varName="OXIDE_MIRRORS_$site"
+            if test -n "${!varName:-}"; then mirrors="${!varName}"; fi
 
             for url3 in $mirrors; do
                 urls2="$urls2 $url3$fileName";
@@ -132,12 +237,12 @@ urls="$urls2"
 # Restore globbing settings
 set +o noglob
 
-if test -n "$showURLs"; then
+if test -n "${SHOW_URLS:-}"; then
     echo "$urls" > $out
     exit 0
 fi
 
-if test -n "$preferHashedMirrors"; then
+if test -n "${PREFER_HASHED_MIRRORS:-}"; then
     tryHashedMirrors
 fi
 
@@ -146,13 +251,13 @@ set -o noglob
 
 success=
 for url in $urls; do
-    if [ -z "$postFetch" ]; then
+    if [ -z "${POST_FETCH:-}" ]; then
        case "$url" in
            https://github.com/*/archive/*)
-               echo "warning: archives from GitHub revisions should use fetchFromGitHub"
+               echo "warning: archives from GitHub revisions should use fetch_from_github"
                ;;
            https://gitlab.com/*/-/archive/*)
-               echo "warning: archives from GitLab revisions should use fetchFromGitLab"
+               echo "warning: archives from GitLab revisions should use fetch_from_gitlab"
                ;;
        esac
     fi
@@ -163,10 +268,10 @@ done
 # Restore globbing settings
 set +o noglob
 
-if test -z "$preferHashedMirrors"; then
+if test -z "${PREFER_HASHED_MIRRORS:-}"; then
     tryHashedMirrors
 fi
 
 
-echo "error: cannot download $name from any mirror"
+echo "error: cannot download ${name:-$url} from any mirror"
 exit 1
@@ -478,4 +478,28 @@ impl Mirrors {
             _ => None,
         }
     }
+
+    // the site and its mirrors of a mirror://site/file url
+    pub fn site_of<'a>(&self, url: &'a str) -> Option<(&'a str, Mirror)> {
+        let (site, _) = url.strip_prefix("mirror://")?.split_once('/')?;
+        Some((site, self.get_mirror(site)?))
+    }
+
+    // the urls fetchurl tries for `url` in order,
+    // a mirror:// url expands to every mirror of its site
+    pub fn resolve(&self, url: &str) -> Option<Vec<String>> {
+        match url.strip_prefix("mirror://") {
+            Some(rest) => {
+                let (site, file) = rest.split_once('/')?;
+                let mirrors = self.get_mirror(site)?;
+                Some(
+                    mirrors
+                        .iter()
+                        .map(|mirror| format!("{mirror}{file}"))
+                        .collect(),
+                )
+            }
+            None => Some(vec![url.to_string()]),
+        }
+    }
 }
@@ -1,12 +1,14 @@
 mod mirrors;
 pub use mirrors::*;
 
-use crate::stdenv::Stdenv;
+use crate::stdenv::{Stdenv, StdenvBuilder};
 use oxide_core::{builtins, prelude::*};
 
 #[derive(Clone)]
 pub enum FetchUrl {
     Stdenv(StdenvFetchUrl),
+    // downloaded by oxide itself, without the hints of builder.sh on
+    // a mismatch or a `fake_hash()`
     Builtins,
 }
 
@@ -19,63 +21,201 @@ impl FetchUrl {
         Self::Builtins
     }
 
-    // TODO: for now super simple, only url and hash
-    // expand in the future to allow more arguments
     pub fn fetch<T>(&self, url: T, hash: Hash) -> LazyDrv
     where
         T: Into<Cow<str>>,
     {
-        let url = url.into();
+        self.fetch_with(FetchUrlArgs::new(url, hash))
+    }
+
+    pub fn fetch_with(&self, args: FetchUrlArgs) -> LazyDrv {
         match self {
             FetchUrl::Stdenv(fetchurl) => LazyDrv::new(FetchUrlParam {
                 stdenv_no_cc: fetchurl.stdenv_no_cc.clone(),
                 curl: LazyDrv::clone(&fetchurl.curl),
-                url,
-                hash,
+                args,
             }),
             FetchUrl::Builtins => {
-                let url = if let Some(rest) = url.strip_prefix("mirror://") {
-                    let (mirror_name, suffix) = rest.split_once("/").unwrap();
-                    let mirror = MIRRORS.get_mirror(mirror_name).unwrap();
-                    format!("{}{}", mirror[0], suffix).into()
-                } else {
-                    url
-                };
+                assert!(
+                    args.post_fetch.is_none()
+                        && args.netrc.is_none()
+                        && args.inputs.is_empty()
+                        && args.deps.is_empty(),
+                    "builtins fetchurl can only download, {} needs a stdenv fetchurl",
+                    args.url
+                );
                 LazyDrv::new(builtins::FetchUrl {
-                    name: None,
-                    url,
-                    hash,
+                    name: args.name,
+                    url: resolve_mirror(args.url),
+                    hash: args.hash,
                     unpack: false,
-                    executable: false,
+                    executable: args.executable,
                 })
             }
         }
     }
 }
 
+// builtins fetchurl does not know about mirror:// urls
+// so we pick the first mirror of the list
+pub(crate) fn resolve_mirror(url: Cow<str>) -> Cow<str> {
+    if url.starts_with("mirror://") {
+        let urls = MIRRORS
+            .resolve(&url)
+            .unwrap_or_else(|| panic!("unknown mirror:// site in {url}"));
+        urls.into_iter().next().unwrap().into()
+    } else {
+        url
+    }
+}
+
 #[derive(Clone)]
 pub struct StdenvFetchUrl {
     pub stdenv_no_cc: Stdenv,
     pub curl: LazyDrv,
 }
 
+// Credentials for private sources, `phase` must write them to `./netrc`
+// reading them from `impure_env_vars` so they never end up in the derivation
+#[derive(Clone)]
+pub struct Netrc {
+    pub phase: Cow<str>,
+    pub impure_env_vars: Vec<Cow<str>>,
+}
+
+impl Netrc {
+    pub fn build(self, builder: StdenvBuilder) -> StdenvBuilder {
+        let impure_env_vars: Vec<Expr> = self.impure_env_vars.into_iter().map(Expr::from).collect();
+        builder
+            .input("NETRC_PHASE", self.phase)
+            .input("IMPURE_ENV_VARS", impure_env_vars)
+    }
+}
+
+pub struct FetchUrlArgs {
+    pub name: Option<Cow<str>>,
+    pub url: Cow<str>,
+    pub hash: Hash,
+    pub executable: bool,
+    pub download_to_temp: bool,
+    pub post_fetch: Option<Cow<str>>,
+    pub netrc: Option<Netrc>,
+    pub deps: Vec<Expr>,
+    pub inputs: Vec<(String, Expr)>,
+}
+
+impl FetchUrlArgs {
+    pub fn new<T>(url: T, hash: Hash) -> Self
+    where
+        T: Into<Cow<str>>,
+    {
+        Self {
+            name: None,
+            url: url.into(),
+            hash,
+            executable: false,
+            download_to_temp: false,
+            post_fetch: None,
+            netrc: None,
+            deps: Vec::new(),
+            inputs: Vec::new(),
+        }
+    }
+
+    pub fn name<T>(mut self, name: T) -> Self
+    where
+        T: Into<Cow<str>>,
+    {
+        self.name = Some(name.into());
+        self
+    }
+
+    pub fn executable(mut self) -> Self {
+        self.executable = true;
+        self
+    }
+
+    /// Download to `$TMPDIR/file` instead of `$out`,
+    /// the post fetch hook is then responsible for creating `$out`
+    pub fn download_to_temp(mut self) -> Self {
+        self.download_to_temp = true;
+        self
+    }
+
+    pub fn post_fetch<T>(mut self, post_fetch: T) -> Self
+    where
+        T: Into<Cow<str>>,
+    {
+        self.post_fetch = Some(post_fetch.into());
+        self
+    }
+
+    pub fn netrc(mut self, netrc: Netrc) -> Self {
+        self.netrc = Some(netrc);
+        self
+    }
+
+    pub fn dep<T>(mut self, dep: T) -> Self
+    where
+        T: Into<Expr>,
+    {
+        self.deps.push(dep.into());
+        self
+    }
+
+    pub fn input<K, V>(mut self, key: K, expr: V) -> Self
+    where
+        K: Into<String>,
+        V: Into<Expr>,
+    {
+        self.inputs.push((key.into(), expr.into()));
+        self
+    }
+}
+
 struct FetchUrlParam {
     stdenv_no_cc: Stdenv,
     curl: LazyDrv,
-    url: Cow<str>,
-    hash: Hash,
+    args: FetchUrlArgs,
 }
 
 impl IntoDrv for FetchUrlParam {
     fn into_drv(self) -> Drv {
-        let name = base_name(&self.url).to_string();
-        self.stdenv_no_cc
+        let args = self.args;
+        let name = args
+            .name
+            .unwrap_or_else(|| base_name(&args.url).to_string().into());
+        // the builder expands mirror://site/ urls with the $site input
+        let mirrors = MIRRORS
+            .site_of(&args.url)
+            .map(|(site, mirrors)| (site.to_string(), mirrors.join(" ")));
+        let builder = self
+            .stdenv_no_cc
             .make_derivation()
             .name(name)
             .builder(local_file!("builder.sh"))
-            .fixed_hash(self.hash)
-            .input("url", self.url)
-            .input("curl", self.curl)
+            .input("EXPECTED_HASH", args.hash.to_string())
+            .fixed_hash(args.hash)
+            .input("url", args.url)
+            .dep_build_host(self.curl)
+            .input_bool("EXECUTABLE", args.executable)
+            .input_bool("DOWNLOAD_TO_TEMP", args.download_to_temp)
+            .input_if("POST_FETCH", args.post_fetch);
+        let builder = match mirrors {
+            Some((site, mirrors)) => builder.input(site, mirrors),
+            None => builder,
+        };
+        let builder = match args.netrc {
+            Some(netrc) => netrc.build(builder),
+            None => builder,
+        };
+        let builder = args
+            .deps
+            .into_iter()
+            .fold(builder, |builder, dep| builder.dep_build_host(dep));
+        args.inputs
+            .into_iter()
+            .fold(builder, |builder, (key, expr)| builder.input(key, expr))
             .build()
     }
 }
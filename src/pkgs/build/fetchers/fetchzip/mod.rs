@@ -0,0 +1,158 @@
+use crate::build::fetchurl::{FetchUrl, FetchUrlArgs, Netrc, resolve_mirror};
+use oxide_core::{builtins, prelude::*};
+
+#[derive(Clone)]
+pub struct FetchZip {
+    pub fetchurl: FetchUrl,
+    // only needed to unpack .zip archives
+    pub unzip: Option<LazyDrv>,
+}
+
+impl FetchZip {
+    pub fn new(fetchurl: FetchUrl) -> Self {
+        Self {
+            fetchurl,
+            unzip: None,
+        }
+    }
+
+    pub fn unzip(mut self, unzip: LazyDrv) -> Self {
+        self.unzip = Some(unzip);
+        self
+    }
+
+    pub fn fetch<T>(&self, url: T, hash: Hash) -> LazyDrv
+    where
+        T: Into<Cow<str>>,
+    {
+        self.fetch_with(FetchZipArgs::new(url, hash))
+    }
+
+    // same as fetch but for urls that do not end with the archive extension
+    pub fn fetch_tarball<T>(&self, url: T, hash: Hash) -> LazyDrv
+    where
+        T: Into<Cow<str>>,
+    {
+        self.fetch_with(FetchZipArgs::new(url, hash).extension("tar"))
+    }
+
+    pub fn fetch_with(&self, args: FetchZipArgs) -> LazyDrv {
+        let name = args.name.unwrap_or("source".into());
+        match &self.fetchurl {
+            FetchUrl::Builtins => {
+                // builtins fetchurl unpacks on its own and always strips the root
+                assert!(
+                    args.strip_root && args.post_fetch.is_none() && args.netrc.is_none(),
+                    "builtins fetchzip can only unpack, {} needs a stdenv fetchurl",
+                    args.url
+                );
+                LazyDrv::new(builtins::FetchUrl {
+                    name: Some(name),
+                    url: resolve_mirror(args.url),
+                    hash: args.hash,
+                    unpack: true,
+                    executable: false,
+                })
+            }
+            FetchUrl::Stdenv(_) => {
+                let archive_name = match args.extension {
+                    Some(extension) => format!("source.{extension}"),
+                    None => base_name(&args.url).to_string(),
+                };
+                // there is no unzip in the stdenv, fail now rather than at build time
+                assert!(
+                    !archive_name.ends_with(".zip") || self.unzip.is_some(),
+                    "{} needs a fetchzip with unzip",
+                    args.url
+                );
+                let post_fetch = format!(
+                    "source \"$FETCHZIP\"\n{}\nnormalise_tree \"$out\"",
+                    args.post_fetch.unwrap_or_default()
+                );
+                let fetchurl_args = FetchUrlArgs::new(args.url, args.hash)
+                    .name(name)
+                    .download_to_temp()
+                    .input("FETCHZIP", local_file!("unpack.sh"))
+                    .input("NORMALISE", local_file!("../normalise.sh"))
+                    .input("ARCHIVE_NAME", archive_name)
+                    .post_fetch(post_fetch);
+                let fetchurl_args = if args.strip_root {
+                    fetchurl_args.input("STRIP_ROOT", "1")
+                } else {
+                    fetchurl_args
+                };
+                let fetchurl_args = match args.netrc {
+                    Some(netrc) => fetchurl_args.netrc(netrc),
+                    None => fetchurl_args,
+                };
+                let fetchurl_args = match &self.unzip {
+                    Some(unzip) => fetchurl_args.dep(unzip),
+                    None => fetchurl_args,
+                };
+                self.fetchurl.fetch_with(fetchurl_args)
+            }
+        }
+    }
+}
+
+pub struct FetchZipArgs {
+    pub name: Option<Cow<str>>,
+    pub url: Cow<str>,
+    pub hash: Hash,
+    pub strip_root: bool,
+    pub extension: Option<Cow<str>>,
+    pub post_fetch: Option<Cow<str>>,
+    pub netrc: Option<Netrc>,
+}
+
+impl FetchZipArgs {
+    pub fn new<T>(url: T, hash: Hash) -> Self
+    where
+        T: Into<Cow<str>>,
+    {
+        Self {
+            name: None,
+            url: url.into(),
+            hash,
+            strip_root: true,
+            extension: None,
+            post_fetch: None,
+            netrc: None,
+        }
+    }
+
+    pub fn name<T>(mut self, name: T) -> Self
+    where
+        T: Into<Cow<str>>,
+    {
+        self.name = Some(name.into());
+        self
+    }
+
+    pub fn dont_strip_root(mut self) -> Self {
+        self.strip_root = false;
+        self
+    }
+
+    pub fn extension<T>(mut self, extension: T) -> Self
+    where
+        T: Into<Cow<str>>,
+    {
+        self.extension = Some(extension.into());
+        self
+    }
+
+    // runs after unpacking but before the tree is normalised
+    pub fn post_fetch<T>(mut self, post_fetch: T) -> Self
+    where
+        T: Into<Cow<str>>,
+    {
+        self.post_fetch = Some(post_fetch.into());
+        self
+    }
+
+    pub fn netrc(mut self, netrc: Netrc) -> Self {
+        self.netrc = Some(netrc);
+        self
+    }
+}
@@ -0,0 +1,48 @@
+# shellcheck shell=bash disable=SC2154
+#
+# Post fetch hook of fetchzip: unpack the downloaded archive into $out,
+# dropping its top-level directory unless told otherwise.
+
+source "$NORMALISE"
+
+_unpack_zip() {
+    case "$1" in
+        *.zip | *.ZIP)
+            unzip -qq "$1"
+            ;;
+        *)
+            return 1
+            ;;
+    esac
+}
+UNPACK_CMD_HOOKS+=(_unpack_zip)
+
+unpack_dir="$TMPDIR/unpack"
+mkdir "$unpack_dir"
+cd "$unpack_dir"
+
+# unpack_file picks the unpacker from the file extension
+# and urls are not guaranteed to carry one
+renamed="$TMPDIR/$ARCHIVE_NAME"
+mv "$downloadedFile" "$renamed"
+unpack_file "$renamed"
+chmod -R +w "$unpack_dir"
+
+if [ -n "${STRIP_ROOT:-}" ]; then
+    shopt -s dotglob
+    entries=("$unpack_dir"/*)
+    shopt -u dotglob
+    if [ "${#entries[@]}" != 1 ]; then
+        echo "error: archive must contain a single file or directory to strip its root"
+        echo "hint: use dont_strip_root() to keep every top-level entry"
+        exit 1
+    fi
+    if [ -f "${entries[0]}" ]; then
+        mkdir "$out"
+        mv "${entries[0]}" "$out/"
+    else
+        mv "${entries[0]}" "$out"
+    fi
+else
+    mv "$unpack_dir" "$out"
+fi
@@ -0,0 +1,245 @@
+use crate::build::{
+    fetchgit::{FetchGit, FetchGitArgs},
+    fetchurl::Netrc,
+    fetchzip::{FetchZip, FetchZipArgs},
+};
+use oxide_core::prelude::*;
+
+// Fetch sources from the archives generated by code forges,
+// they are cheaper to download than a git checkout but are regenerated
+// from time to time so fetchzip is used to hash the unpacked tree
+#[derive(Clone)]
+pub struct FetchFromForge {
+    pub fetchzip: FetchZip,
+    pub fetchgit: FetchGit,
+}
+
+impl FetchFromForge {
+    pub fn new(fetchzip: FetchZip, fetchgit: FetchGit) -> Self {
+        Self { fetchzip, fetchgit }
+    }
+
+    pub fn fetch_from_github(&self, args: ForgeArgs) -> LazyDrv {
+        let domain = args.domain.clone().unwrap_or("github.com".into());
+        let base_url = format!("https://{domain}/{}/{}", args.owner, args.repo);
+        let api_url = if domain == "github.com" {
+            "https://api.github.com".to_string()
+        } else {
+            format!("https://{domain}/api/v3")
+        };
+        let netrc = args.private.then(|| private_netrc("GITHUB", &api_url));
+        if args.use_fetchgit() {
+            return self.fetch_git(format!("{base_url}.git"), args, netrc);
+        }
+        let zip_args = if args.private {
+            // archive links of private repositories are only served by the api
+            FetchZipArgs::new(
+                format!(
+                    "{api_url}/repos/{}/{}/tarball/{}",
+                    args.owner, args.repo, args.rev
+                ),
+                args.hash,
+            )
+            .extension("tar.gz")
+        } else {
+            FetchZipArgs::new(format!("{base_url}/archive/{}.tar.gz", args.rev), args.hash)
+        };
+        self.fetch_zip(zip_args, args.name, netrc)
+    }
+
+    pub fn fetch_from_gitlab(&self, args: ForgeArgs) -> LazyDrv {
+        let domain = args.domain.clone().unwrap_or("gitlab.com".into());
+        let base_url = format!("https://{domain}/{}/{}", args.owner, args.repo);
+        // the archive endpoint does not accept basic auth, private
+        // repositories are cloned with git which does
+        if args.use_fetchgit() || args.private {
+            let netrc = args.private.then(|| private_netrc("GITLAB", &domain));
+            return self.fetch_git(format!("{base_url}.git"), args, netrc);
+        }
+        let url = format!(
+            "{base_url}/-/archive/{rev}/{repo}-{rev}.tar.gz",
+            rev = args.rev,
+            repo = args.repo
+        );
+        self.fetch_zip(FetchZipArgs::new(url, args.hash), args.name, None)
+    }
+
+    // gitea and its forks forgejo and codeberg share the same urls
+    pub fn fetch_from_gitea(&self, args: ForgeArgs) -> LazyDrv {
+        let domain = args
+            .domain
+            .clone()
+            .expect("fetch_from_gitea needs the domain of the instance");
+        let base_url = format!("https://{domain}/{}/{}", args.owner, args.repo);
+        let netrc = args.private.then(|| private_netrc("GITEA", &domain));
+        if args.use_fetchgit() {
+            return self.fetch_git(format!("{base_url}.git"), args, netrc);
+        }
+        let url = format!("{base_url}/archive/{}.tar.gz", args.rev);
+        self.fetch_zip(FetchZipArgs::new(url, args.hash), args.name, netrc)
+    }
+
+    pub fn fetch_from_codeberg(&self, args: ForgeArgs) -> LazyDrv {
+        self.fetch_from_gitea(args.domain("codeberg.org"))
+    }
+
+    pub fn fetch_from_sourcehut(&self, args: ForgeArgs) -> LazyDrv {
+        assert!(
+            !args.private,
+            "sourcehut only serves private repositories over ssh"
+        );
+        let domain = args.domain.clone().unwrap_or("git.sr.ht".into());
+        let base_url = format!("https://{domain}/~{}/{}", args.owner, args.repo);
+        if args.use_fetchgit() {
+            return self.fetch_git(base_url, args, None);
+        }
+        let url = format!("{base_url}/archive/{}.tar.gz", args.rev);
+        self.fetch_zip(FetchZipArgs::new(url, args.hash), args.name, None)
+    }
+
+    fn fetch_zip(
+        &self,
+        zip_args: FetchZipArgs,
+        name: Option<Cow<str>>,
+        netrc: Option<Netrc>,
+    ) -> LazyDrv {
+        let zip_args = match name {
+            Some(name) => zip_args.name(name),
+            None => zip_args,
+        };
+        let zip_args = match netrc {
+            Some(netrc) => zip_args.netrc(netrc),
+            None => zip_args,
+        };
+        self.fetchzip.fetch_with(zip_args)
+    }
+
+    fn fetch_git(&self, url: String, args: ForgeArgs, netrc: Option<Netrc>) -> LazyDrv {
+        let mut git_args = FetchGitArgs::new(url, args.hash).rev(args.rev);
+        git_args.name = args.name;
+        git_args.fetch_submodules = args.fetch_submodules;
+        git_args.deep_clone = args.deep_clone;
+        git_args.leave_dot_git = args.leave_dot_git;
+        git_args.sparse_checkout = args.sparse_checkout;
+        git_args.netrc = netrc;
+        self.fetchgit.fetch_with(git_args)
+    }
+}
+
+// Credentials are read from `OXIDE_<FORGE>_PRIVATE_USERNAME`
+// and `OXIDE_<FORGE>_PRIVATE_PASSWORD`, the password being a token
+fn private_netrc(forge: &str, machine: &str) -> Netrc {
+    let machine = machine
+        .trim_start_matches("https://")
+        .split('/')
+        .next()
+        .unwrap();
+    let username = format!("OXIDE_{forge}_PRIVATE_USERNAME");
+    let password = format!("OXIDE_{forge}_PRIVATE_PASSWORD");
+    Netrc {
+        phase: format!(
+            r#"if [ -z "${{{username}:-}}" ] || [ -z "${{{password}:-}}" ]; then
+    echo "error: private repositories need {username} and {password} to be set for the oxide builder" >&2
+    exit 1
+fi
+cat > netrc <<EOF
+machine {machine}
+        login ${username}
+        password ${password}
+EOF"#
+        )
+        .into(),
+        impure_env_vars: vec![username.into(), password.into()],
+    }
+}
+
+pub struct ForgeArgs {
+    pub name: Option<Cow<str>>,
+    pub owner: Cow<str>,
+    pub repo: Cow<str>,
+    // a commit hash or a tag
+    pub rev: Cow<str>,
+    pub hash: Hash,
+    pub domain: Option<Cow<str>>,
+    pub private: bool,
+    pub fetch_submodules: bool,
+    pub deep_clone: bool,
+    pub leave_dot_git: bool,
+    pub sparse_checkout: Vec<Cow<str>>,
+}
+
+impl ForgeArgs {
+    pub fn new<O, R, V>(owner: O, repo: R, rev: V, hash: Hash) -> Self
+    where
+        O: Into<Cow<str>>,
+        R: Into<Cow<str>>,
+        V: Into<Cow<str>>,
+    {
+        Self {
+            name: None,
+            owner: owner.into(),
+            repo: repo.into(),
+            rev: rev.into(),
+            hash,
+            domain: None,
+            private: false,
+            fetch_submodules: false,
+            deep_clone: false,
+            leave_dot_git: false,
+            sparse_checkout: Vec::new(),
+        }
+    }
+
+    // archives do not contain submodules nor history,
+    // fall back to a git checkout when those are needed
+    fn use_fetchgit(&self) -> bool {
+        self.fetch_submodules
+            || self.deep_clone
+            || self.leave_dot_git
+            || !self.sparse_checkout.is_empty()
+    }
+
+    pub fn name<T>(mut self, name: T) -> Self
+    where
+        T: Into<Cow<str>>,
+    {
+        self.name = Some(name.into());
+        self
+    }
+
+    pub fn domain<T>(mut self, domain: T) -> Self
+    where
+        T: Into<Cow<str>>,
+    {
+        self.domain = Some(domain.into());
+        self
+    }
+
+    pub fn private(mut self) -> Self {
+        self.private = true;
+        self
+    }
+
+    pub fn fetch_submodules(mut self) -> Self {
+        self.fetch_submodules = true;
+        self
+    }
+
+    pub fn deep_clone(mut self) -> Self {
+        self.deep_clone = true;
+        self
+    }
+
+    pub fn leave_dot_git(mut self) -> Self {
+        self.leave_dot_git = true;
+        self
+    }
+
+    pub fn sparse_checkout<T>(mut self, path: T) -> Self
+    where
+        T: Into<Cow<str>>,
+    {
+        self.sparse_checkout.push(path.into());
+        self
+    }
+}
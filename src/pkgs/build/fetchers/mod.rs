@@ -1 +1,5 @@
+pub mod fetchgit;
+pub mod fetchpatch;
 pub mod fetchurl;
+pub mod fetchzip;
+pub mod forges;
@@ -0,0 +1,14 @@
+# shellcheck shell=bash
+#
+# Make a fetched tree independent of how it was transported:
+# permissions only keep the executable bit and every timestamp
+# is pinned to SOURCE_DATE_EPOCH, so recompressing an archive
+# or checking out the same revision again gives the same hash.
+
+normalise_tree() {
+    local dir="$1"
+    find "$dir" -type d -exec chmod 755 {} +
+    find "$dir" -type f -perm /111 -exec chmod 755 {} +
+    find "$dir" -type f ! -perm /111 -exec chmod 644 {} +
+    find "$dir" -exec touch -h -d "@${SOURCE_DATE_EPOCH:-315532800}" {} +
+}
@@ -0,0 +1,86 @@
+# shellcheck shell=bash disable=SC2154
+#
+# Builder of build_go_module: the generic phases with go instead of make.
+# Custom phases and their pre and post hooks work as with the default builder.
+
+configure_phase() {
+    run_hook PRE_CONFIGURE
+
+    export GOCACHE="$TMPDIR/go-cache" GOPATH="$TMPDIR/go"
+    # go never touches the network, every module comes from vendor/
+    export GOFLAGS="-mod=vendor -trimpath" GOPROXY=off GOTOOLCHAIN=local
+
+    if [ -n "${GO_VENDOR:-}" ]; then
+        rm -rf vendor
+        cp -r "$GO_VENDOR" vendor
+        chmod -R u+w vendor
+    fi
+
+    run_hook POST_CONFIGURE
+}
+
+
+_go_flags() {
+    local -n flags_ref="$1"
+    flags_ref=(-p "$OXIDE_BUILD_CORES")
+    if [ -n "${GO_TAGS:-}" ]; then
+        flags_ref+=(-tags "$GO_TAGS")
+    fi
+}
+
+_go_packages() {
+    local -n packages_ref="$1"
+    packages_ref=()
+    concatTo packages_ref GO_SUB_PACKAGES
+    if (( ${#packages_ref[@]} == 0 )); then
+        packages_ref=(./...)
+    fi
+}
+
+
+build_phase() {
+    run_hook PRE_BUILD
+
+    local -a flags_array packages_array
+    _go_flags flags_array
+    if [ -n "${GO_LDFLAGS:-}" ]; then
+        flags_array+=(-ldflags "$GO_LDFLAGS")
+    fi
+    _go_packages packages_array
+
+    echo "go install flags: ${flags_array[*]}"
+    go install "${flags_array[@]}" "${packages_array[@]}"
+    unset flags_array packages_array
+
+    run_hook POST_BUILD
+}
+
+
+check_phase() {
+    run_hook PRE_CHECK
+
+    local -a flags_array packages_array
+    _go_flags flags_array
+    _go_packages packages_array
+
+    go test "${flags_array[@]}" "${packages_array[@]}"
+    unset flags_array packages_array
+
+    run_hook POST_CHECK
+}
+
+
+install_phase() {
+    run_hook PRE_INSTALL
+
+    # cross compiled commands are installed in bin/$GOOS_$GOARCH
+    mkdir -p "$out/bin"
+    if [ -d "$GOPATH/bin" ]; then
+        find "$GOPATH/bin" -type f -exec install -m755 -t "$out/bin" {} +
+    fi
+
+    run_hook POST_INSTALL
+}
+
+
+generic_build
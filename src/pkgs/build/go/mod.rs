@@ -0,0 +1,120 @@
+use crate::{development::compilers::go::go_arch, stdenv::Stdenv, stdenv::StdenvBuilder};
+use oxide_core::prelude::*;
+
+#[derive(Clone)]
+pub struct GoPlatform {
+    pub stdenv: Stdenv,
+    pub go: LazyDrv,
+}
+
+impl GoPlatform {
+    pub fn new(stdenv: Stdenv, go: LazyDrv) -> Self {
+        Self { stdenv, go }
+    }
+
+    // The modules of the go.mod of `src` as a fixed output derivation
+    pub fn fetch_go_vendor<N, S>(&self, name: N, src: S, hash: Hash) -> LazyDrv
+    where
+        N: Into<Cow<str>>,
+        S: Into<Expr>,
+    {
+        self.stdenv
+            .make_derivation()
+            .name(format!("{}-go-modules", name.into()))
+            .src(src)
+            .fixed_hash(hash)
+            .builder(local_file!("vendor.sh"))
+            .dep_build_host(LazyDrv::clone(&self.go))
+            .input("NORMALISE", local_file!("../fetchers/normalise.sh"))
+            .lazy()
+    }
+
+    // A `StdenvBuilder` building the commands of `args.src` with go, the usual
+    // phase setters still apply on top of it
+    pub fn build_go_module(&self, args: GoModuleArgs) -> StdenvBuilder {
+        let vendor = args.vendor_hash.map(|hash| {
+            self.fetch_go_vendor(
+                format!("{}-{}", args.name, args.version),
+                Expr::clone(&args.src),
+                hash,
+            )
+        });
+        self.stdenv
+            .make_derivation()
+            .name(args.name)
+            .version(args.version)
+            .src(args.src)
+            .builder(local_file!("builder.sh"))
+            .dep_build_host(LazyDrv::clone(&self.go))
+            .input_if("GO_VENDOR", vendor)
+            .input("GOOS", "linux")
+            .input("GOARCH", go_arch(self.stdenv.host_platform))
+            .input("GO_SUB_PACKAGES", args.sub_packages.join(" "))
+            .input("GO_LDFLAGS", args.ldflags.join(" "))
+            .input("GO_TAGS", args.tags.join(","))
+            .optional(args.check, |builder| builder.do_check())
+    }
+}
+
+pub struct GoModuleArgs {
+    pub name: Cow<str>,
+    pub version: Cow<str>,
+    pub src: Expr,
+    // None when the module has no dependencies to vendor
+    pub vendor_hash: Option<Hash>,
+    pub sub_packages: Vec<Cow<str>>,
+    pub ldflags: Vec<Cow<str>>,
+    pub tags: Vec<Cow<str>>,
+    pub check: bool,
+}
+
+impl GoModuleArgs {
+    pub fn new<N, V, S>(name: N, version: V, src: S, vendor_hash: Option<Hash>) -> Self
+    where
+        N: Into<Cow<str>>,
+        V: Into<Cow<str>>,
+        S: Into<Expr>,
+    {
+        Self {
+            name: name.into(),
+            version: version.into(),
+            src: src.into(),
+            vendor_hash,
+            sub_packages: Vec::new(),
+            ldflags: Vec::new(),
+            tags: Vec::new(),
+            check: true,
+        }
+    }
+
+    // a package to build instead of every package of the module, e.g. ./cmd/foo
+    pub fn sub_package<T>(mut self, sub_package: T) -> Self
+    where
+        T: Into<Cow<str>>,
+    {
+        self.sub_packages.push(sub_package.into());
+        self
+    }
+
+    // e.g. -s, -w or -X main.version=1.0
+    pub fn ldflag<T>(mut self, ldflag: T) -> Self
+    where
+        T: Into<Cow<str>>,
+    {
+        self.ldflags.push(ldflag.into());
+        self
+    }
+
+    pub fn tag<T>(mut self, tag: T) -> Self
+    where
+        T: Into<Cow<str>>,
+    {
+        self.tags.push(tag.into());
+        self
+    }
+
+    pub fn dont_check(mut self) -> Self {
+        self.check = false;
+        self
+    }
+}
@@ -0,0 +1,29 @@
+# shellcheck shell=bash disable=SC1091,SC2154
+#
+# Vendors the modules of go.mod into $out, runs as a fixed output
+# derivation so go can reach the module proxy.
+
+source "$NORMALISE"
+
+export GOCACHE="$TMPDIR/go-cache" GOPATH="$TMPDIR/go"
+export GOFLAGS=-mod=mod GOTOOLCHAIN=local
+
+run_phase UNPACK_PHASE
+run_phase PATCH_PHASE
+
+if [ ! -f go.mod ]; then
+    echo "error: $name has no go.mod, build_go_module needs one to vendor the modules"
+    exit 1
+fi
+
+rm -rf vendor
+go mod vendor
+
+if [ ! -d vendor ]; then
+    echo "error: $name has no dependencies to vendor"
+    echo "hint: set vendor_hash to None"
+    exit 1
+fi
+
+cp -r vendor "$out"
+normalise_tree "$out"
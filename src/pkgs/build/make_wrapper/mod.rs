@@ -0,0 +1,19 @@
+use crate::stdenv::Stdenv;
+use oxide_core::prelude::*;
+
+// Provides the make_wrapper and wrap_program shell functions to the
+// packages having it in dep_build_host
+pub struct MakeWrapper {
+    pub stdenv: Stdenv,
+}
+
+impl IntoDrv for MakeWrapper {
+    fn into_drv(self) -> Drv {
+        self.stdenv
+            .make_derivation()
+            .name("make-wrapper")
+            .dont_unpack()
+            .input("SETUP_HOOK", local_file!("../setup-hooks/make-wrapper.sh"))
+            .build()
+    }
+}
@@ -0,0 +1,34 @@
+use crate::{build::fetchurl::FetchUrl, lib::fake_hash, stdenv::Stdenv};
+use oxide_core::prelude::*;
+
+pub struct Meson {
+    pub stdenv: Stdenv,
+    pub fetchurl: FetchUrl,
+    pub python3: LazyDrv,
+}
+
+impl IntoDrv for Meson {
+    fn into_drv(self) -> Drv {
+        let version = "1.9.1";
+        self.stdenv
+            .make_derivation()
+            .name("meson")
+            .version(version)
+            .src(self.fetchurl.fetch(
+                format!(
+                    "https://github.com/mesonbuild/meson/releases/download/{version}/meson-{version}.tar.gz"
+                ),
+                fake_hash(),
+            ))
+            // pure python, meson.py runs from the source tree
+            .input("PYTHON3", self.python3)
+            .install_phase(
+                r#"mkdir -p "$out/lib/meson" "$out/bin"
+cp -r mesonbuild meson.py "$out/lib/meson"
+sed -i "1s|.*|#!$PYTHON3/bin/python3|" "$out/lib/meson/meson.py"
+ln -s ../lib/meson/meson.py "$out/bin/meson""#,
+            )
+            .input("SETUP_HOOK", local_file!("setup-hook.sh"))
+            .build()
+    }
+}
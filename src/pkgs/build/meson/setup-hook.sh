@@ -0,0 +1,92 @@
+# shellcheck shell=bash disable=SC2154
+#
+# Configure with `meson setup`, check with `meson test` and install with
+# `meson install`, set DONT_USE_MESON_CONFIGURE, DONT_USE_MESON_CHECK or
+# DONT_USE_MESON_INSTALL to opt out.
+# Needs ninja in dep_build_host, its hook builds the generated project.
+
+meson_configure_phase() {
+    run_hook PRE_CONFIGURE
+
+    local bin_dir dev_dir lib_dir doc_dir man_dir info_dir
+    bin_dir=$(get_output_dir OUTPUT_BIN bin)
+    dev_dir=$(get_output_dir OUTPUT_DEV dev)
+    lib_dir=$(get_output_dir OUTPUT_LIB lib)
+    doc_dir=$(get_output_dir OUTPUT_DOC doc)
+    man_dir=$(get_output_dir OUTPUT_MAN man doc)
+    info_dir=$(get_output_dir OUTPUT_INFO info doc)
+
+    local -a flags_array=(
+        --prefix="$prefix"
+        --bindir="$bin_dir/bin"
+        --sbindir="$bin_dir/sbin"
+        --includedir="$dev_dir/include"
+        --libdir="$lib_dir/lib"
+        --libexecdir="$lib_dir/libexec"
+        --localedir="$lib_dir/share/locale"
+        --mandir="$man_dir/share/man"
+        --infodir="$info_dir/share/info"
+        -Ddocdir="$doc_dir/share/doc/$name"
+        # optimisation and debug info come from the compiler flags
+        --buildtype=plain
+        # subprojects must be packaged, nothing is downloaded during the build
+        --wrap-mode=nodownload
+    )
+
+    if [ -n "${MESON_CROSS_CONFIG:-}" ]; then
+        printf '%s\n' "$MESON_CROSS_CONFIG" > "$TMPDIR/meson-cross.ini"
+        flags_array+=(--cross-file="$TMPDIR/meson-cross.ini")
+    fi
+
+    concatTo flags_array MESON_FLAGS
+
+    : "${MESON_BUILD_DIR:=build}"
+    echo "meson flags: ${flags_array[*]}"
+    meson setup "$MESON_BUILD_DIR" "${flags_array[@]}"
+    cd "$MESON_BUILD_DIR"
+
+    run_hook POST_CONFIGURE
+}
+
+meson_check_phase() {
+    run_hook PRE_CHECK
+
+    # parallel unless ENABLE_PARALLEL_CHECKING is set to "", like ninja
+    local cores=1
+    if [ -n "${ENABLE_PARALLEL_CHECKING-1}" ]; then
+        cores="$OXIDE_BUILD_CORES"
+    fi
+    local -a flags_array=(--num-processes="$cores")
+    concatTo flags_array MESON_CHECK_FLAGS
+
+    echo "meson check flags: ${flags_array[*]}"
+    meson test --no-rebuild --print-errorlogs "${flags_array[@]}"
+    unset flags_array
+
+    run_hook POST_CHECK
+}
+
+meson_install_phase() {
+    run_hook PRE_INSTALL
+
+    local -a flags_array=()
+    concatTo flags_array MESON_INSTALL_FLAGS
+
+    echo "meson install flags: ${flags_array[*]}"
+    meson install --no-rebuild "${flags_array[@]}"
+    unset flags_array
+
+    run_hook POST_INSTALL
+}
+
+# meson knows better than ninja how to run the tests and install,
+# so its phases win whatever the order of the hooks
+if [ -z "${DONT_USE_MESON_CONFIGURE:-}" ] && [ -z "${CONFIGURE_PHASE:-}" ]; then
+    CONFIGURE_PHASE=meson_configure_phase
+fi
+if [ -z "${DONT_USE_MESON_CHECK:-}" ] && [[ "${CHECK_PHASE:-ninja_check_phase}" == ninja_check_phase ]]; then
+    CHECK_PHASE=meson_check_phase
+fi
+if [ -z "${DONT_USE_MESON_INSTALL:-}" ] && [[ "${INSTALL_PHASE:-ninja_install_phase}" == ninja_install_phase ]]; then
+    INSTALL_PHASE=meson_install_phase
+fi
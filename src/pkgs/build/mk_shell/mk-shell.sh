@@ -0,0 +1,65 @@
+# shellcheck shell=bash disable=SC1090,SC2154
+#
+# Writes the environment the builder sees as $out/rc instead of building.
+# Sourcing it gives back the variables and the functions of setup.sh so
+# the phases can be run one by one, the outputs go to ./outputs.
+
+# the phases of a package builder, without running them
+if [ -n "${SHELL_BUILDER:-}" ]; then
+    eval "$(declare -f generic_build | sed '1s/^generic_build/_shell_generic_build/')"
+    generic_build() { :; }
+    source "$SHELL_BUILDER"
+    eval "$(declare -f _shell_generic_build | sed '1s/^_shell_generic_build/generic_build/')"
+    unset -f _shell_generic_build
+fi
+
+_shell_outputs="${SHELL_OUTPUTS:-$outputs}"
+
+mkdir -p "$out"
+printf '%s\n' "${SHELL_HOOK:-}" > "$out/shell-hook"
+
+_shell_variables() {
+    local _shell_var _shell_attrs
+    for _shell_var in $(compgen -v); do
+        case "$_shell_var" in
+            # set by bash or private to this build
+            BASH* | COMP* | DIRSTACK | EUID | FUNCNAME | GROUPS | HISTCMD | HOSTNAME \
+                | HOSTTYPE | IFS | LINENO | MACHTYPE | OPTERR | OPTIND | OSTYPE \
+                | PIPESTATUS | PPID | PS4 | PWD | OLDPWD | RANDOM | SECONDS | SHELLOPTS \
+                | SHLVL | SRANDOM | UID | EPOCH* | _ | _shell_*)
+                continue
+                ;;
+            HOME | TMPDIR | TEMPDIR | TMP | TEMP | PATH | outputs | prefix | SHELL_*)
+                continue
+                ;;
+        esac
+        if [[ " $_shell_outputs " == *" $_shell_var "* ]]; then
+            continue
+        fi
+        _shell_attrs="$(declare -p "$_shell_var")"
+        _shell_attrs="${_shell_attrs#declare -}"
+        if [[ "${_shell_attrs%% *}" == *r* ]]; then
+            continue
+        fi
+        declare -p "$_shell_var"
+    done
+}
+
+{
+    echo "# environment of $name"
+    _shell_variables
+    unset -f _shell_variables
+    printf 'export PATH=%q"${PATH:+:$PATH}"\n' "$PATH"
+    printf 'export TMPDIR="${TMPDIR:-/tmp}"\n'
+    printf 'outputs=%q\n' "$_shell_outputs"
+    for _shell_output in $_shell_outputs; do
+        printf 'export %s="$PWD/outputs/%s"\n' "$_shell_output" "$_shell_output"
+    done
+    echo 'prefix="$out"'
+    declare -f
+    # back to an interactive shell, an error must not close it but the
+    # phases still expect nullglob
+    echo 'set +e +u +o pipefail'
+    echo 'shopt -s nullglob'
+    printf 'source %q\n' "$out/shell-hook"
+} > "$out/rc"
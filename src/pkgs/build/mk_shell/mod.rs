@@ -0,0 +1,111 @@
+use crate::stdenv::{Stdenv, prefer_local_build_inputs};
+use oxide_core::prelude::*;
+
+// A shell with `packages` available, `$out/rc` is meant to be given to
+// `bash --rcfile` and runs `shell_hook` last
+pub struct MkShell {
+    pub stdenv: Stdenv,
+    pub name: Cow<str>,
+    pub packages: Vec<Expr>,
+    pub shell_hook: Option<Cow<str>>,
+}
+
+impl MkShell {
+    pub fn new<N>(stdenv: Stdenv, name: N) -> Self
+    where
+        N: Into<Cow<str>>,
+    {
+        Self {
+            stdenv,
+            name: name.into(),
+            packages: Vec::new(),
+            shell_hook: None,
+        }
+    }
+
+    pub fn package<T>(mut self, package: T) -> Self
+    where
+        T: Into<Expr>,
+    {
+        self.packages.push(package.into());
+        self
+    }
+
+    pub fn shell_hook<T>(mut self, shell_hook: T) -> Self
+    where
+        T: Into<Cow<str>>,
+    {
+        self.shell_hook = Some(shell_hook.into());
+        self
+    }
+}
+
+impl IntoDrv for MkShell {
+    fn into_drv(self) -> Drv {
+        let mut builder = self
+            .stdenv
+            .make_derivation()
+            .name(self.name)
+            .builder(local_file!("mk-shell.sh"))
+            .input_if("SHELL_HOOK", self.shell_hook)
+            .prefer_local_build();
+        for package in self.packages {
+            builder = builder.dep_build_host(package);
+        }
+        builder.build()
+    }
+}
+
+// The environment the builder of `drv` sees, in the same layout as `MkShell`,
+// `drv` itself is not built. It keeps the name of `drv`, which its phases
+// may use
+pub struct EnvOf {
+    pub drv: LazyDrv,
+    pub shell_hook: Option<Cow<str>>,
+}
+
+impl EnvOf {
+    pub fn new(drv: LazyDrv) -> Self {
+        Self {
+            drv,
+            shell_hook: None,
+        }
+    }
+
+    pub fn shell_hook<T>(mut self, shell_hook: T) -> Self
+    where
+        T: Into<Cow<str>>,
+    {
+        self.shell_hook = Some(shell_hook.into());
+        self
+    }
+}
+
+impl IntoDrv for EnvOf {
+    fn into_drv(self) -> Drv {
+        let mut drv = self.drv.into_drv();
+        // `StdenvBuilder` derivations run `source-stdenv.sh <builder>`,
+        // mk-shell.sh sources the builder for its phases
+        let builder = drv
+            .args
+            .pop()
+            .expect("env_of needs a derivation made by StdenvBuilder");
+        drv.args.push(local_file!("mk-shell.sh"));
+        drv.inputs.insert("SHELL_BUILDER".to_string(), builder);
+        drv.inputs
+            .insert("SHELL_OUTPUTS".to_string(), drv.outputs.join(" ").into());
+        if let Some(shell_hook) = self.shell_hook {
+            drv.inputs
+                .insert("SHELL_HOOK".to_string(), shell_hook.into());
+        }
+        drv.inputs.extend(prefer_local_build_inputs());
+        drv.outputs = vec!["out".to_string()];
+        // the rc file is not what a fixed output derivation produces
+        drv.hash = None;
+        drv
+    }
+}
+
+pub fn env_of(drv: LazyDrv) -> LazyDrv {
+    LazyDrv::new(EnvOf::new(drv))
+}
@@ -1,5 +1,19 @@
 mod fetchers;
 pub use fetchers::*;
 
+pub mod autotools;
+pub mod build_env;
+pub mod cmake;
 pub mod curl;
+pub mod docker_tools;
+pub mod go;
+pub mod make_wrapper;
+pub mod meson;
+pub mod mk_shell;
+pub mod ninja;
+pub mod perl;
 pub mod pkg_config;
+pub mod python;
+pub mod rust;
+pub mod tests;
+pub mod trivial_builders;
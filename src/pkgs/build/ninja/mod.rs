@@ -0,0 +1,38 @@
+use crate::{
+    build::forges::{FetchFromForge, ForgeArgs},
+    lib::fake_hash,
+    stdenv::Stdenv,
+};
+use oxide_core::prelude::*;
+
+pub struct Ninja {
+    pub stdenv: Stdenv,
+    pub forges: FetchFromForge,
+    pub cmake: LazyDrv,
+}
+
+impl IntoDrv for Ninja {
+    fn into_drv(self) -> Drv {
+        let version = "1.13.1";
+        self.stdenv
+            .make_derivation()
+            .name("ninja")
+            .version(version)
+            .src(self.forges.fetch_from_github(ForgeArgs::new(
+                "ninja-build",
+                "ninja",
+                format!("v{version}"),
+                fake_hash(),
+            )))
+            .dep_build_host(self.cmake)
+            // the cmake hook generates for ninja which does not exist yet
+            .input_bool("DONT_USE_CMAKE_CONFIGURE", true)
+            .configure_phase(
+                r#"cmake -B build -G "Unix Makefiles" -DCMAKE_INSTALL_PREFIX="$out" -DCMAKE_BUILD_TYPE=Release -DBUILD_TESTING=OFF
+cd build"#,
+            )
+            .input_bool("ENABLE_PARALLEL_BUILDING", true)
+            .input("SETUP_HOOK", local_file!("setup-hook.sh"))
+            .build()
+    }
+}
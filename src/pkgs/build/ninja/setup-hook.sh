@@ -0,0 +1,72 @@
+# shellcheck shell=bash disable=SC2154
+#
+# Build, check and install with ninja instead of make, set
+# DONT_USE_NINJA_BUILD, DONT_USE_NINJA_CHECK or DONT_USE_NINJA_INSTALL to opt out.
+# Unlike make, ninja is parallel unless ENABLE_PARALLEL_* is set to "".
+
+_ninja_cores() {
+    if [ -n "${!1-1}" ]; then
+        echo "$OXIDE_BUILD_CORES"
+    else
+        echo 1
+    fi
+}
+
+ninja_build_phase() {
+    run_hook PRE_BUILD
+
+    local -a flags_array=(-j"$(_ninja_cores ENABLE_PARALLEL_BUILDING)")
+    concatTo flags_array NINJA_FLAGS BUILD_FLAGS
+
+    echo "ninja build flags: ${flags_array[*]}"
+    ninja "${flags_array[@]}"
+    unset flags_array
+
+    run_hook POST_BUILD
+}
+
+ninja_check_phase() {
+    run_hook PRE_CHECK
+
+    if [ -z "${CHECK_TARGET:-}" ]; then
+        if ninja -t query test > /dev/null 2>&1; then
+            CHECK_TARGET=test
+        fi
+    fi
+
+    if [ -z "${CHECK_TARGET:-}" ]; then
+        echo "no test target found in ninja, doing nothing"
+    else
+        local -a flags_array=(-j"$(_ninja_cores ENABLE_PARALLEL_CHECKING)")
+        concatTo flags_array NINJA_FLAGS CHECK_FLAGS CHECK_TARGET
+
+        echo "ninja check flags: ${flags_array[*]}"
+        ninja "${flags_array[@]}"
+        unset flags_array
+    fi
+
+    run_hook POST_CHECK
+}
+
+ninja_install_phase() {
+    run_hook PRE_INSTALL
+
+    local -a flags_array=(-j"$(_ninja_cores ENABLE_PARALLEL_INSTALLING)")
+    concatTo flags_array NINJA_FLAGS INSTALL_FLAGS INSTALL_TARGETS=install
+
+    echo "ninja install flags: ${flags_array[*]}"
+    ninja "${flags_array[@]}"
+    unset flags_array
+
+    run_hook POST_INSTALL
+}
+
+if [ -z "${DONT_USE_NINJA_BUILD:-}" ] && [ -z "${BUILD_PHASE:-}" ]; then
+    BUILD_PHASE=ninja_build_phase
+fi
+if [ -z "${DONT_USE_NINJA_CHECK:-}" ] && [ -z "${CHECK_PHASE:-}" ]; then
+    CHECK_PHASE=ninja_check_phase
+fi
+if [ -z "${DONT_USE_NINJA_INSTALL:-}" ] && [ -z "${INSTALL_PHASE:-}" ]; then
+    INSTALL_PHASE=ninja_install_phase
+fi
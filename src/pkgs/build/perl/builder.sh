@@ -0,0 +1,79 @@
+# shellcheck shell=bash disable=SC2154
+#
+# Builder of build_perl_package: configure with Makefile.PL, or Build.PL when
+# there is none, installing the modules into $out/lib/perl5/site_perl where the
+# setup hook of perl finds them. Makefile.PL builds use the generic make phases.
+
+_perl_config() {
+    perl -MConfig -e "print \$Config{$1}"
+}
+
+configure_phase() {
+    run_hook PRE_CONFIGURE
+
+    # the dependencies are packaged, never ask CPAN for them
+    export PERL_AUTOINSTALL=--skipdeps PERL_MM_USE_DEFAULT=1
+
+    local site arch
+    site="$out/lib/perl5/site_perl/$(_perl_config version)"
+    arch="$site/$(_perl_config archname)"
+
+    local -a flags_array
+    if [ -f Makefile.PL ]; then
+        flags_array=(
+            PREFIX="$out"
+            INSTALLDIRS=site
+            INSTALLSITELIB="$site"
+            INSTALLSITEARCH="$arch"
+            INSTALLSITEBIN="$out/bin"
+            INSTALLSITESCRIPT="$out/bin"
+            INSTALLSITEMAN1DIR="$out/share/man/man1"
+            INSTALLSITEMAN3DIR="$out/share/man/man3"
+        )
+        concatTo flags_array PERL_CONFIGURE_FLAGS
+        echo "Makefile.PL flags: ${flags_array[*]}"
+        perl Makefile.PL "${flags_array[@]}"
+    elif [ -f Build.PL ]; then
+        flags_array=(
+            --installdirs site
+            --install_path lib="$site"
+            --install_path arch="$arch"
+            --install_path bin="$out/bin"
+            --install_path script="$out/bin"
+            --install_path bindoc="$out/share/man/man1"
+            --install_path libdoc="$out/share/man/man3"
+        )
+        concatTo flags_array PERL_CONFIGURE_FLAGS
+        echo "Build.PL flags: ${flags_array[*]}"
+        perl Build.PL "${flags_array[@]}"
+        : "${BUILD_PHASE:=perl_build_phase}"
+        : "${CHECK_PHASE:=perl_check_phase}"
+        : "${INSTALL_PHASE:=perl_install_phase}"
+    else
+        echo "error: neither Makefile.PL nor Build.PL found in $PWD"
+        exit 1
+    fi
+    unset flags_array
+
+    run_hook POST_CONFIGURE
+}
+
+perl_build_phase() {
+    run_hook PRE_BUILD
+    perl Build
+    run_hook POST_BUILD
+}
+
+perl_check_phase() {
+    run_hook PRE_CHECK
+    perl Build test
+    run_hook POST_CHECK
+}
+
+perl_install_phase() {
+    run_hook PRE_INSTALL
+    perl Build install
+    run_hook POST_INSTALL
+}
+
+generic_build
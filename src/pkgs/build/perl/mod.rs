@@ -0,0 +1,159 @@
+use crate::{
+    build::fetchurl::FetchUrl,
+    stdenv::{Stdenv, StdenvBuilder},
+};
+use oxide_core::prelude::*;
+
+#[derive(Clone)]
+pub struct PerlPlatform {
+    pub stdenv: Stdenv,
+    pub fetchurl: FetchUrl,
+    pub perl: LazyDrv,
+}
+
+impl PerlPlatform {
+    pub fn new(stdenv: Stdenv, fetchurl: FetchUrl, perl: LazyDrv) -> Self {
+        Self {
+            stdenv,
+            fetchurl,
+            perl,
+        }
+    }
+
+    // `file` from the directory of the PAUSE id `author` on CPAN,
+    // e.g. ETHER and Try-Tiny-0.31.tar.gz
+    pub fn fetch_cpan(&self, author: &str, file: &str, hash: Hash) -> LazyDrv {
+        let prefix = author
+            .get(..2)
+            .filter(|_| author.is_ascii())
+            .unwrap_or_else(|| {
+                panic!("{author:?} is not a PAUSE id, those have at least two ASCII characters")
+            });
+        self.fetchurl.fetch(
+            format!(
+                "mirror://cpan/authors/id/{}/{prefix}/{author}/{file}",
+                &prefix[..1]
+            ),
+            hash,
+        )
+    }
+
+    // A `StdenvBuilder` building the CPAN distribution `args.name`, the usual
+    // phase setters still apply on top of it
+    pub fn build_perl_package(&self, args: PerlPackageArgs) -> StdenvBuilder {
+        let src = args.src.unwrap_or_else(|| {
+            self.fetch_cpan(
+                &args.author,
+                &format!("{}-{}.tar.gz", args.name, args.version),
+                args.hash,
+            )
+            .into()
+        });
+        let builder = self
+            .stdenv
+            .make_derivation()
+            .name(format!("perl-{}", args.name))
+            .version(args.version)
+            .src(src)
+            .builder(local_file!("builder.sh"))
+            .dep_build_host(LazyDrv::clone(&self.perl))
+            .input_if("PERL_CONFIGURE_FLAGS", args.perl_configure_flags)
+            .optional(args.check, |builder| builder.do_check());
+        // the modules depending on this one need its dependencies to load it
+        args.dependencies
+            .into_iter()
+            .fold(builder, |builder, dep| builder.propagated_host_target(dep))
+    }
+
+    // perl with `packages` and their dependencies on its PERL5LIB
+    pub fn with_packages<I>(&self, packages: I) -> LazyDrv
+    where
+        I: IntoIterator<Item = LazyDrv>,
+    {
+        let builder = self
+            .stdenv
+            .make_derivation()
+            .name("perl-with-packages")
+            .dont_unpack()
+            // the setup hook of perl computes PERL5LIB from the dependencies
+            .dep_build_host(LazyDrv::clone(&self.perl))
+            .input("PERL", LazyDrv::clone(&self.perl))
+            .install_phase(
+                r#"mkdir -p "$out/bin"
+cat > "$out/bin/perl" <<EOF
+#!$SHELL
+export PERL5LIB="$PERL5LIB\${PERL5LIB:+:\$PERL5LIB}"
+exec "$PERL/bin/perl" "\$@"
+EOF
+chmod +x "$out/bin/perl""#,
+            );
+        packages
+            .into_iter()
+            .fold(builder, |builder, package| builder.dep_host_target(package))
+            .lazy()
+    }
+}
+
+pub struct PerlPackageArgs {
+    pub name: Cow<str>,
+    pub version: Cow<str>,
+    // PAUSE id of the author, the distribution is fetched from their CPAN directory
+    pub author: Cow<str>,
+    pub hash: Hash,
+    pub src: Option<Expr>,
+    pub dependencies: Vec<Expr>,
+    pub perl_configure_flags: Option<Cow<str>>,
+    pub check: bool,
+}
+
+impl PerlPackageArgs {
+    pub fn new<N, V, A>(name: N, version: V, author: A, hash: Hash) -> Self
+    where
+        N: Into<Cow<str>>,
+        V: Into<Cow<str>>,
+        A: Into<Cow<str>>,
+    {
+        Self {
+            name: name.into(),
+            version: version.into(),
+            author: author.into(),
+            hash,
+            src: None,
+            dependencies: Vec::new(),
+            perl_configure_flags: None,
+            check: true,
+        }
+    }
+
+    // instead of the tarball on CPAN
+    pub fn src<T>(mut self, src: T) -> Self
+    where
+        T: Into<Expr>,
+    {
+        self.src = Some(src.into());
+        self
+    }
+
+    // perl modules needed at runtime, propagated to the dependents
+    pub fn dependency<T>(mut self, dep: T) -> Self
+    where
+        T: Into<Expr>,
+    {
+        self.dependencies.push(dep.into());
+        self
+    }
+
+    // given to Makefile.PL or Build.PL
+    pub fn perl_configure_flags<T>(mut self, perl_configure_flags: T) -> Self
+    where
+        T: Into<Cow<str>>,
+    {
+        self.perl_configure_flags = Some(perl_configure_flags.into());
+        self
+    }
+
+    pub fn dont_check(mut self) -> Self {
+        self.check = false;
+        self
+    }
+}
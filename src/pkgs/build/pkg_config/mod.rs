@@ -1,22 +1,39 @@
 use super::fetchurl::FetchUrl;
-use crate::stdenv::Stdenv;
+use crate::{lib::systems::platform, stdenv::Stdenv};
 use oxide_core::{
     drv::{Drv, IntoDrv, LazyDrv},
-    hash, local_file,
+    expr, hash, local_file,
+    types::Cow,
 };
 
+pub const PKG_CONFIG_VERSION: &str = "0.29.2";
+
 pub struct PkgConfig {
     pub stdenv: Stdenv,
     pub fetchurl: FetchUrl,
     pub libiconv: LazyDrv,
     pub vanilla: Option<bool>,
+    // None keeps the config scripts of the tarball, the bootstrap of the
+    // fetchurl gnu-config is fetched with needs it
+    pub update_autotools_gnu_config_scripts_hook: Option<LazyDrv>,
 }
 
 impl IntoDrv for PkgConfig {
     fn into_drv(self) -> Drv {
         let name = "pkg-config";
-        let version = "0.29.2";
+        let version = PKG_CONFIG_VERSION;
         let vanilla = self.vanilla.unwrap_or(false);
+        // without the hook the config.guess of the tarball is kept, it
+        // calls /usr/bin/uname which is not in the sandbox
+        let post_patch: Vec<&str> = [
+            self.update_autotools_gnu_config_scripts_hook.is_none().then_some(
+                "substituteInPlace ./config.guess ./glib/config.guess --replace-fail /usr/bin/uname uname",
+            ),
+            (!vanilla).then_some("rm -f check/check-requires-private check/check-gtk check/missing"),
+        ]
+        .into_iter()
+        .flatten()
+        .collect();
         self.stdenv
             .make_derivation()
             .name(name)
@@ -30,11 +47,9 @@ impl IntoDrv for PkgConfig {
             .out("doc")
             .input_bool("STRICT_DEPS", true)
             .optional(!vanilla, |builder| builder.patch(local_file!("requires-private.patch")))
-            .post_patch(format!(
-                    "substituteInPlace ./config.guess ./glib/config.guess --replace-fail /usr/bin/uname uname\n{}", 
-                    (!vanilla).then_some("rm -f check/check-requires-private check/check-gtk check/missing").unwrap_or_default(),
-            ))
+            .optional(!post_patch.is_empty(), |builder| builder.post_patch(post_patch.join("\n")))
             .dep_build_host(self.libiconv)
+            .dep_build_host_if(self.update_autotools_gnu_config_scripts_hook)
             .configure_flags("--with-internal-glib")
             .input_bool("ENABLE_PARALLEL_BUILDING", true)
             .do_check()
@@ -42,3 +57,55 @@ impl IntoDrv for PkgConfig {
             .build()
     }
 }
+
+// pkg-config with the setup hook finding the .pc files of the dependencies,
+// prefixed with the target triple when the target is another platform
+pub struct PkgConfigWrapper {
+    pub stdenv: Stdenv,
+    pub pkg_config: LazyDrv,
+    // the version of `pkg_config`
+    pub version: Cow<str>,
+}
+
+impl IntoDrv for PkgConfigWrapper {
+    fn into_drv(self) -> Drv {
+        let target = platform(self.stdenv.target_platform);
+        let target_prefix = if self.stdenv.target_platform == self.stdenv.host_platform {
+            String::new()
+        } else {
+            format!("{}-", target.config)
+        };
+        self.stdenv
+            .make_derivation()
+            .name("pkg-config-wrapper")
+            .version(self.version)
+            .dont_unpack()
+            .input("PKG_CONFIG_UNWRAPPED", self.pkg_config)
+            .input("TARGET_PREFIX", target_prefix)
+            .input("SUFFIX_SALT", target.config.replace('-', "_"))
+            .input("WRAPPER", local_file!("wrapper.sh"))
+            .input(
+                "SETUP_HOOKS",
+                expr![
+                    local_file!("../setup-hooks/role.bash"),
+                    local_file!("setup-hook.sh"),
+                ],
+            )
+            .install_phase(
+                r#"mkdir -p "$out/bin"
+substitute "$WRAPPER" "$out/bin/${TARGET_PREFIX}pkg-config" \
+    --replace-fail @shell@ "$SHELL" \
+    --replace-fail @pkg_config@ "$PKG_CONFIG_UNWRAPPED" \
+    --replace-fail @suffix_salt@ "$SUFFIX_SALT"
+chmod +x "$out/bin/${TARGET_PREFIX}pkg-config"
+# pkg.m4 for aclocal
+ln -s "$PKG_CONFIG_UNWRAPPED/share" "$out/share""#,
+            )
+            .post_fix(
+                r#"substituteInPlace "$out/nix-support/setup-hook" \
+    --replace-fail @suffix_salt@ "$SUFFIX_SALT" \
+    --replace-fail @target_prefix@ "$TARGET_PREFIX""#,
+            )
+            .build()
+    }
+}
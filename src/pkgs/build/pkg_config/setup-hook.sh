@@ -0,0 +1,21 @@
+# shellcheck shell=bash disable=SC2154
+#
+# Adds the lib/pkgconfig and share/pkgconfig of the dependencies to the
+# PKG_CONFIG_PATH of their role, PKG_CONFIG_PATH_FOR_BUILD for the build
+# platform, and sets PKG_CONFIG of the role of the target to the wrapper.
+# Needs role.bash sourced before.
+
+add_pkg_config_path() {
+    local role_post
+    get_host_role_env_hook
+
+    addToSearchPath "PKG_CONFIG_PATH${role_post}" "$1/lib/pkgconfig"
+    addToSearchPath "PKG_CONFIG_PATH${role_post}" "$1/share/pkgconfig"
+}
+
+get_target_role
+get_target_role_wrapper PKG_CONFIG_WRAPPER @suffix_salt@
+
+addEnvHooks "$targetOffset" add_pkg_config_path
+
+export "PKG_CONFIG${role_post}=@target_prefix@pkg-config"
@@ -0,0 +1,23 @@
+#!@shell@
+# pkg-config searching the PKG_CONFIG_PATH of the roles the setup hook gave
+# it, outside of builds the environment is kept as it is
+
+path=
+roles=
+if [ -n "${PKG_CONFIG_WRAPPER_TARGET_BUILD_@suffix_salt@:-}" ]; then
+    path="${path:+$path:}${PKG_CONFIG_PATH_FOR_BUILD:-}"
+    roles=1
+fi
+if [ -n "${PKG_CONFIG_WRAPPER_TARGET_HOST_@suffix_salt@:-}" ]; then
+    path="${path:+$path:}${PKG_CONFIG_PATH:-}"
+    roles=1
+fi
+if [ -n "${PKG_CONFIG_WRAPPER_TARGET_TARGET_@suffix_salt@:-}" ]; then
+    path="${path:+$path:}${PKG_CONFIG_PATH_FOR_TARGET:-}"
+    roles=1
+fi
+if [ -n "$roles" ]; then
+    export PKG_CONFIG_PATH="$path"
+fi
+
+exec @pkg_config@/bin/pkg-config "$@"
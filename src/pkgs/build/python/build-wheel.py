@@ -0,0 +1,29 @@
+# Builds a wheel of the project in the current directory into argv[1]
+# with its PEP 517 backend, in the current environment, without pip.
+# The other arguments are KEY=VALUE config settings for the backend.
+
+import importlib
+import os
+import sys
+import tomllib
+
+out_dir = os.path.abspath(sys.argv[1])
+config_settings = dict(arg.split("=", 1) for arg in sys.argv[2:])
+
+try:
+    with open("pyproject.toml", "rb") as f:
+        build_system = tomllib.load(f).get("build-system", {})
+except FileNotFoundError:
+    build_system = {}
+
+# the PEP 517 fallback for projects without a build-backend
+backend_name = build_system.get("build-backend", "setuptools.build_meta:__legacy__")
+sys.path[:0] = [os.path.abspath(path) for path in build_system.get("backend-path", [])]
+module_name, _, object_path = backend_name.partition(":")
+backend = importlib.import_module(module_name)
+for attr in filter(None, object_path.split(".")):
+    backend = getattr(backend, attr)
+
+os.makedirs(out_dir, exist_ok=True)
+wheel = backend.build_wheel(out_dir, config_settings or None)
+print(f"built {wheel}")
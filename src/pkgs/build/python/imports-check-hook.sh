@@ -0,0 +1,25 @@
+# shellcheck shell=bash disable=SC2154
+#
+# After the install check, import each module of PYTHON_IMPORTS_CHECK from
+# $out with only the dependencies on PYTHONPATH, catches missing dependencies.
+# -P keeps the source tree, the current directory, out of sys.path.
+
+python_imports_check_phase() {
+    local -a modules_array=()
+    concatTo modules_array PYTHON_IMPORTS_CHECK
+    if (( ${#modules_array[@]} == 0 )); then
+        return
+    fi
+
+    echo "checking imports: ${modules_array[*]}"
+    PYTHONPATH="$out/$PYTHON_SITE_PACKAGES${PYTHONPATH:+:$PYTHONPATH}" python3 -P -c '
+import importlib
+import sys
+
+for module in sys.argv[1:]:
+    importlib.import_module(module)
+' "${modules_array[@]}"
+    unset modules_array
+}
+
+POST_PHASES+=(PYTHON_IMPORTS_CHECK_PHASE)
@@ -0,0 +1,24 @@
+# shellcheck shell=bash disable=SC2154
+#
+# Install the wheels of dist/ into $out/$PYTHON_SITE_PACKAGES and compile
+# their bytecode, set DONT_USE_PYTHON_INSTALL to opt out.
+
+python_install_phase() {
+    run_hook PRE_INSTALL
+
+    local wheel
+    for wheel in dist/*.whl; do
+        echo "installing $wheel"
+        python3 @out@/share/python/install-wheel.py "$wheel" "$out" "$PYTHON_SITE_PACKAGES"
+    done
+
+    # checked by hash, timestamps of the store are meaningless
+    python3 -m compileall -q -j "$OXIDE_BUILD_CORES" \
+        --invalidation-mode unchecked-hash "$out/$PYTHON_SITE_PACKAGES"
+
+    run_hook POST_INSTALL
+}
+
+if [ -z "${DONT_USE_PYTHON_INSTALL:-}" ] && [ -z "${INSTALL_PHASE:-}" ]; then
+    INSTALL_PHASE=python_install_phase
+fi
@@ -0,0 +1,73 @@
+# Installs the wheel argv[1] into the prefix argv[2] without pip:
+# modules go to argv[3], the site-packages relative to the prefix,
+# the .data directories to their scheme and the console scripts of
+# entry_points.txt get a launcher running the current interpreter.
+
+import configparser
+import os
+import sys
+import zipfile
+
+wheel, prefix, site_packages = sys.argv[1:]
+site = os.path.join(prefix, site_packages)
+version = f"{sys.version_info.major}.{sys.version_info.minor}"
+scheme = {
+    "purelib": site,
+    "platlib": site,
+    "scripts": os.path.join(prefix, "bin"),
+    "headers": os.path.join(prefix, "include", f"python{version}"),
+    "data": prefix,
+}
+interpreter = sys.executable.encode()
+
+
+def write(path, data, executable):
+    os.makedirs(os.path.dirname(path), exist_ok=True)
+    with open(path, "wb") as f:
+        f.write(data)
+    os.chmod(path, 0o755 if executable else 0o644)
+
+
+with zipfile.ZipFile(wheel) as zf:
+    dist_info = None
+    for info in zf.infolist():
+        if info.is_dir():
+            continue
+        top, _, rest = info.filename.partition("/")
+        data = zf.read(info)
+        executable = bool((info.external_attr >> 16) & 0o111)
+        if top.endswith(".dist-info"):
+            dist_info = top
+        if top.endswith(".data"):
+            kind, _, rest = rest.partition("/")
+            path = os.path.join(scheme[kind], rest)
+            if kind == "scripts":
+                executable = True
+                for shebang in (b"#!pythonw", b"#!python"):
+                    if data.startswith(shebang):
+                        data = b"#!" + interpreter + data[len(shebang):]
+                        break
+        else:
+            path = os.path.join(site, info.filename)
+        write(path, data, executable)
+
+    if dist_info is None:
+        sys.exit(f"error: {wheel} has no .dist-info directory")
+
+    entry_points = configparser.ConfigParser(delimiters=("=",), interpolation=None)
+    entry_points.optionxform = str
+    entry_points.read(os.path.join(site, dist_info, "entry_points.txt"))
+    for section in ("console_scripts", "gui_scripts"):
+        if not entry_points.has_section(section):
+            continue
+        for name, value in entry_points.items(section):
+            module, _, function = value.partition(":")
+            function = function.split("[")[0].strip()
+            top = function.split(".")[0]
+            launcher = (
+                f"#!{sys.executable}\n"
+                "import sys\n"
+                f"from {module.strip()} import {top}\n"
+                f"sys.exit({function}())\n"
+            )
+            write(os.path.join(scheme["scripts"], name), launcher.encode(), True)
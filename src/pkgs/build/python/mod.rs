@@ -0,0 +1,156 @@
+use crate::stdenv::{Stdenv, StdenvBuilder};
+use oxide_core::prelude::*;
+
+#[derive(Clone)]
+pub struct PythonPlatform {
+    pub stdenv: Stdenv,
+    pub python3: LazyDrv,
+    pub wheel_build_hook: LazyDrv,
+    pub install_hook: LazyDrv,
+    pub imports_check_hook: LazyDrv,
+}
+
+// A package whose setup hook is `hook`, `script` is installed in
+// $out/share/python and @out@ of the hook replaced to find it
+fn setup_hook(
+    stdenv: &Stdenv,
+    name: &'static str,
+    hook: Expr,
+    script: Option<(&str, Expr)>,
+) -> LazyDrv {
+    let builder = stdenv
+        .make_derivation()
+        .name(name)
+        .dont_unpack()
+        .input("SETUP_HOOK", hook)
+        .post_fix(
+            r#"substituteInPlace "$out/nix-support/setup-hook" --replace-quiet @out@ "$out""#,
+        );
+    match script {
+        Some((file_name, script)) => builder.input("SCRIPT", script).install_phase(format!(
+            r#"install -D -m644 "$SCRIPT" "$out/share/python/{file_name}""#
+        )),
+        None => builder,
+    }
+    .lazy()
+}
+
+impl PythonPlatform {
+    pub fn new(stdenv: Stdenv, python3: LazyDrv) -> Self {
+        let wheel_build_hook = setup_hook(
+            &stdenv,
+            "python-wheel-build-hook",
+            local_file!("wheel-build-hook.sh"),
+            Some(("build-wheel.py", local_file!("build-wheel.py"))),
+        );
+        let install_hook = setup_hook(
+            &stdenv,
+            "python-install-hook",
+            local_file!("install-hook.sh"),
+            Some(("install-wheel.py", local_file!("install-wheel.py"))),
+        );
+        let imports_check_hook = setup_hook(
+            &stdenv,
+            "python-imports-check-hook",
+            local_file!("imports-check-hook.sh"),
+            None,
+        );
+        Self {
+            stdenv,
+            python3,
+            wheel_build_hook,
+            install_hook,
+            imports_check_hook,
+        }
+    }
+
+    // A `StdenvBuilder` building a wheel of `args.src` with its PEP 517
+    // backend and installing it into the site-packages of python3
+    pub fn build_python_package(&self, args: PythonPackageArgs) -> StdenvBuilder {
+        let builder = self
+            .stdenv
+            .make_derivation()
+            .name(args.name)
+            .version(args.version)
+            .src(args.src)
+            .dep_build_host(LazyDrv::clone(&self.python3))
+            .dep_build_host(LazyDrv::clone(&self.wheel_build_hook))
+            .dep_build_host(LazyDrv::clone(&self.install_hook))
+            .dep_build_host(LazyDrv::clone(&self.imports_check_hook))
+            .input("PYTHON_IMPORTS_CHECK", args.imports_check.join(" "))
+            .input_if("PYTHON_CONFIG_SETTINGS", args.config_settings);
+        let builder = args
+            .build_system
+            .into_iter()
+            .fold(builder, |builder, dep| builder.dep_build_host(dep));
+        // the packages depending on this one need its dependencies to import it
+        args.dependencies
+            .into_iter()
+            .fold(builder, |builder, dep| builder.propagated_host_target(dep))
+    }
+}
+
+pub struct PythonPackageArgs {
+    pub name: Cow<str>,
+    pub version: Cow<str>,
+    pub src: Expr,
+    pub build_system: Vec<Expr>,
+    pub dependencies: Vec<Expr>,
+    pub imports_check: Vec<Cow<str>>,
+    pub config_settings: Option<Cow<str>>,
+}
+
+impl PythonPackageArgs {
+    pub fn new<N, V, S>(name: N, version: V, src: S) -> Self
+    where
+        N: Into<Cow<str>>,
+        V: Into<Cow<str>>,
+        S: Into<Expr>,
+    {
+        Self {
+            name: name.into(),
+            version: version.into(),
+            src: src.into(),
+            build_system: Vec::new(),
+            dependencies: Vec::new(),
+            imports_check: Vec::new(),
+            config_settings: None,
+        }
+    }
+
+    // the PEP 517 backend and its requirements, e.g. setuptools
+    pub fn build_system<T>(mut self, dep: T) -> Self
+    where
+        T: Into<Expr>,
+    {
+        self.build_system.push(dep.into());
+        self
+    }
+
+    // python packages needed at runtime, propagated to the dependents
+    pub fn dependency<T>(mut self, dep: T) -> Self
+    where
+        T: Into<Expr>,
+    {
+        self.dependencies.push(dep.into());
+        self
+    }
+
+    // a module that must import from the installed package
+    pub fn imports_check<T>(mut self, module: T) -> Self
+    where
+        T: Into<Cow<str>>,
+    {
+        self.imports_check.push(module.into());
+        self
+    }
+
+    // KEY=VALUE pairs given to the backend, separated by spaces
+    pub fn config_settings<T>(mut self, config_settings: T) -> Self
+    where
+        T: Into<Cow<str>>,
+    {
+        self.config_settings = Some(config_settings.into());
+        self
+    }
+}
@@ -0,0 +1,23 @@
+# shellcheck shell=bash disable=SC2154
+#
+# Build a wheel into dist/ with the PEP 517 backend declared in pyproject.toml,
+# set DONT_USE_PYTHON_WHEEL_BUILD to opt out.
+# The backend and its requirements must be dependencies, nothing is downloaded.
+
+python_wheel_build_phase() {
+    run_hook PRE_BUILD
+
+    # KEY=VALUE config settings given to the backend
+    local -a flags_array=()
+    concatTo flags_array PYTHON_CONFIG_SETTINGS
+
+    echo "python config settings: ${flags_array[*]}"
+    python3 @out@/share/python/build-wheel.py dist "${flags_array[@]}"
+    unset flags_array
+
+    run_hook POST_BUILD
+}
+
+if [ -z "${DONT_USE_PYTHON_WHEEL_BUILD:-}" ] && [ -z "${BUILD_PHASE:-}" ]; then
+    BUILD_PHASE=python_wheel_build_phase
+fi
@@ -0,0 +1,100 @@
+# shellcheck shell=bash disable=SC2154
+#
+# Builder of build_rust_package: the generic phases with cargo instead of make.
+# Custom phases and their pre and post hooks work as with the default builder.
+
+configure_phase() {
+    run_hook PRE_CONFIGURE
+
+    # cargo never touches the network, every crate comes from $CARGO_VENDOR
+    export CARGO_HOME="$TMPDIR/cargo-home"
+    mkdir -p "$CARGO_HOME"
+    {
+        sed "s|@vendor@|$CARGO_VENDOR/vendor|g" "$CARGO_VENDOR/config.toml"
+        echo
+        echo "[net]"
+        echo "offline = true"
+    } > "$CARGO_HOME/config.toml"
+
+    if ! cmp -s "$CARGO_VENDOR/Cargo.lock" Cargo.lock; then
+        echo "error: Cargo.lock differs from the one the crates were vendored for"
+        echo "hint: update cargo_hash"
+        diff -u "$CARGO_VENDOR/Cargo.lock" Cargo.lock || true
+        exit 1
+    fi
+
+    run_hook POST_CONFIGURE
+}
+
+
+_cargo_flags() {
+    local -n flags_ref="$1"
+    flags_ref=(--release --frozen -j "$OXIDE_BUILD_CORES")
+    if [ -n "${CARGO_BUILD_TARGET:-}" ]; then
+        flags_ref+=(--target "$CARGO_BUILD_TARGET")
+    fi
+    if [ -n "${CARGO_NO_DEFAULT_FEATURES:-}" ]; then
+        flags_ref+=(--no-default-features)
+    fi
+    if [ -n "${CARGO_FEATURES:-}" ]; then
+        flags_ref+=(--features "$CARGO_FEATURES")
+    fi
+}
+
+
+build_phase() {
+    run_hook PRE_BUILD
+
+    local -a flags_array
+    _cargo_flags flags_array
+    concatTo flags_array CARGO_BUILD_FLAGS
+
+    cargo build "${flags_array[@]}"
+    unset flags_array
+
+    run_hook POST_BUILD
+}
+
+
+check_phase() {
+    run_hook PRE_CHECK
+
+    local -a flags_array
+    _cargo_flags flags_array
+    concatTo flags_array CARGO_TEST_FLAGS
+
+    cargo test "${flags_array[@]}"
+    unset flags_array
+
+    run_hook POST_CHECK
+}
+
+
+install_phase() {
+    run_hook PRE_INSTALL
+
+    local release_dir="target/${CARGO_BUILD_TARGET:+$CARGO_BUILD_TARGET/}release"
+
+    # binaries go to bin, shared and static libraries to lib
+    local file
+    for file in "$release_dir"/*; do
+        [ -f "$file" ] || continue
+        case "$file" in
+            *.so | *.a)
+                install -Dm644 -t "$out/lib" "$file"
+                ;;
+            *.d | *.rlib)
+                ;;
+            *)
+                if [ -x "$file" ]; then
+                    install -Dm755 -t "$out/bin" "$file"
+                fi
+                ;;
+        esac
+    done
+
+    run_hook POST_INSTALL
+}
+
+
+generic_build
@@ -0,0 +1,160 @@
+use crate::{
+    build::fetchurl::FetchUrl,
+    development::compilers::rust::{RustBin, rust_system, rust_target},
+    stdenv::Stdenv,
+    stdenv::StdenvBuilder,
+};
+use oxide_core::prelude::*;
+
+#[derive(Clone)]
+pub struct RustPlatform {
+    pub stdenv: Stdenv,
+    pub fetchurl: FetchUrl,
+    // rustc and cargo
+    pub rust: LazyDrv,
+}
+
+impl RustPlatform {
+    pub fn new(stdenv: Stdenv, fetchurl: FetchUrl, rust: LazyDrv) -> Self {
+        Self {
+            stdenv,
+            fetchurl,
+            rust,
+        }
+    }
+
+    // `rust` with the standard library of the `target` triple as well
+    fn rust_for(&self, target: &str) -> LazyDrv {
+        let system = rust_system(target);
+        if system == self.stdenv.host_platform {
+            return LazyDrv::clone(&self.rust);
+        }
+        LazyDrv::new(RustBin {
+            stdenv: Stdenv::clone(&self.stdenv),
+            fetchurl: FetchUrl::clone(&self.fetchurl),
+            targets: vec![system],
+        })
+    }
+
+    // The crates of the Cargo.lock of `src` as a fixed output derivation
+    pub fn fetch_cargo_vendor<N, S>(&self, name: N, src: S, hash: Hash) -> LazyDrv
+    where
+        N: Into<Cow<str>>,
+        S: Into<Expr>,
+    {
+        self.stdenv
+            .make_derivation()
+            .name(format!("{}-vendor", name.into()))
+            .src(src)
+            .fixed_hash(hash)
+            .builder(local_file!("vendor.sh"))
+            .dep_build_host(LazyDrv::clone(&self.rust))
+            .input("NORMALISE", local_file!("../fetchers/normalise.sh"))
+            .lazy()
+    }
+
+    // A `StdenvBuilder` building `args.src` with cargo, the usual phase
+    // setters still apply on top of it
+    pub fn build_rust_package(&self, args: RustPackageArgs) -> StdenvBuilder {
+        let vendor = self.fetch_cargo_vendor(
+            format!("{}-{}", args.name, args.version),
+            Expr::clone(&args.src),
+            args.cargo_hash,
+        );
+        let target = args
+            .target
+            .unwrap_or_else(|| rust_target(self.stdenv.host_platform).into());
+        self.stdenv
+            .make_derivation()
+            .name(args.name)
+            .version(args.version)
+            .src(args.src)
+            .builder(local_file!("builder.sh"))
+            .dep_build_host(self.rust_for(&target))
+            .input("CARGO_VENDOR", vendor)
+            .input("CARGO_BUILD_TARGET", target)
+            .input("CARGO_FEATURES", args.features.join(" "))
+            .input_bool("CARGO_NO_DEFAULT_FEATURES", args.no_default_features)
+            .input_if("CARGO_BUILD_FLAGS", args.cargo_build_flags)
+            .input_if("CARGO_TEST_FLAGS", args.cargo_test_flags)
+            .optional(args.check, |builder| builder.do_check())
+    }
+}
+
+pub struct RustPackageArgs {
+    pub name: Cow<str>,
+    pub version: Cow<str>,
+    pub src: Expr,
+    pub cargo_hash: Hash,
+    pub features: Vec<Cow<str>>,
+    pub no_default_features: bool,
+    pub target: Option<Cow<str>>,
+    pub cargo_build_flags: Option<Cow<str>>,
+    pub cargo_test_flags: Option<Cow<str>>,
+    pub check: bool,
+}
+
+impl RustPackageArgs {
+    pub fn new<N, V, S>(name: N, version: V, src: S, cargo_hash: Hash) -> Self
+    where
+        N: Into<Cow<str>>,
+        V: Into<Cow<str>>,
+        S: Into<Expr>,
+    {
+        Self {
+            name: name.into(),
+            version: version.into(),
+            src: src.into(),
+            cargo_hash,
+            features: Vec::new(),
+            no_default_features: false,
+            target: None,
+            cargo_build_flags: None,
+            cargo_test_flags: None,
+            check: true,
+        }
+    }
+
+    pub fn feature<T>(mut self, feature: T) -> Self
+    where
+        T: Into<Cow<str>>,
+    {
+        self.features.push(feature.into());
+        self
+    }
+
+    pub fn no_default_features(mut self) -> Self {
+        self.no_default_features = true;
+        self
+    }
+
+    // cross compile for the `target` triple instead of the host platform
+    pub fn target<T>(mut self, target: T) -> Self
+    where
+        T: Into<Cow<str>>,
+    {
+        self.target = Some(target.into());
+        self
+    }
+
+    pub fn cargo_build_flags<T>(mut self, cargo_build_flags: T) -> Self
+    where
+        T: Into<Cow<str>>,
+    {
+        self.cargo_build_flags = Some(cargo_build_flags.into());
+        self
+    }
+
+    pub fn cargo_test_flags<T>(mut self, cargo_test_flags: T) -> Self
+    where
+        T: Into<Cow<str>>,
+    {
+        self.cargo_test_flags = Some(cargo_test_flags.into());
+        self
+    }
+
+    pub fn dont_check(mut self) -> Self {
+        self.check = false;
+        self
+    }
+}
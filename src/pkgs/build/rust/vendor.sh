@@ -0,0 +1,27 @@
+# shellcheck shell=bash disable=SC1091,SC2154
+#
+# Vendors the crates of Cargo.lock into $out, runs as a fixed output
+# derivation so cargo can reach the registry and git dependencies.
+
+source "$NORMALISE"
+
+export CARGO_HOME="$TMPDIR/cargo-home"
+mkdir -p "$CARGO_HOME"
+
+run_phase UNPACK_PHASE
+run_phase PATCH_PHASE
+
+if [ ! -f Cargo.lock ]; then
+    echo "error: $name has no Cargo.lock, build_rust_package needs one to vendor the crates"
+    exit 1
+fi
+
+mkdir -p "$out"
+cargo vendor --locked --versioned-dirs "$out/vendor" > config.toml
+
+# the config points at this build's output which build_rust_package
+# substitutes with wherever the vendored crates end up
+sed "s|$out/vendor|@vendor@|g" config.toml > "$out/config.toml"
+cp Cargo.lock "$out/Cargo.lock"
+
+normalise_tree "$out"
@@ -0,0 +1,93 @@
+# shellcheck shell=bash
+#
+# make_wrapper ORIGINAL WRAPPER ARGS writes a script WRAPPER running ORIGINAL
+# after setting up its environment, ARGS are
+#   --set VAR VALUE          export VAR=VALUE
+#   --set-default VAR VALUE  export VAR=VALUE unless VAR is already set
+#   --unset VAR              unset VAR
+#   --prefix VAR SEP VALUE   prepend VALUE to VAR, separated by SEP
+#   --suffix VAR SEP VALUE   append VALUE to VAR, separated by SEP
+#   --add-flags FLAGS        pass FLAGS before the arguments of the wrapper
+#   --run COMMAND            run COMMAND before ORIGINAL
+#   --chdir DIR              cd into DIR before running ORIGINAL
+#   --argv0 NAME             run ORIGINAL with NAME as its $0
+# the values are quoted, FLAGS and COMMAND are written as they are.
+
+make_wrapper() {
+    local original="$1" wrapper="$2"
+    shift 2
+
+    if [[ ! -f "$original" || ! -x "$original" ]]; then
+        echo "make_wrapper: $original is not an executable file" >&2
+        return 1
+    fi
+
+    local argv0='"$0"'
+    local -a flags_array=()
+    mkdir -p "$(dirname "$wrapper")"
+    {
+        echo "#!$SHELL -e"
+        while (( $# > 0 )); do
+            case "$1" in
+                --set)
+                    echo "export $2=$(printf %q "$3")"
+                    shift 3
+                    ;;
+                --set-default)
+                    echo "export $2=\${$2-$(printf %q "$3")}"
+                    shift 3
+                    ;;
+                --unset)
+                    echo "unset $2"
+                    shift 2
+                    ;;
+                --prefix)
+                    echo "export $2=$(printf %q "$4")\${$2:+$(printf %q "$3")\$$2}"
+                    shift 4
+                    ;;
+                --suffix)
+                    echo "export $2=\${$2:+\$$2$(printf %q "$3")}$(printf %q "$4")"
+                    shift 4
+                    ;;
+                --add-flags)
+                    flags_array+=("$2")
+                    shift 2
+                    ;;
+                --run)
+                    echo "$2"
+                    shift 2
+                    ;;
+                --chdir)
+                    echo "cd $(printf %q "$2")"
+                    shift 2
+                    ;;
+                --argv0)
+                    argv0=$(printf %q "$2")
+                    shift 2
+                    ;;
+                *)
+                    echo "make_wrapper: unknown argument $1" >&2
+                    return 1
+                    ;;
+            esac
+        done
+        echo "exec -a $argv0 $(printf %q "$original") ${flags_array[*]} \"\$@\""
+    } > "$wrapper"
+    chmod +x "$wrapper"
+    unset flags_array
+}
+
+# wrap_program PROGRAM ARGS moves PROGRAM to .PROGRAM-wrapped and puts a
+# wrapper made with ARGS in its place
+wrap_program() {
+    local program="$1"
+    shift
+
+    local hidden
+    hidden="$(dirname "$program")/.$(basename "$program")-wrapped"
+    while [ -e "$hidden" ]; do
+        hidden="${hidden}_"
+    done
+    mv "$program" "$hidden"
+    make_wrapper "$hidden" "$program" "$@"
+}
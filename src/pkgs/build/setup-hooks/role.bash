@@ -1 +1,51 @@
-# TODO
+# shellcheck shell=bash disable=SC2034,SC2154
+#
+# A package can be a dependency in several roles at once, these set role_post
+# to the suffix of the variables of a role: _FOR_BUILD, nothing for the host
+# or _FOR_TARGET, e.g. PKG_CONFIG_PATH${role_post}.
+
+get_role() {
+    case "$1" in
+        -1) role_post=_FOR_BUILD ;;
+        0) role_post= ;;
+        1) role_post=_FOR_TARGET ;;
+        *)
+            echo "$1 is not the offset of a platform, improper sort of dependency" >&2
+            return 1
+            ;;
+    esac
+}
+
+# The roles of the platforms of the package whose setup hook is sourced,
+# relative to the package being built
+get_host_role() {
+    get_role "$hostOffset"
+}
+
+get_target_role() {
+    get_role "$targetOffset"
+}
+
+# The roles of the platforms of the dependency given to an env hook
+get_host_role_env_hook() {
+    get_role "$depHostOffset"
+}
+
+get_target_role_env_hook() {
+    get_role "$depTargetOffset"
+}
+
+# For wrappers of tools producing or reading files for their target platform,
+# exports ${1}_TARGET_{BUILD,HOST,TARGET}_${2} for each role of the target,
+# $2 tells the wrappers of the different target platforms apart
+get_target_role_wrapper() {
+    case "$targetOffset" in
+        -1) export "${1}_TARGET_BUILD_${2}=1" ;;
+        0) export "${1}_TARGET_HOST_${2}=1" ;;
+        1) export "${1}_TARGET_TARGET_${2}=1" ;;
+        *)
+            echo "$targetOffset is not the offset of a platform, improper sort of dependency" >&2
+            return 1
+            ;;
+    esac
+}
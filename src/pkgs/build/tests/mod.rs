@@ -0,0 +1,170 @@
+use crate::{build::trivial_builders::TrivialBuilders, lib::parse_drv_name};
+use oxide_core::prelude::*;
+
+// Derivations checking a built package, each of them only builds when its
+// check passes. They are named after the package and find the outputs
+// to check themselves
+#[derive(Clone)]
+pub struct Tests {
+    pub trivial_builders: TrivialBuilders,
+    pub pkg_config: LazyDrv,
+}
+
+impl Tests {
+    pub fn new(trivial_builders: TrivialBuilders, pkg_config: LazyDrv) -> Self {
+        Self {
+            trivial_builders,
+            pkg_config,
+        }
+    }
+
+    // `command`, found in the binaries of `pkg`, prints the version `pkg`
+    // was built with
+    pub fn test_version<S>(&self, pkg: &LazyDrv, command: S) -> LazyDrv
+    where
+        S: Into<String>,
+    {
+        LazyDrv::new(TestVersion {
+            trivial_builders: TrivialBuilders::clone(&self.trivial_builders),
+            pkg: LazyDrv::clone(pkg),
+            command: command.into(),
+        })
+    }
+
+    // The .pc files of `modules` are valid and have the version of `pkg`
+    pub fn test_pkg_config(&self, pkg: &LazyDrv, modules: &[&str]) -> LazyDrv {
+        LazyDrv::new(TestPkgConfig {
+            trivial_builders: TrivialBuilders::clone(&self.trivial_builders),
+            pkg_config: LazyDrv::clone(&self.pkg_config),
+            pkg: LazyDrv::clone(pkg),
+            modules: modules.iter().map(|module| module.to_string()).collect(),
+        })
+    }
+
+    // A program including `header` of the dev output of `pkg` and making
+    // `call` to one of its functions, linked against `libs`, runs
+    pub fn test_linking(&self, pkg: &LazyDrv, libs: &[&str], header: &str, call: &str) -> LazyDrv {
+        LazyDrv::new(TestLinking {
+            trivial_builders: TrivialBuilders::clone(&self.trivial_builders),
+            pkg: LazyDrv::clone(pkg),
+            libs: libs.iter().map(|lib| lib.to_string()).collect(),
+            header: header.to_string(),
+            call: call.to_string(),
+        })
+    }
+}
+
+// The derivation of `pkg` with its name and version, tests are meaningless
+// without a version
+fn name_version(pkg: &LazyDrv) -> (Drv, String, String) {
+    let drv = LazyDrv::clone(pkg).into_drv();
+    let (name, version) = parse_drv_name(&drv.name);
+    let version = version.unwrap_or_else(|| panic!("{} has no version", drv.name));
+    let (name, version) = (name.to_string(), version.to_string());
+    (drv, name, version)
+}
+
+// The first of `outputs` `drv` has, its default one otherwise
+fn output(pkg: &LazyDrv, drv: &Drv, outputs: &[&str]) -> Expr {
+    match outputs
+        .iter()
+        .find(|output| drv.outputs.iter().any(|o| o == *output))
+    {
+        Some(output) => pkg.out(*output),
+        None => Expr::Drv(LazyDrv::clone(pkg), None),
+    }
+}
+
+struct TestVersion {
+    trivial_builders: TrivialBuilders,
+    pkg: LazyDrv,
+    command: String,
+}
+
+impl IntoDrv for TestVersion {
+    fn into_drv(self) -> Drv {
+        let (_, name, version) = name_version(&self.pkg);
+        self.trivial_builders
+            .run_command(
+                format!("{name}-test-version"),
+                r#"output=$(eval "$COMMAND" 2>&1)
+echo "$output"
+case "$output" in
+*"$VERSION"*) ;;
+*)
+    echo "$COMMAND does not print $VERSION"
+    exit 1
+    ;;
+esac
+touch "$out""#,
+            )
+            .dep_build_host(self.pkg)
+            .input("COMMAND", self.command)
+            .input("VERSION", version)
+            .build()
+    }
+}
+
+struct TestPkgConfig {
+    trivial_builders: TrivialBuilders,
+    pkg_config: LazyDrv,
+    pkg: LazyDrv,
+    modules: Vec<String>,
+}
+
+impl IntoDrv for TestPkgConfig {
+    fn into_drv(self) -> Drv {
+        let (drv, name, version) = name_version(&self.pkg);
+        self.trivial_builders
+            .run_command(
+                format!("{name}-test-pkg-config"),
+                r#"export PKG_CONFIG_PATH="$pkg_dev/lib/pkgconfig:$pkg_dev/share/pkgconfig"
+for module in $MODULES; do
+    pkg-config --validate "$module"
+    module_version=$(pkg-config --modversion "$module")
+    if [ "$module_version" != "$VERSION" ]; then
+        echo "$module has version $module_version instead of $VERSION"
+        exit 1
+    fi
+done
+touch "$out""#,
+            )
+            .dep_build_host(self.pkg_config)
+            .input("pkg_dev", output(&self.pkg, &drv, &["dev"]))
+            .input("MODULES", self.modules.join(" "))
+            .input("VERSION", version)
+            .build()
+    }
+}
+
+struct TestLinking {
+    trivial_builders: TrivialBuilders,
+    pkg: LazyDrv,
+    libs: Vec<String>,
+    header: String,
+    call: String,
+}
+
+impl IntoDrv for TestLinking {
+    fn into_drv(self) -> Drv {
+        let (drv, name, _) = name_version(&self.pkg);
+        let libs: Vec<String> = self.libs.iter().map(|lib| format!("-l{lib}")).collect();
+        self.trivial_builders
+            .run_command_cc(
+                format!("{name}-test-linking"),
+                r#"printf '#include <%s>\nint main(void) {\n    (void) %s;\n    return 0;\n}\n' \
+    "$HEADER" "$CALL" > test.c
+# --no-as-needed so that the libraries are loaded when the program runs
+cc test.c -o test -I"$pkg_dev/include" -L"$pkg_lib/lib" -Wl,-rpath,"$pkg_lib/lib" \
+    -Wl,--no-as-needed $LIBS
+./test
+touch "$out""#,
+            )
+            .input("pkg_dev", output(&self.pkg, &drv, &["dev"]))
+            .input("pkg_lib", output(&self.pkg, &drv, &["lib", "out"]))
+            .input("LIBS", libs.join(" "))
+            .input("HEADER", self.header)
+            .input("CALL", self.call)
+            .build()
+    }
+}
@@ -0,0 +1,176 @@
+use crate::stdenv::{Stdenv, StdenvBuilder, StdenvDrv};
+use oxide_core::prelude::*;
+
+// Small derivations made of a few shell commands, all of them are cheaper
+// to build locally than to download
+#[derive(Clone)]
+pub struct TrivialBuilders {
+    pub stdenv: Stdenv,
+}
+
+impl TrivialBuilders {
+    pub fn new(stdenv: Stdenv) -> Self {
+        Self { stdenv }
+    }
+
+    // `script` runs instead of the phases, without a C compiler
+    pub fn run_command<N, S>(&self, name: N, script: S) -> StdenvBuilder
+    where
+        N: Into<Cow<str>>,
+        S: Into<Expr>,
+    {
+        self.make_derivation(name).build_command(script)
+    }
+
+    // Same as `run_command` with the C compiler of the stdenv
+    pub fn run_command_cc<N, S>(&self, name: N, script: S) -> StdenvBuilder
+    where
+        N: Into<Cow<str>>,
+        S: Into<Expr>,
+    {
+        self.stdenv
+            .make_derivation()
+            .name(name)
+            .build_command(script)
+            .prefer_local_build()
+    }
+
+    // The base of the builders above, for the ones with a builder script
+    pub fn make_derivation<N>(&self, name: N) -> StdenvBuilder
+    where
+        N: Into<Cow<str>>,
+    {
+        let stdenv = StdenvDrv {
+            cc: None,
+            ..StdenvDrv::clone(&self.stdenv)
+        };
+        stdenv.make_derivation().name(name).prefer_local_build()
+    }
+
+    pub fn write_text_file(&self, args: TextFileArgs) -> LazyDrv {
+        self.make_derivation(args.name)
+            .builder(local_file!("write-text-file.sh"))
+            .input("TEXT", args.text)
+            .input_if("DESTINATION", args.destination)
+            .input_bool("EXECUTABLE", args.executable)
+            .input_if("CHECK_TEXT", args.check)
+            .lazy()
+    }
+
+    // A file at $out
+    pub fn write_text<N, T>(&self, name: N, text: T) -> LazyDrv
+    where
+        N: Into<Cow<str>>,
+        T: Into<Cow<str>>,
+    {
+        self.write_text_file(TextFileArgs::new(name, text))
+    }
+
+    // An executable file at $out, `text` brings its own shebang
+    pub fn write_script<N, T>(&self, name: N, text: T) -> LazyDrv
+    where
+        N: Into<Cow<str>>,
+        T: Into<Cow<str>>,
+    {
+        self.write_text_file(TextFileArgs::new(name, text).executable())
+    }
+
+    // $out/bin/<name> running `text` with the shell of the stdenv, in strict
+    // mode and with the runtime inputs on PATH
+    pub fn write_shell_application(&self, args: ShellApplicationArgs) -> LazyDrv {
+        self.make_derivation(Cow::clone(&args.name))
+            .builder(local_file!("write-shell-application.sh"))
+            .input("APPLICATION", args.name)
+            .input("TEXT", args.text)
+            .input("RUNTIME_INPUTS", args.runtime_inputs)
+            .lazy()
+    }
+
+    // The union of `paths` as a tree of symlinks, the first path providing
+    // a file wins
+    pub fn symlink_join<N>(&self, name: N, paths: Vec<Expr>) -> LazyDrv
+    where
+        N: Into<Cow<str>>,
+    {
+        self.make_derivation(name)
+            .builder(local_file!("symlink-join.sh"))
+            .input("PATHS", paths)
+            .lazy()
+    }
+}
+
+pub struct TextFileArgs {
+    pub name: Cow<str>,
+    pub text: Cow<str>,
+    // relative to $out, e.g. /bin/foo
+    pub destination: Option<Cow<str>>,
+    pub executable: bool,
+    // commands checking "$target" once written
+    pub check: Option<Cow<str>>,
+}
+
+impl TextFileArgs {
+    pub fn new<N, T>(name: N, text: T) -> Self
+    where
+        N: Into<Cow<str>>,
+        T: Into<Cow<str>>,
+    {
+        Self {
+            name: name.into(),
+            text: text.into(),
+            destination: None,
+            executable: false,
+            check: None,
+        }
+    }
+
+    pub fn destination<T>(mut self, destination: T) -> Self
+    where
+        T: Into<Cow<str>>,
+    {
+        self.destination = Some(destination.into());
+        self
+    }
+
+    pub fn executable(mut self) -> Self {
+        self.executable = true;
+        self
+    }
+
+    pub fn check<T>(mut self, check: T) -> Self
+    where
+        T: Into<Cow<str>>,
+    {
+        self.check = Some(check.into());
+        self
+    }
+}
+
+pub struct ShellApplicationArgs {
+    pub name: Cow<str>,
+    pub text: Cow<str>,
+    pub runtime_inputs: Vec<Expr>,
+}
+
+impl ShellApplicationArgs {
+    pub fn new<N, T>(name: N, text: T) -> Self
+    where
+        N: Into<Cow<str>>,
+        T: Into<Cow<str>>,
+    {
+        Self {
+            name: name.into(),
+            text: text.into(),
+            runtime_inputs: Vec::new(),
+        }
+    }
+
+    // its bin directory is on PATH when the application runs
+    pub fn runtime_input<T>(mut self, input: T) -> Self
+    where
+        T: Into<Expr>,
+    {
+        self.runtime_inputs.push(input.into());
+        self
+    }
+}
@@ -0,0 +1,16 @@
+# shellcheck shell=bash disable=SC2154
+#
+# Links every file of $PATHS into $out, directories are merged and the
+# first path providing a file wins.
+
+mkdir -p "$out"
+for path in $PATHS; do
+    while IFS= read -r -d '' file; do
+        rel="${file#"$path"/}"
+        if [ -d "$file" ] && [ ! -L "$file" ]; then
+            mkdir -p "$out/$rel"
+        elif [ ! -e "$out/$rel" ] && [ ! -L "$out/$rel" ]; then
+            ln -s "$file" "$out/$rel"
+        fi
+    done < <(find "$path/" -mindepth 1 -print0)
+done
@@ -0,0 +1,26 @@
+# shellcheck shell=bash disable=SC2154
+#
+# Writes $TEXT as $out/bin/$APPLICATION, run by the shell of the stdenv in
+# strict mode with the bin directories of $RUNTIME_INPUTS on PATH.
+
+runtime_path=
+for input in ${RUNTIME_INPUTS:-}; do
+    addToSearchPath runtime_path "$input/bin"
+done
+
+target="$out/bin/$APPLICATION"
+mkdir -p "$out/bin"
+{
+    echo "#!$SHELL"
+    echo "set -o errexit"
+    echo "set -o nounset"
+    echo "set -o pipefail"
+    if [ -n "$runtime_path" ]; then
+        echo "export PATH=\"$runtime_path:\$PATH\""
+    fi
+    echo
+    printf '%s\n' "$TEXT"
+} > "$target"
+chmod +x "$target"
+
+"$SHELL" -n "$target"
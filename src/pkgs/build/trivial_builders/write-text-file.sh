@@ -0,0 +1,14 @@
+# shellcheck shell=bash disable=SC2154
+#
+# Writes $TEXT to $out, or to $out$DESTINATION so the file can live in
+# a directory like bin.
+
+target="$out${DESTINATION:-}"
+mkdir -p "$(dirname "$target")"
+printf '%s' "$TEXT" > "$target"
+
+if [ -n "${EXECUTABLE:-}" ]; then
+    chmod +x "$target"
+fi
+
+eval "${CHECK_TEXT:-}"
@@ -0,0 +1,46 @@
+use crate::{build::fetchurl::FetchUrl, lib::fake_hash, stdenv::Stdenv};
+use oxide_core::prelude::*;
+
+// The GOARCH of `system`
+pub fn go_arch(system: System) -> &'static str {
+    match system {
+        System::x86_64_linux => "amd64",
+        System::i686_linux => "386",
+        _ => unimplemented!(),
+    }
+}
+
+// go from the official binary distribution, building go needs a go
+// to begin with. The binaries are static, they run as they are
+pub struct GoBin {
+    pub stdenv: Stdenv,
+    pub fetchurl: FetchUrl,
+}
+
+impl IntoDrv for GoBin {
+    fn into_drv(self) -> Drv {
+        let version = "1.25.1";
+        let arch = go_arch(self.stdenv.host_platform);
+        let hash = match self.stdenv.host_platform {
+            System::x86_64_linux => fake_hash(),
+            System::i686_linux => fake_hash(),
+            _ => unimplemented!(),
+        };
+        self.stdenv
+            .make_derivation()
+            .name("go-bin")
+            .version(version)
+            .src(self.fetchurl.fetch(
+                format!("https://go.dev/dl/go{version}.linux-{arch}.tar.gz"),
+                hash,
+            ))
+            .dont_configure()
+            .dont_build()
+            .install_phase(
+                r#"mkdir -p "$out/share/go" "$out/bin"
+cp -r . "$out/share/go"
+ln -s ../share/go/bin/go ../share/go/bin/gofmt "$out/bin""#,
+            )
+            .build()
+    }
+}
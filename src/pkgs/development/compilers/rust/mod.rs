@@ -0,0 +1,111 @@
+use crate::{
+    build::fetchurl::FetchUrl,
+    lib::{
+        fake_hash,
+        systems::{SYSTEMS, platform},
+    },
+    stdenv::Stdenv,
+};
+use oxide_core::prelude::*;
+
+// The target triple rustc and cargo use for `system`
+pub fn rust_target(system: System) -> &'static str {
+    match system {
+        System::x86_64_linux => "x86_64-unknown-linux-gnu",
+        System::i686_linux => "i686-unknown-linux-gnu",
+        _ => unimplemented!(),
+    }
+}
+
+// The system of the target triple `target`, the ones rust-std is
+// available for
+pub fn rust_system(target: &str) -> System {
+    SYSTEMS
+        .into_iter()
+        .find(|system| rust_target(*system) == target)
+        .unwrap_or_else(|| panic!("no rust-std for {target}"))
+}
+
+// rustc and cargo from the official binary distribution,
+// building rustc needs a rustc to begin with
+pub struct RustBin {
+    pub stdenv: Stdenv,
+    pub fetchurl: FetchUrl,
+    // the systems to install rust-std for besides the host one
+    pub targets: Vec<System>,
+}
+
+impl IntoDrv for RustBin {
+    fn into_drv(self) -> Drv {
+        let version = "1.90.0";
+        let host = self.stdenv.host_platform;
+        let glibc = self
+            .stdenv
+            .glibc
+            .clone()
+            .expect("rust-bin needs the glibc of the stdenv");
+        let hash = match host {
+            System::x86_64_linux => fake_hash(),
+            System::i686_linux => fake_hash(),
+            _ => unimplemented!(),
+        };
+        let rust_stds: Vec<LazyDrv> = self
+            .targets
+            .iter()
+            .filter(|target| **target != host)
+            .map(|target| {
+                let hash = match target {
+                    System::x86_64_linux => fake_hash(),
+                    System::i686_linux => fake_hash(),
+                    _ => unimplemented!(),
+                };
+                self.fetchurl.fetch(
+                    format!(
+                        "https://static.rust-lang.org/dist/rust-std-{version}-{}.tar.xz",
+                        rust_target(*target)
+                    ),
+                    hash,
+                )
+            })
+            .collect();
+        self.stdenv
+            .make_derivation()
+            .name("rust-bin")
+            .version(version)
+            .src(self.fetchurl.fetch(
+                format!(
+                    "https://static.rust-lang.org/dist/rust-{version}-{}.tar.xz",
+                    rust_target(host)
+                ),
+                hash,
+            ))
+            .dont_configure()
+            .dont_build()
+            .input("RUST_TARGET", rust_target(host))
+            .input("RUST_STDS", rust_stds)
+            .input("GLIBC", glibc)
+            .input("DYNAMIC_LINKER", platform(host).dynamic_linker)
+            .install_phase(
+                r#"./install.sh --prefix="$out" --components="rustc,cargo,rust-std-$RUST_TARGET" --disable-ldconfig
+for rust_std in $RUST_STDS; do
+    dir=$(mktemp -d)
+    tar -xf "$rust_std" -C "$dir" --strip-components=1
+    "$dir/install.sh" --prefix="$out" --disable-ldconfig
+done
+# the binaries look for the loader and the libraries of a FHS system
+for file in "$out"/bin/* "$out"/lib/*.so "$out/lib/rustlib/$RUST_TARGET"/bin/* \
+    "$out/lib/rustlib/$RUST_TARGET"/bin/gcc-ld/*; do
+    if [ ! -f "$file" ] || [ -L "$file" ]; then
+        continue
+    fi
+    # the scripts among them are not ELF files
+    rpath=$(patchelf --print-rpath "$file" 2> /dev/null) || continue
+    if patchelf --print-interpreter "$file" > /dev/null 2>&1; then
+        patchelf --set-interpreter "$GLIBC/lib/$DYNAMIC_LINKER" "$file"
+    fi
+    patchelf --set-rpath "${rpath:+$rpath:}$GLIBC/lib" "$file"
+done"#,
+            )
+            .build()
+    }
+}
@@ -1 +1,2 @@
 pub mod perl;
+pub mod python;
@@ -29,6 +29,7 @@ impl IntoDrv for Perl {
         self.stdenv
             .make_derivation()
             .name("perl")
+            .version(version)
             .src(self.fetchurl.fetch(
                 format!("mirror://cpan/src/5.0/perl-{version}.tar.gz"),
                 hash!("sha512:fq6a0PymjshE-2APytfavrpCdpV8y0oXomFi8Icb-eRgqyENgsaf_CcLYkYQxxqnZO_pvr5pZzSQG016MKdTEg"),
@@ -0,0 +1,51 @@
+use crate::{build::fetchurl::FetchUrl, lib::fake_hash, stdenv::Stdenv};
+use oxide_core::prelude::*;
+
+pub const PYTHON3_VERSION: &str = "3.13.7";
+
+// Where python3 and the python packages install their modules
+pub fn site_packages() -> String {
+    let (major_minor, _) = PYTHON3_VERSION.rsplit_once('.').unwrap();
+    format!("lib/python{major_minor}/site-packages")
+}
+
+pub struct Python3 {
+    pub stdenv: Stdenv,
+    pub fetchurl: FetchUrl,
+    pub zlib: LazyDrv,
+}
+
+impl IntoDrv for Python3 {
+    fn into_drv(self) -> Drv {
+        let version = PYTHON3_VERSION;
+        self.stdenv
+            .make_derivation()
+            .name("python3")
+            .version(version)
+            .src(self.fetchurl.fetch(
+                format!("https://www.python.org/ftp/python/{version}/Python-{version}.tar.xz"),
+                fake_hash(),
+            ))
+            .input("STRICT_DEPS", "1")
+            // TODO: add libffi, openssl, expat, bzip2, xz and sqlite once they are packaged
+            .dep_host_target(self.zlib.out("dev"))
+            .dep_host_target(self.zlib)
+            // the interpreter links to libpython3.so, without an rpath it does
+            // not find it out of the build directory
+            .pre_configure(r#"export LDFLAGS="-Wl,-rpath,$out/lib ${LDFLAGS:-}""#)
+            // pip is not bundled, python packages are installed with build_python_package
+            .configure_flags("--enable-shared --without-ensurepip --with-computed-gotos")
+            .input_bool("ENABLE_PARALLEL_BUILDING", true)
+            .post_install(
+                r#"ln -s python3 "$out/bin/python"
+# the tests weigh more than the rest of the standard library
+rm -r "$out"/lib/python3.*/test"#,
+            )
+            .input("SETUP_HOOK", local_file!("setup-hook.sh"))
+            .post_fix(format!(
+                r#"substituteInPlace "$out/nix-support/setup-hook" --replace-fail @site_packages@ {}"#,
+                site_packages()
+            ))
+            .build()
+    }
+}
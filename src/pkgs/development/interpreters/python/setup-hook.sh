@@ -0,0 +1,17 @@
+# shellcheck shell=bash disable=SC2154
+#
+# Make the modules of the python packages among the dependencies importable,
+# PYTHON_SITE_PACKAGES is where they are installed relative to their prefix.
+
+PYTHON_SITE_PACKAGES=@site_packages@
+
+_python_add_site_packages() {
+    addToSearchPath PYTHONPATH "$1/$PYTHON_SITE_PACKAGES"
+}
+
+addEnvHooks "$targetOffset" _python_add_site_packages
+
+# only the dependencies should be importable, not the user's site-packages
+export PYTHONNOUSERSITE=1
+# hash randomisation leaks into the order of generated files
+export PYTHONHASHSEED=0
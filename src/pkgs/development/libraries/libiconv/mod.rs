@@ -1,15 +1,15 @@
 use crate::{build::fetchurl::FetchUrl, stdenv::Stdenv};
 use oxide_core::{
-    drv::{Drv, IntoDrv},
-    expr,
-    expr::Expr,
-    hash, local_file,
+    drv::{Drv, IntoDrv, LazyDrv},
+    expr, hash, local_file,
 };
 
 pub struct LibIConv {
     pub stdenv: Stdenv,
     pub fetchurl: FetchUrl,
-    pub update_autotools_gnu_config_scripts: Expr,
+    // None keeps the config scripts of the tarball, the bootstrap of the
+    // fetchurl gnu-config is fetched with needs it
+    pub update_autotools_gnu_config_scripts_hook: Option<LazyDrv>,
     pub r#static: Option<bool>,
     pub shared: Option<bool>,
 }
@@ -19,7 +19,7 @@ impl IntoDrv for LibIConv {
         let name = "libiconv";
         let version = "1.17";
         let r#static = self.r#static.unwrap_or(false);
-        let shared = self.r#static.unwrap_or(true);
+        let shared = self.shared.unwrap_or(true);
         self.stdenv
             .make_derivation()
             .name(name)
@@ -29,14 +29,13 @@ impl IntoDrv for LibIConv {
                     hash!("sha512:tV3EG2q4Dl4Z_KqSDqaQcMRHakko0t9aNhFfnpM-Semzv05RLy5zbhHAPqxIrV3cmNdDdahkRYtwn166fZdyHA"),
             ))
             .input_bool("ENABLE_PARALLEL_BUILDING", true)
-            // TODO:
-            // .dep_build_host(self.update_autotools_gnu_config_scripts)
+            .dep_build_host_if(self.update_autotools_gnu_config_scripts_hook)
             .input("SETUP_HOOKS", expr![
                 local_file!("../../../build/setup-hooks/role.bash"),
                 local_file!("setup-hook.sh"),
             ])
             .post_patch(format!("{}", (!shared).then_some("sed -i -e '/preload/d' Makefile.in").unwrap_or_default()))
-            .configure_flags(format!("{}-static {}-shared", if r#static { "enable" } else { "disable" }, if shared { "enable" } else { "disable" }))
+            .configure_flags(format!("--{}-static --{}-shared", if r#static { "enable" } else { "disable" }, if shared { "enable" } else { "disable" }))
             .build()
     }
 }
@@ -1,2 +1,4 @@
+pub mod compilers;
 pub mod interpreters;
 pub mod libraries;
+pub mod tools;
@@ -0,0 +1,31 @@
+use crate::{build::fetchurl::FetchUrl, lib::fake_hash, stdenv::Stdenv};
+use oxide_core::prelude::*;
+
+pub struct Autoconf {
+    pub stdenv: Stdenv,
+    pub fetchurl: FetchUrl,
+    pub m4: LazyDrv,
+    pub perl: LazyDrv,
+}
+
+impl IntoDrv for Autoconf {
+    fn into_drv(self) -> Drv {
+        let version = "2.72";
+        self.stdenv
+            .make_derivation()
+            .name("autoconf")
+            .version(version)
+            .src(self.fetchurl.fetch(
+                format!("mirror://gnu/autoconf/autoconf-{version}.tar.xz"),
+                fake_hash(),
+            ))
+            .input_bool("STRICT_DEPS", true)
+            .dep_build_host(LazyDrv::clone(&self.m4))
+            .dep_build_host(LazyDrv::clone(&self.perl))
+            // autoconf runs m4 and its scripts are perl scripts
+            .dep_host_target(self.m4)
+            .dep_host_target(self.perl)
+            .input_bool("ENABLE_PARALLEL_BUILDING", true)
+            .build()
+    }
+}
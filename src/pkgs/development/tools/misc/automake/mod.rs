@@ -0,0 +1,31 @@
+use crate::{build::fetchurl::FetchUrl, lib::fake_hash, stdenv::Stdenv};
+use oxide_core::prelude::*;
+
+pub struct Automake {
+    pub stdenv: Stdenv,
+    pub fetchurl: FetchUrl,
+    pub autoconf: LazyDrv,
+    pub perl: LazyDrv,
+}
+
+impl IntoDrv for Automake {
+    fn into_drv(self) -> Drv {
+        let version = "1.18.1";
+        self.stdenv
+            .make_derivation()
+            .name("automake")
+            .version(version)
+            .src(self.fetchurl.fetch(
+                format!("mirror://gnu/automake/automake-{version}.tar.xz"),
+                fake_hash(),
+            ))
+            .input_bool("STRICT_DEPS", true)
+            .dep_build_host(LazyDrv::clone(&self.autoconf))
+            .dep_build_host(LazyDrv::clone(&self.perl))
+            .dep_host_target(self.autoconf)
+            .dep_host_target(self.perl)
+            .input_bool("ENABLE_PARALLEL_BUILDING", true)
+            .input("SETUP_HOOK", local_file!("setup-hook.sh"))
+            .build()
+    }
+}
@@ -0,0 +1,10 @@
+# shellcheck shell=bash
+#
+# aclocal finds the m4 macros of the dependencies, e.g. libtool.m4 or pkg.m4,
+# through ACLOCAL_PATH.
+
+add_aclocals() {
+    addToSearchPath ACLOCAL_PATH "$1/share/aclocal"
+}
+
+addEnvHooks "$hostOffset" add_aclocals
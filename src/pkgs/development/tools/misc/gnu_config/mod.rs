@@ -0,0 +1,36 @@
+use crate::{build::fetchurl::FetchUrl, lib::fake_hash, stdenv::Stdenv};
+use oxide_core::prelude::*;
+
+// config.guess and config.sub from upstream, the copies shipped in
+// tarballs are often too old to know newer architectures
+pub struct GnuConfig {
+    pub stdenv: Stdenv,
+    pub fetchurl: FetchUrl,
+}
+
+impl IntoDrv for GnuConfig {
+    fn into_drv(self) -> Drv {
+        let rev = "a2287c3041a3f2a204eb942e09c015eab00dc7dd";
+        // each file has its own hash
+        let fetch = |file: &str, hash: Hash| {
+            self.fetchurl.fetch(
+                format!("https://git.savannah.gnu.org/cgit/config.git/plain/{file}?id={rev}"),
+                hash,
+            )
+        };
+        self.stdenv
+            .make_derivation()
+            .name("gnu-config")
+            .version("2024-01-01")
+            .dont_unpack()
+            .input("CONFIG_GUESS", fetch("config.guess", fake_hash()))
+            .input("CONFIG_SUB", fetch("config.sub", fake_hash()))
+            .install_phase(
+                r#"install -D -m755 "$CONFIG_GUESS" "$out/config.guess"
+install -D -m755 "$CONFIG_SUB" "$out/config.sub"
+# there is no /usr/bin/uname in the sandbox
+substituteInPlace "$out/config.guess" --replace-quiet /usr/bin/uname uname"#,
+            )
+            .build()
+    }
+}
@@ -0,0 +1,25 @@
+use crate::{build::fetchurl::FetchUrl, lib::fake_hash, stdenv::Stdenv};
+use oxide_core::prelude::*;
+
+pub struct GnuM4 {
+    pub stdenv: Stdenv,
+    pub fetchurl: FetchUrl,
+}
+
+impl IntoDrv for GnuM4 {
+    fn into_drv(self) -> Drv {
+        let version = "1.4.20";
+        self.stdenv
+            .make_derivation()
+            .name("gnum4")
+            .version(version)
+            .src(
+                self.fetchurl
+                    .fetch(format!("mirror://gnu/m4/m4-{version}.tar.xz"), fake_hash()),
+            )
+            .configure_flags("--with-syscmd-shell=/bin/sh")
+            .input_bool("ENABLE_PARALLEL_BUILDING", true)
+            .do_check()
+            .build()
+    }
+}
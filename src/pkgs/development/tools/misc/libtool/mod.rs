@@ -0,0 +1,30 @@
+use crate::{build::fetchurl::FetchUrl, lib::fake_hash, stdenv::Stdenv};
+use oxide_core::prelude::*;
+
+pub struct Libtool {
+    pub stdenv: Stdenv,
+    pub fetchurl: FetchUrl,
+    pub m4: LazyDrv,
+    pub perl: LazyDrv,
+}
+
+impl IntoDrv for Libtool {
+    fn into_drv(self) -> Drv {
+        let version = "2.5.4";
+        self.stdenv
+            .make_derivation()
+            .name("libtool")
+            .version(version)
+            .src(self.fetchurl.fetch(
+                format!("mirror://gnu/libtool/libtool-{version}.tar.xz"),
+                fake_hash(),
+            ))
+            .input_bool("STRICT_DEPS", true)
+            .dep_build_host(LazyDrv::clone(&self.m4))
+            .dep_build_host(self.perl)
+            // libtoolize and the libtool.m4 macros run m4 in the packages using them
+            .propagated_host_target(self.m4)
+            .input_bool("ENABLE_PARALLEL_BUILDING", true)
+            .build()
+    }
+}
@@ -0,0 +1,5 @@
+pub mod autoconf;
+pub mod automake;
+pub mod gnu_config;
+pub mod gnum4;
+pub mod libtool;
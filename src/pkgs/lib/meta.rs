@@ -0,0 +1,9 @@
+// What is known of a package besides its derivation, kept out of the
+// derivation so that editing it does not rebuild anything
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Meta {
+    pub description: &'static str,
+    pub homepage: &'static str,
+    // SPDX identifier
+    pub license: &'static str,
+}
@@ -0,0 +1,27 @@
+pub mod meta;
+pub mod systems;
+
+use oxide_core::prelude::*;
+
+// Stand-in for the hash of a new source: the fetch fails on purpose.
+// The stdenv fetchurl reports the real hash to paste in place of
+// `fake_hash()`, builtins fetchurl only fails with the hash mismatch of
+// oxide, `prefetch <url>` prints the hash for those
+pub fn fake_hash() -> Hash {
+    // an all-zero sha512 digest, no source hashes to it
+    hash!(
+        "sha512:AAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAA"
+    )
+}
+
+// `name-version` split like the names of the derivations of `StdenvBuilder`:
+// the version starts at the first dash followed by a digit
+pub fn parse_drv_name(drv_name: &str) -> (&str, Option<&str>) {
+    let bytes = drv_name.as_bytes();
+    for i in 0..bytes.len().saturating_sub(1) {
+        if bytes[i] == b'-' && bytes[i + 1].is_ascii_digit() {
+            return (&drv_name[..i], Some(&drv_name[i + 1..]));
+        }
+    }
+    (drv_name, None)
+}
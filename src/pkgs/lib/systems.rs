@@ -0,0 +1,38 @@
+use oxide_core::prelude::*;
+
+// The systems the package set is built on and for
+pub const SYSTEMS: [System; 2] = [System::x86_64_linux, System::i686_linux];
+
+// What build systems need to know about a platform
+pub struct Platform {
+    // the GNU triple, prefix of the cross tools
+    pub config: &'static str,
+    pub kernel: &'static str,
+    pub cpu: &'static str,
+    pub cpu_family: &'static str,
+    pub endian: &'static str,
+    // the file name of the loader of the dynamically linked programs
+    pub dynamic_linker: &'static str,
+}
+
+pub fn platform(system: System) -> Platform {
+    match system {
+        System::x86_64_linux => Platform {
+            config: "x86_64-unknown-linux-gnu",
+            kernel: "linux",
+            cpu: "x86_64",
+            cpu_family: "x86_64",
+            endian: "little",
+            dynamic_linker: "ld-linux-x86-64.so.2",
+        },
+        System::i686_linux => Platform {
+            config: "i686-unknown-linux-gnu",
+            kernel: "linux",
+            cpu: "i686",
+            cpu_family: "x86",
+            endian: "little",
+            dynamic_linker: "ld-linux.so.2",
+        },
+        _ => unimplemented!(),
+    }
+}
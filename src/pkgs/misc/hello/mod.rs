@@ -19,7 +19,7 @@ impl IntoDrv for Hello {
             ))
             .do_check()
             .do_install_check()
-            .post_install_check(r#"stat "out/bin/hello""#)
+            .post_install_check(r#"stat "$out/bin/hello""#)
             .build()
     }
 }
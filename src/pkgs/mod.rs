@@ -1,5 +1,7 @@
+pub mod applications;
 pub mod build;
 pub mod development;
+pub mod lib;
 pub mod misc;
 pub mod stdenv;
 pub mod top_level;
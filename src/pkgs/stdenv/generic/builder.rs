@@ -1,6 +1,6 @@
 use super::{
-    BuildPhase, CheckPhase, ConfigurePhase, Deps, FixPhase, InstallCheckPhase, InstallPhase,
-    PatchPhase, UnpackPhase,
+    BuildPhase, CMakeConfigure, CheckPhase, ConfigurePhase, Deps, FixPhase, InstallCheckPhase,
+    InstallPhase, MesonConfigure, PatchPhase, UnpackPhase, WrapPrograms,
 };
 use crate::stdenv::StdenvDrv;
 use oxide_core::{
@@ -11,6 +11,7 @@ use oxide_core::{
     system::System,
     types::Cow,
 };
+use std::collections::BTreeMap;
 
 pub struct StdenvBuilder {
     pub(super) stdenv: StdenvDrv,
@@ -30,10 +31,13 @@ pub struct StdenvBuilder {
     pub(super) unpack: UnpackPhase,
     pub(super) patch: PatchPhase,
     pub(super) configure: ConfigurePhase,
+    pub(super) cmake: CMakeConfigure,
+    pub(super) meson: MesonConfigure,
     pub(super) build: BuildPhase,
     pub(super) check: CheckPhase,
     pub(super) install: InstallPhase,
     pub(super) fix: FixPhase,
+    pub(super) wrap: WrapPrograms,
     pub(super) install_check: InstallCheckPhase,
     pub(super) post_phase: Option<Cow<str>>,
 }
@@ -54,16 +58,30 @@ impl StdenvBuilder {
             unpack: UnpackPhase::new(),
             patch: PatchPhase::new(),
             configure: ConfigurePhase::new(),
+            cmake: CMakeConfigure::new(),
+            meson: MesonConfigure::new(),
             build: BuildPhase::new(),
             check: CheckPhase::new(),
             install: InstallPhase::new(),
             fix: FixPhase::new(),
+            wrap: WrapPrograms::new(),
             install_check: InstallCheckPhase::new(),
             post_phase: None,
         }
     }
 }
 
+// The inputs of `StdenvBuilder::prefer_local_build`, for the derivations
+// derived from an already built one
+pub(crate) fn prefer_local_build_inputs() -> BTreeMap<String, Expr> {
+    DrvBuilder::new()
+        .name("prefer-local-build")
+        .input_bool("PREFER_LOCAL_BUILD", true)
+        .input_bool("ALLOW_SUBSTITUTES", false)
+        .build()
+        .inputs
+}
+
 impl StdenvBuilder {
     pub fn name<T>(mut self, name: T) -> Self
     where
@@ -125,6 +143,13 @@ impl StdenvBuilder {
         self
     }
 
+    // for derivations cheaper to build than to download
+    pub fn prefer_local_build(self) -> Self {
+        prefer_local_build_inputs()
+            .into_iter()
+            .fold(self, |builder, (key, expr)| builder.input(key, expr))
+    }
+
     pub fn optional<F>(self, v: bool, f: F) -> Self
     where
         F: Fn(Self) -> Self,
@@ -180,6 +205,8 @@ impl StdenvBuilder {
         } else {
             name.to_string()
         };
+        let build_platform = self.stdenv.build_platform;
+        let host_platform = self.stdenv.host_platform;
         let builder = self
             .drv_builder
             .name(versioned_name)
@@ -200,10 +227,13 @@ impl StdenvBuilder {
         let builder = self.unpack.build(builder);
         let builder = self.patch.build(builder);
         let builder = self.configure.build(builder);
+        let builder = self.cmake.build(builder, build_platform, host_platform);
+        let builder = self.meson.build(builder, build_platform, host_platform);
         let builder = self.build.build(builder);
         let builder = self.check.build(builder);
         let builder = self.install.build(builder);
         let builder = self.fix.build(builder);
+        let builder = self.wrap.build(builder);
         let builder = self.install_check.build(builder);
         builder.build()
     }
@@ -54,6 +54,16 @@ impl StdenvBuilder {
         self
     }
 
+    pub fn dep_build_host_if<T>(self, dep: Option<T>) -> Self
+    where
+        T: Into<Expr>,
+    {
+        match dep {
+            Some(dep) => self.dep_build_host(dep),
+            None => self,
+        }
+    }
+
     pub fn dep_build_target<T>(mut self, dep: T) -> Self
     where
         T: Into<Expr>,
@@ -90,7 +100,7 @@ impl StdenvBuilder {
     where
         T: Into<Expr>,
     {
-        self.deps.build_build.push(dep.into());
+        self.propagated.build_build.push(dep.into());
         self
     }
 
@@ -98,7 +108,7 @@ impl StdenvBuilder {
     where
         T: Into<Expr>,
     {
-        self.deps.build_host.push(dep.into());
+        self.propagated.build_host.push(dep.into());
         self
     }
 
@@ -106,7 +116,7 @@ impl StdenvBuilder {
     where
         T: Into<Expr>,
     {
-        self.deps.build_target.push(dep.into());
+        self.propagated.build_target.push(dep.into());
         self
     }
 
@@ -114,7 +124,7 @@ impl StdenvBuilder {
     where
         T: Into<Expr>,
     {
-        self.deps.host_host.push(dep.into());
+        self.propagated.host_host.push(dep.into());
         self
     }
 
@@ -122,7 +132,7 @@ impl StdenvBuilder {
     where
         T: Into<Expr>,
     {
-        self.deps.host_target.push(dep.into());
+        self.propagated.host_target.push(dep.into());
         self
     }
 
@@ -130,7 +140,7 @@ impl StdenvBuilder {
     where
         T: Into<Expr>,
     {
-        self.deps.target_target.push(dep.into());
+        self.propagated.target_target.push(dep.into());
         self
     }
 }
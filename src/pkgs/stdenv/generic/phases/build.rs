@@ -30,6 +30,7 @@ impl BuildPhase {
                 .input("BUILD", "1")
                 .input_if("MAKEFILE", self.makefile)
                 .input_if("MAKE_FLAGS", self.make_flags)
+                .input_if("BUILD_FLAGS", self.build_flags)
                 .input_if("PRE_BUILD", self.pre_build)
                 .input_if("BUILD_PHASE", self.build_phase)
                 .input_if("POST_BUILD", self.post_build)
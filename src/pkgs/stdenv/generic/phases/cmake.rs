@@ -0,0 +1,93 @@
+use crate::{lib::systems::platform, stdenv::StdenvBuilder};
+use oxide_core::{drv::DrvBuilder, system::System, types::Cow};
+
+// Settings of the configure phase of the cmake setup hook
+pub struct CMakeConfigure {
+    pub cmake_flags: Option<Cow<str>>,
+    pub cmake_build_type: Option<Cow<str>>,
+    pub cmake_dir: Option<Cow<str>>,
+}
+
+impl CMakeConfigure {
+    pub fn new() -> Self {
+        Self {
+            cmake_flags: None,
+            cmake_build_type: None,
+            cmake_dir: None,
+        }
+    }
+
+    pub fn build(
+        self,
+        builder: DrvBuilder,
+        build_platform: System,
+        host_platform: System,
+    ) -> DrvBuilder {
+        let builder = builder
+            .input_if("CMAKE_FLAGS", self.cmake_flags)
+            .input_if("CMAKE_BUILD_TYPE", self.cmake_build_type)
+            .input_if("CMAKE_DIR", self.cmake_dir);
+        if build_platform == host_platform {
+            return builder;
+        }
+        let build = platform(build_platform);
+        let host = platform(host_platform);
+        // without the host prefixed tools cmake would pick the build ones
+        let prefix = host.config;
+        builder.input(
+            "CMAKE_CROSS_FLAGS",
+            format!(
+                "-DCMAKE_SYSTEM_NAME={} -DCMAKE_SYSTEM_PROCESSOR={} \
+                 -DCMAKE_HOST_SYSTEM_NAME={} -DCMAKE_HOST_SYSTEM_PROCESSOR={} \
+                 -DCMAKE_C_COMPILER={prefix}-cc -DCMAKE_CXX_COMPILER={prefix}-c++ \
+                 -DCMAKE_AR={prefix}-ar -DCMAKE_RANLIB={prefix}-ranlib \
+                 -DCMAKE_STRIP={prefix}-strip",
+                cmake_system_name(host.kernel),
+                host.cpu,
+                cmake_system_name(build.kernel),
+                build.cpu
+            ),
+        )
+    }
+}
+
+impl Default for CMakeConfigure {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn cmake_system_name(kernel: &str) -> &'static str {
+    match kernel {
+        "linux" => "Linux",
+        _ => unimplemented!(),
+    }
+}
+
+impl StdenvBuilder {
+    pub fn cmake_flags<T>(mut self, cmake_flags: T) -> Self
+    where
+        T: Into<Cow<str>>,
+    {
+        self.cmake.cmake_flags = Some(cmake_flags.into());
+        self
+    }
+
+    // Release unless set
+    pub fn cmake_build_type<T>(mut self, cmake_build_type: T) -> Self
+    where
+        T: Into<Cow<str>>,
+    {
+        self.cmake.cmake_build_type = Some(cmake_build_type.into());
+        self
+    }
+
+    // directory of the top level CMakeLists.txt relative to the source root
+    pub fn cmake_dir<T>(mut self, cmake_dir: T) -> Self
+    where
+        T: Into<Cow<str>>,
+    {
+        self.cmake.cmake_dir = Some(cmake_dir.into());
+        self
+    }
+}
@@ -49,18 +49,12 @@ impl StdenvBuilder {
         self
     }
 
-    pub fn dont_strip<T>(mut self) -> Self
-    where
-        T: Into<Cow<str>>,
-    {
+    pub fn dont_strip(mut self) -> Self {
         self.fix.strip = false;
         self
     }
 
-    pub fn dont_patch_elf<T>(mut self) -> Self
-    where
-        T: Into<Cow<str>>,
-    {
+    pub fn dont_patch_elf(mut self) -> Self {
         self.fix.patch_elf = false;
         self
     }
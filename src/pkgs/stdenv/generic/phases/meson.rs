@@ -0,0 +1,53 @@
+use crate::{lib::systems::platform, stdenv::StdenvBuilder};
+use oxide_core::{drv::DrvBuilder, system::System, types::Cow};
+
+// Settings of the configure phase of the meson setup hook
+pub struct MesonConfigure {
+    pub meson_flags: Option<Cow<str>>,
+}
+
+impl MesonConfigure {
+    pub fn new() -> Self {
+        Self { meson_flags: None }
+    }
+
+    pub fn build(
+        self,
+        builder: DrvBuilder,
+        build_platform: System,
+        host_platform: System,
+    ) -> DrvBuilder {
+        let builder = builder.input_if("MESON_FLAGS", self.meson_flags);
+        if build_platform == host_platform {
+            return builder;
+        }
+        // the hook writes it to a file given to --cross-file, the compilers
+        // are taken from CC, CXX, AR... as usual
+        let host = platform(host_platform);
+        builder.input(
+            "MESON_CROSS_CONFIG",
+            format!(
+                "[properties]\nneeds_exe_wrapper = true\n\n\
+                 [host_machine]\nsystem = '{}'\ncpu_family = '{}'\ncpu = '{}'\nendian = '{}'",
+                host.kernel, host.cpu_family, host.cpu, host.endian
+            ),
+        )
+    }
+}
+
+impl Default for MesonConfigure {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl StdenvBuilder {
+    // e.g. "-Dtests=false -Ddocs=disabled"
+    pub fn meson_flags<T>(mut self, meson_flags: T) -> Self
+    where
+        T: Into<Cow<str>>,
+    {
+        self.meson.meson_flags = Some(meson_flags.into());
+        self
+    }
+}
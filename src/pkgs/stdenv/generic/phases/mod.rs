@@ -1,17 +1,23 @@
 mod build;
 mod check;
+mod cmake;
 mod configure;
 mod fix;
 mod install;
 mod install_check;
+mod meson;
 mod patch;
 mod unpack;
+mod wrap;
 
 pub use build::*;
 pub use check::*;
+pub use cmake::*;
 pub use configure::*;
 pub use fix::*;
 pub use install::*;
 pub use install_check::*;
+pub use meson::*;
 pub use patch::*;
 pub use unpack::*;
+pub use wrap::*;
@@ -0,0 +1,181 @@
+use crate::stdenv::StdenvBuilder;
+use oxide_core::{drv::DrvBuilder, local_file, types::Cow};
+
+// The arguments of wrap_program, the values are expanded by the builder
+// so they can refer to $out or other inputs
+pub struct Wrap {
+    args: Vec<String>,
+}
+
+impl Wrap {
+    pub fn new() -> Self {
+        Self { args: Vec::new() }
+    }
+
+    fn arg(mut self, flag: &str, values: &[Cow<str>]) -> Self {
+        self.args.push(flag.to_string());
+        self.args
+            .extend(values.iter().map(|value| double_quote(value)));
+        self
+    }
+
+    pub fn set<K, V>(self, var: K, value: V) -> Self
+    where
+        K: Into<Cow<str>>,
+        V: Into<Cow<str>>,
+    {
+        self.arg("--set", &[var.into(), value.into()])
+    }
+
+    // unless the variable is already set when running the program
+    pub fn set_default<K, V>(self, var: K, value: V) -> Self
+    where
+        K: Into<Cow<str>>,
+        V: Into<Cow<str>>,
+    {
+        self.arg("--set-default", &[var.into(), value.into()])
+    }
+
+    pub fn unset<K>(self, var: K) -> Self
+    where
+        K: Into<Cow<str>>,
+    {
+        self.arg("--unset", &[var.into()])
+    }
+
+    // separated by ':' like PATH
+    pub fn prefix<K, V>(self, var: K, value: V) -> Self
+    where
+        K: Into<Cow<str>>,
+        V: Into<Cow<str>>,
+    {
+        self.prefix_sep(var, ":", value)
+    }
+
+    pub fn prefix_sep<K, S, V>(self, var: K, sep: S, value: V) -> Self
+    where
+        K: Into<Cow<str>>,
+        S: Into<Cow<str>>,
+        V: Into<Cow<str>>,
+    {
+        self.arg("--prefix", &[var.into(), sep.into(), value.into()])
+    }
+
+    // separated by ':' like PATH
+    pub fn suffix<K, V>(self, var: K, value: V) -> Self
+    where
+        K: Into<Cow<str>>,
+        V: Into<Cow<str>>,
+    {
+        self.suffix_sep(var, ":", value)
+    }
+
+    pub fn suffix_sep<K, S, V>(self, var: K, sep: S, value: V) -> Self
+    where
+        K: Into<Cow<str>>,
+        S: Into<Cow<str>>,
+        V: Into<Cow<str>>,
+    {
+        self.arg("--suffix", &[var.into(), sep.into(), value.into()])
+    }
+
+    // written as they are in the wrapper, before the arguments it is given
+    pub fn add_flags<T>(self, flags: T) -> Self
+    where
+        T: Into<Cow<str>>,
+    {
+        self.arg("--add-flags", &[flags.into()])
+    }
+
+    // a shell command run by the wrapper, not expanded by the builder
+    pub fn run<T>(mut self, command: T) -> Self
+    where
+        T: Into<Cow<str>>,
+    {
+        let command: Cow<str> = command.into();
+        self.args.push("--run".to_string());
+        self.args.push(single_quote(&command));
+        self
+    }
+
+    pub fn chdir<T>(self, dir: T) -> Self
+    where
+        T: Into<Cow<str>>,
+    {
+        self.arg("--chdir", &[dir.into()])
+    }
+
+    // the $0 of the program instead of the path of the wrapper
+    pub fn argv0<T>(self, argv0: T) -> Self
+    where
+        T: Into<Cow<str>>,
+    {
+        self.arg("--argv0", &[argv0.into()])
+    }
+}
+
+impl Default for Wrap {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn double_quote(value: &str) -> String {
+    let escaped = value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('`', "\\`");
+    format!("\"{escaped}\"")
+}
+
+fn single_quote(value: &str) -> String {
+    format!("'{}'", value.replace('\'', r"'\''"))
+}
+
+// Programs of $out wrapped at the end of the fix phase
+pub struct WrapPrograms {
+    pub wraps: Vec<(Cow<str>, Wrap)>,
+}
+
+impl WrapPrograms {
+    pub fn new() -> Self {
+        Self { wraps: Vec::new() }
+    }
+
+    pub fn build(self, builder: DrvBuilder) -> DrvBuilder {
+        if self.wraps.is_empty() {
+            return builder;
+        }
+        let commands: Vec<String> = self
+            .wraps
+            .into_iter()
+            .map(|(program, wrap)| {
+                let program = double_quote(&format!("$out/{program}"));
+                format!("wrap_program {program} {}", wrap.args.join(" "))
+            })
+            .collect();
+        builder
+            .input(
+                "MAKE_WRAPPER",
+                local_file!("../../../build/setup-hooks/make-wrapper.sh"),
+            )
+            .input("WRAP_PROGRAMS", commands.join("\n"))
+    }
+}
+
+impl Default for WrapPrograms {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl StdenvBuilder {
+    // `program` is relative to $out, e.g. bin/foo
+    pub fn wrap_program<T>(mut self, program: T, wrap: Wrap) -> Self
+    where
+        T: Into<Cow<str>>,
+    {
+        self.wrap.wraps.push((program.into(), wrap));
+        self
+    }
+}
@@ -20,10 +20,35 @@ get_all_output_names() {
     echo "$outputs"
 }
 
+# the output holding a kind of files: $OUTPUT_<KIND> when set,
+# otherwise the first existing output of the candidates, otherwise $out
+# e.g. get_output_dir OUTPUT_DEV dev
+get_output_dir() {
+    local override="$1"
+    shift
+    if [ -n "${!override:-}" ]; then
+        local output="${!override}"
+        echo "${!output}"
+        return
+    fi
+    local name
+    for name in "$@"; do
+        if [[ " $outputs " == *" $name "* ]]; then
+            echo "${!name}"
+            return
+        fi
+    done
+    echo "$out"
+}
+
 run_hook() {
     local hook_name="$1"
     shift
     local hooks_slice="${hook_name%_HOOK}_HOOKS[@]"
+    # the env hooks registered by addEnvHooks, e.g. envHostTargetHooks
+    if [[ "$hook_name" == env*Hook ]]; then
+        hooks_slice="${hook_name}s[@]"
+    fi
 
     local hook
     for hook in "_call_implicit_hook 0 $hook_name" ${!hooks_slice+"${!hooks_slice}"}; do
@@ -338,7 +363,7 @@ for i in $initial_path; do
     # For backward compatibility, we add initial path to HOST_PATH so
     # it can be used in auto patch-shebangs. Unfortunately this will
     # not work with cross compilation.
-    if [ -z "${strictDeps-}" ]; then
+    if [ -z "${STRICT_DEPS:-}" ]; then
         addToSearchPath HOST_PATH "$i/bin"
     fi
 done
@@ -431,6 +456,9 @@ findInputs() {
     # Sanity check
     (( hostOffset <= targetOffset )) || exit 1
 
+    # empty dependency lists expand to a single empty word
+    [ -n "$pkg" ] || return 0
+
     # shellcheck disable=SC1087
     local varVar="${pkgAccumVarVars[hostOffset + 1]}"
     # shellcheck disable=SC1087
@@ -524,29 +552,29 @@ findInputs() {
 : "${DEPS_HOST_TARGET=}" "${PROPAGATED_HOST_TARGET=}" "${default_host_target=}"
 : "${DEPS_TARGET_TARGET=}" "${PROPAGATED_TARGET_TARGET=}"
 
-for pkg in "${DEPS_BUILD_BUILD[@]}" "${PROPAGATED_BUILD_BUILD[@]}"; do
+for pkg in ${DEPS_BUILD_BUILD[@]} ${PROPAGATED_BUILD_BUILD[@]}; do
     findInputs "$pkg" -1 -1
 done
-for pkg in "${DEPS_BUILD_HOST[@]}" "${PROPAGATED_BUILD_HOST[@]}"; do
+for pkg in ${DEPS_BUILD_HOST[@]} ${PROPAGATED_BUILD_HOST[@]}; do
     findInputs "$pkg" -1  0
 done
-for pkg in "${DEPS_BUILD_TARGET[@]}" "${PROPAGATED_BUILD_TARGET[@]}"; do
+for pkg in ${DEPS_BUILD_TARGET[@]} ${PROPAGATED_BUILD_TARGET[@]}; do
     findInputs "$pkg" -1  1
 done
-for pkg in "${DEPS_HOST_HOST[@]}" "${PROPAGATED_HOST_HOST[@]}"; do
+for pkg in ${DEPS_HOST_HOST[@]} ${PROPAGATED_HOST_HOST[@]}; do
     findInputs "$pkg"  0  0
 done
-for pkg in "${HOST_TARGET[@]}" "${PROPAGATED_HOST_TARGET[@]}" ; do
+for pkg in ${DEPS_HOST_TARGET[@]} ${PROPAGATED_HOST_TARGET[@]}; do
     findInputs "$pkg"  0  1
 done
-for pkg in "${DEPS_TARGET_TARGET[@]}" "${PROPAGATED_TARGET_TARGET[@]}"; do
+for pkg in ${DEPS_TARGET_TARGET[@]} ${PROPAGATED_TARGET_TARGET[@]}; do
     findInputs "$pkg"  1  1
 done
 # Default inputs must be processed last
-for pkg in "${default_build_host[@]}"; do
+for pkg in ${default_build_host[@]}; do
     findInputs "$pkg" -1  0
 done
-for pkg in "${default_host_target[@]}"; do
+for pkg in ${default_host_target[@]}; do
     findInputs "$pkg"  0  1
 done
 
@@ -570,7 +598,7 @@ activatePackage() {
     # the transition, we do include everything in that case.
     #
     # TODO(@Ericson2314): Don't special-case native compilation
-    if [[ -z "${strictDeps-}" || "$hostOffset" -le -1 ]]; then
+    if [[ -z "${STRICT_DEPS:-}" || "$hostOffset" -le -1 ]]; then
         addToSearchPath _PATH "$pkg/bin"
     fi
 
@@ -625,7 +653,7 @@ _addToEnv() {
         for depTargetOffset in "${allPlatOffsets[@]}"; do
             (( depHostOffset <= depTargetOffset )) || continue
             local hookRef="${hookVar}[$depTargetOffset - $depHostOffset]"
-            if [[ -z "${strictDeps-}" ]]; then
+            if [[ -z "${STRICT_DEPS:-}" ]]; then
 
                 # Keep track of which packages we have visited before.
                 local visitedPkgs=""
@@ -1011,7 +1039,7 @@ patch_phase() {
     run_hook PRE_PATCH
 
     local -a patches_array
-    concatTo patches_array patches
+    concatTo patches_array PATCHES
 
     for i in "${patches_array[@]}"; do
         echo "applying patch $i"
@@ -1245,6 +1273,46 @@ fix_phase() {
         prefix="${!output}" run_hook fixupOutput
     done
 
+    # Install the setup hooks sourced by the packages depending on this one
+    local -a hooks_array=()
+    concatTo hooks_array SETUP_HOOKS SETUP_HOOK
+    if (( ${#hooks_array[@]} )); then
+        mkdir -p "$out/nix-support"
+        cat "${hooks_array[@]}" > "$out/nix-support/setup-hook"
+    fi
+    unset hooks_array
+
+    # Record the propagated dependencies, findInputs reads them back
+    # in the packages depending on this one
+    local -A propagated_files=(
+        [PROPAGATED_BUILD_BUILD]=propagated-build-build-deps
+        [PROPAGATED_BUILD_HOST]=propagated-native-build-inputs
+        [PROPAGATED_BUILD_TARGET]=propagated-build-target-deps
+        [PROPAGATED_HOST_HOST]=propagated-host-host-deps
+        [PROPAGATED_HOST_TARGET]=propagated-build-inputs
+        [PROPAGATED_TARGET_TARGET]=propagated-target-target-deps
+    )
+    local dev_dir var
+    dev_dir=$(get_output_dir OUTPUT_DEV dev)
+    for var in "${!propagated_files[@]}"; do
+        local -a deps_array=()
+        concatTo deps_array "$var"
+        if (( ${#deps_array[@]} )); then
+            mkdir -p "$dev_dir/nix-support"
+            echo "${deps_array[*]}" > "$dev_dir/nix-support/${propagated_files[$var]}"
+        fi
+    done
+    unset deps_array
+
+    # The wrappers requested with `StdenvBuilder::wrap_program`
+    if [ -n "${WRAP_PROGRAMS:-}" ]; then
+        if ! declare -F wrap_program > /dev/null; then
+            # shellcheck disable=SC1090
+            source "$MAKE_WRAPPER"
+        fi
+        eval "$WRAP_PROGRAMS"
+    fi
+
     run_hook POST_FIX
 }
 
@@ -1,8 +1,6 @@
 use super::FetchBootstrapFile;
-use oxide_core::{
-    drv::{Drv, IntoDrv},
-    hash,
-};
+use crate::lib::fake_hash;
+use oxide_core::drv::{Drv, IntoDrv};
 
 pub struct BootstrapTools;
 
@@ -10,7 +8,7 @@ impl IntoDrv for BootstrapTools {
     fn into_drv(self) -> Drv {
         FetchBootstrapFile {
             url: "http://tarballs.nixos.org/stdenv/i686-unknown-linux-gnu/125cefd4cf8f857e5ff1aceaef9230ba578a033d/bootstrap-tools.tar.xz",
-            hash: hash!("sha512:YzJSc1lXcHFabXhyWVhOa2FtWnNZWE5xWm14ellXcG1jMjNSc2FtWnNjMlJxYkdaaGFuTnNhMlpoYzJSclphcw"),
+            hash: fake_hash(),
             exec: false,
         }.into_drv()
     }
@@ -22,7 +20,7 @@ impl IntoDrv for Busybox {
     fn into_drv(self) -> Drv {
         FetchBootstrapFile {
             url: "http://tarballs.nixos.org/stdenv/i686-unknown-linux-gnu/125cefd4cf8f857e5ff1aceaef9230ba578a033d/busybox",
-            hash: hash!("sha512:YzJSc1lXcHFabXhyWVhOa2FtWnNZWE5xWm14ellXcG1jMjNSc2FtWnNjMlJxYkdaaGFuTnNhMlpoYzJSclphcw"),
+            hash: fake_hash(),
             exec: true,
         }.into_drv()
     }
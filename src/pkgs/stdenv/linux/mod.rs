@@ -1,5 +1,7 @@
 use crate::{
-    build::fetchurl::FetchUrl, development::interpreters::perl, stdenv::generic::StdenvDrv,
+    build::{fetchurl::FetchUrl, trivial_builders::TrivialBuilders},
+    development::interpreters::perl,
+    stdenv::generic::StdenvDrv,
 };
 use bootstrap_files::{BootstrapFiles, i686_unknown_linux, x86_64_unknown_linux};
 use bootstrap_tools::BootstrapTools;
@@ -123,16 +125,9 @@ pub fn stager(stage: StdenvStage) -> StdenvStage {
             };
             if glibc {
                 stdenv.glibc = Some(
-                    stdenv
-                        .make_derivation()
-                        .name("bootstrap-stage0-glibc")
-                        .version("bootstrap-files")
-                        // TODO: this would not be necessary once we will have format strings
-                        .out("out")
-                        .out("dev")
-                        .out("lib")
-                        .input("bootstrap_tools", &bootstrap_tools)
-                        .build_command(
+                    TrivialBuilders::new(Stdenv::new(stdenv.clone()))
+                        .run_command(
+                            "bootstrap-stage0-glibc",
                             r#"mkdir -p $out
 ln -s ${bootstrap_tools}/lib $out/lib
 ln -s ${bootstrap_tools}/include-glibc $out/include
@@ -140,6 +135,12 @@ ln -s ${bootstrap_tools}/include-glibc $out/include
 mkdir "$dev"
 mkdir "$lib""#,
                         )
+                        .version("bootstrap-files")
+                        // TODO: this would not be necessary once we will have format strings
+                        .out("out")
+                        .out("dev")
+                        .out("lib")
+                        .input("bootstrap_tools", &bootstrap_tools)
                         .lazy(),
                 );
             } else {
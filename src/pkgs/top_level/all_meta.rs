@@ -0,0 +1,72 @@
+use crate::lib::meta::Meta;
+use std::collections::HashMap;
+
+// The meta of the packages of `all_pkgs`, by the same names
+pub fn all_meta() -> HashMap<&'static str, Meta> {
+    HashMap::from([
+        (
+            "zlib",
+            Meta {
+                description: "Lossless data compression library",
+                homepage: "https://zlib.net",
+                license: "Zlib",
+            },
+        ),
+        (
+            "libiconv",
+            Meta {
+                description: "Character set conversion library",
+                homepage: "https://www.gnu.org/software/libiconv",
+                license: "LGPL-2.1-or-later",
+            },
+        ),
+        (
+            "pkg-config-unwrapped",
+            Meta {
+                description: "Tool that returns the compiler and linker flags of libraries",
+                homepage: "https://www.freedesktop.org/wiki/Software/pkg-config",
+                license: "GPL-2.0-or-later",
+            },
+        ),
+        (
+            "pkg-config",
+            Meta {
+                description: "pkg-config searching the .pc files of the dependencies of a build",
+                homepage: "https://www.freedesktop.org/wiki/Software/pkg-config",
+                license: "GPL-2.0-or-later",
+            },
+        ),
+        (
+            "perl",
+            Meta {
+                description: "The Perl 5 programming language",
+                homepage: "https://www.perl.org",
+                license: "Artistic-1.0-Perl OR GPL-1.0-or-later",
+            },
+        ),
+        (
+            "curl",
+            Meta {
+                description: "Command line tool and library for transferring data with URLs",
+                homepage: "https://curl.se",
+                license: "curl",
+            },
+        ),
+        (
+            "hello",
+            Meta {
+                description: "Program that produces a familiar, friendly greeting",
+                homepage: "https://www.gnu.org/software/hello",
+                license: "GPL-3.0-or-later",
+            },
+        ),
+        (
+            "make-wrapper",
+            Meta {
+                description: "Setup hook providing make_wrapper and wrap_program",
+                homepage: "https://github.com/OxidePM/oxide-pkgs",
+                license: "Apache-2.0",
+            },
+        ),
+    ])
+}
@@ -1,35 +1,95 @@
 use crate::{
+    applications::{package_management::oxide::Oxide, version_management::git::Git},
     build::{
+        autotools::{AutoreconfHook, UpdateAutotoolsGnuConfigScriptsHook},
+        cmake::CMake,
         curl::Curl,
+        docker_tools::DockerTools,
+        fetchgit::FetchGit,
+        fetchpatch::FetchPatch,
         fetchurl::{FetchUrl, StdenvFetchUrl},
-        pkg_config::PkgConfig,
+        fetchzip::FetchZip,
+        forges::FetchFromForge,
+        go::GoPlatform,
+        make_wrapper::MakeWrapper,
+        meson::Meson,
+        ninja::Ninja,
+        perl::PerlPlatform,
+        pkg_config::{PKG_CONFIG_VERSION, PkgConfig, PkgConfigWrapper},
+        python::PythonPlatform,
+        rust::RustPlatform,
+        tests::Tests,
+        trivial_builders::TrivialBuilders,
     },
     development::{
-        interpreters::perl::Perl,
+        compilers::{go::GoBin, rust::RustBin},
+        interpreters::{perl::Perl, python::Python3},
         libraries::{libiconv::LibIConv, zlib::Zlib},
+        tools::misc::{
+            autoconf::Autoconf, automake::Automake, gnu_config::GnuConfig, gnum4::GnuM4,
+            libtool::Libtool,
+        },
     },
     misc::hello::Hello,
-    stdenv::{self, Stdenv, linux::Noop},
+    stdenv::{self, Stdenv},
+    top_level::all_tests::{all_tests, test_attr},
 };
 use oxide_core::prelude::*;
 use std::collections::HashMap;
 
 pub struct AllPkgs {
     pub stdenv: Stdenv,
+    pub trivial_builders: TrivialBuilders,
+    pub docker_tools: DockerTools,
     pub fetchurl: FetchUrl,
+    pub fetchzip: FetchZip,
+    pub fetchpatch: FetchPatch,
+    pub fetchgit: FetchGit,
+    pub forges: FetchFromForge,
     pub zlib: LazyDrv,
+    pub gnu_config: LazyDrv,
+    pub update_autotools_gnu_config_scripts_hook: LazyDrv,
     pub libiconv: LazyDrv,
+    pub pkg_config_unwrapped: LazyDrv,
     pub pkg_config: LazyDrv,
     pub perl: LazyDrv,
+    pub perl_platform: PerlPlatform,
+    pub gnum4: LazyDrv,
+    pub autoconf: LazyDrv,
+    pub automake: LazyDrv,
+    pub libtool: LazyDrv,
+    pub autoreconf_hook: LazyDrv,
     pub curl: LazyDrv,
     pub hello: LazyDrv,
+    pub git: LazyDrv,
+    pub rust: LazyDrv,
+    pub rust_platform: RustPlatform,
+    pub oxide: LazyDrv,
+    pub cmake: LazyDrv,
+    pub ninja: LazyDrv,
+    pub python3: LazyDrv,
+    pub python_platform: PythonPlatform,
+    pub meson: LazyDrv,
+    pub go: LazyDrv,
+    pub go_platform: GoPlatform,
+    pub make_wrapper: LazyDrv,
+    pub tests: Tests,
 }
 
 // TODO: make it more ergonomic
 pub fn all_pkgs() -> (HashMap<String, LazyDrv>, Box<AllPkgs>) {
+    all_pkgs_for(current_system())
+}
+
+// The packages built on and for `system`
+pub fn all_pkgs_for(system: System) -> (HashMap<String, LazyDrv>, Box<AllPkgs>) {
     let mut pkgs = HashMap::new();
-    let stdenv = build_stdenv();
+    let stdenv = build_stdenv(system);
+    let trivial_builders = TrivialBuilders::new(Stdenv::clone(&stdenv));
+    let docker_tools = DockerTools::new(Stdenv::clone(&stdenv));
     let fetchurl = build_fetchurl(&stdenv);
+    let fetchzip = FetchZip::new(FetchUrl::clone(&fetchurl));
+    let fetchpatch = FetchPatch::new(FetchUrl::clone(&fetchurl));
 
     let zlib = LazyDrv::new(Zlib {
         stdenv: Stdenv::clone(&stdenv),
@@ -40,23 +100,51 @@ pub fn all_pkgs() -> (HashMap<String, LazyDrv>, Box<AllPkgs>) {
     });
     pkgs.insert("zlib".to_string(), LazyDrv::clone(&zlib));
 
+    let gnu_config = LazyDrv::new(GnuConfig {
+        stdenv: Stdenv::clone(&stdenv),
+        fetchurl: FetchUrl::clone(&fetchurl),
+    });
+    // gnu-config, the autotools and their hooks are registered once their
+    // sources have real hashes instead of fake_hash(), until then the
+    // packages below keep the config.guess and config.sub of their tarballs
+    let update_autotools_gnu_config_scripts_hook =
+        LazyDrv::new(UpdateAutotoolsGnuConfigScriptsHook {
+            stdenv: Stdenv::clone(&stdenv),
+            gnu_config: LazyDrv::clone(&gnu_config),
+        });
+
     let libiconv = LazyDrv::new(LibIConv {
         stdenv: Stdenv::clone(&stdenv),
         fetchurl: FetchUrl::clone(&fetchurl),
-        update_autotools_gnu_config_scripts: "".into(),
+        update_autotools_gnu_config_scripts_hook: None,
         shared: None,
         r#static: None,
     });
     pkgs.insert("libiconv".to_string(), LazyDrv::clone(&libiconv));
 
-    let pkg_config = LazyDrv::new(PkgConfig {
+    let pkg_config_unwrapped = LazyDrv::new(PkgConfig {
         stdenv: Stdenv::clone(&stdenv),
         fetchurl: FetchUrl::clone(&fetchurl),
         libiconv: LazyDrv::clone(&libiconv),
         vanilla: None,
+        update_autotools_gnu_config_scripts_hook: None,
+    });
+    pkgs.insert(
+        "pkg-config-unwrapped".to_string(),
+        LazyDrv::clone(&pkg_config_unwrapped),
+    );
+    let pkg_config = LazyDrv::new(PkgConfigWrapper {
+        stdenv: Stdenv::clone(&stdenv),
+        pkg_config: LazyDrv::clone(&pkg_config_unwrapped),
+        version: PKG_CONFIG_VERSION.into(),
     });
     pkgs.insert("pkg-config".to_string(), LazyDrv::clone(&pkg_config));
 
+    let tests = Tests::new(
+        TrivialBuilders::clone(&trivial_builders),
+        LazyDrv::clone(&pkg_config),
+    );
+
     let perl = LazyDrv::new(Perl {
         stdenv: Stdenv::clone(&stdenv),
         fetchurl: FetchUrl::clone(&fetchurl),
@@ -65,11 +153,51 @@ pub fn all_pkgs() -> (HashMap<String, LazyDrv>, Box<AllPkgs>) {
     });
     pkgs.insert("perl".to_string(), LazyDrv::clone(&perl));
 
+    let perl_platform = PerlPlatform::new(
+        Stdenv::clone(&stdenv),
+        FetchUrl::clone(&fetchurl),
+        LazyDrv::clone(&perl),
+    );
+
+    let gnum4 = LazyDrv::new(GnuM4 {
+        stdenv: Stdenv::clone(&stdenv),
+        fetchurl: FetchUrl::clone(&fetchurl),
+    });
+
+    let autoconf = LazyDrv::new(Autoconf {
+        stdenv: Stdenv::clone(&stdenv),
+        fetchurl: FetchUrl::clone(&fetchurl),
+        m4: LazyDrv::clone(&gnum4),
+        perl: LazyDrv::clone(&perl),
+    });
+
+    let automake = LazyDrv::new(Automake {
+        stdenv: Stdenv::clone(&stdenv),
+        fetchurl: FetchUrl::clone(&fetchurl),
+        autoconf: LazyDrv::clone(&autoconf),
+        perl: LazyDrv::clone(&perl),
+    });
+
+    let libtool = LazyDrv::new(Libtool {
+        stdenv: Stdenv::clone(&stdenv),
+        fetchurl: FetchUrl::clone(&fetchurl),
+        m4: LazyDrv::clone(&gnum4),
+        perl: LazyDrv::clone(&perl),
+    });
+
+    let autoreconf_hook = LazyDrv::new(AutoreconfHook {
+        stdenv: Stdenv::clone(&stdenv),
+        autoconf: LazyDrv::clone(&autoconf),
+        automake: LazyDrv::clone(&automake),
+        libtool: LazyDrv::clone(&libtool),
+    });
+
     let curl = LazyDrv::new(Curl {
         stdenv: Stdenv::clone(&stdenv),
         fetchurl: FetchUrl::clone(&fetchurl),
-        pkg_config: LazyDrv::new(Noop),
+        pkg_config: LazyDrv::clone(&pkg_config),
         perl: LazyDrv::clone(&perl),
+        update_autotools_gnu_config_scripts_hook: None,
     });
     pkgs.insert("curl".to_string(), LazyDrv::clone(&curl));
 
@@ -79,23 +207,123 @@ pub fn all_pkgs() -> (HashMap<String, LazyDrv>, Box<AllPkgs>) {
     });
     pkgs.insert("hello".to_string(), LazyDrv::clone(&hello));
 
-    (
-        pkgs,
-        Box::new(AllPkgs {
-            stdenv,
-            fetchurl,
-            zlib,
-            libiconv,
-            pkg_config,
-            perl,
-            curl,
-            hello,
-        }),
-    )
+    let git = LazyDrv::new(Git {
+        stdenv: Stdenv::clone(&stdenv),
+        fetchurl: FetchUrl::clone(&fetchurl),
+        zlib: LazyDrv::clone(&zlib),
+        curl: LazyDrv::clone(&curl),
+    });
+    // registered once its tarball has a real hash instead of fake_hash()
+    let fetchgit = FetchGit::new(Stdenv::clone(&stdenv), LazyDrv::clone(&git));
+    let forges = FetchFromForge::new(FetchZip::clone(&fetchzip), FetchGit::clone(&fetchgit));
+
+    let rust = LazyDrv::new(RustBin {
+        stdenv: Stdenv::clone(&stdenv),
+        fetchurl: FetchUrl::clone(&fetchurl),
+        targets: Vec::new(),
+    });
+    // registered once the binaries and rust-std have real hashes
+    let rust_platform = RustPlatform::new(
+        Stdenv::clone(&stdenv),
+        FetchUrl::clone(&fetchurl),
+        LazyDrv::clone(&rust),
+    );
+
+    let oxide = LazyDrv::new(Oxide {
+        rust_platform: RustPlatform::clone(&rust_platform),
+        forges: FetchFromForge::clone(&forges),
+    });
+    // registered once its source and its vendored crates have real hashes
+
+    let cmake = LazyDrv::new(CMake {
+        stdenv: Stdenv::clone(&stdenv),
+        fetchurl: FetchUrl::clone(&fetchurl),
+    });
+    // registered once its tarball has a real hash instead of fake_hash()
+
+    let ninja = LazyDrv::new(Ninja {
+        stdenv: Stdenv::clone(&stdenv),
+        forges: FetchFromForge::clone(&forges),
+        cmake: LazyDrv::clone(&cmake),
+    });
+    // registered once its source has a real hash, it also needs cmake
+
+    let python3 = LazyDrv::new(Python3 {
+        stdenv: Stdenv::clone(&stdenv),
+        fetchurl: FetchUrl::clone(&fetchurl),
+        zlib: LazyDrv::clone(&zlib),
+    });
+    // registered once its tarball has a real hash instead of fake_hash()
+
+    let python_platform = PythonPlatform::new(Stdenv::clone(&stdenv), LazyDrv::clone(&python3));
+
+    let meson = LazyDrv::new(Meson {
+        stdenv: Stdenv::clone(&stdenv),
+        fetchurl: FetchUrl::clone(&fetchurl),
+        python3: LazyDrv::clone(&python3),
+    });
+    // registered once its tarball has a real hash instead of fake_hash()
+
+    let go = LazyDrv::new(GoBin {
+        stdenv: Stdenv::clone(&stdenv),
+        fetchurl: FetchUrl::clone(&fetchurl),
+    });
+    // registered once the binaries have real hashes instead of fake_hash()
+    let go_platform = GoPlatform::new(Stdenv::clone(&stdenv), LazyDrv::clone(&go));
+
+    let make_wrapper = LazyDrv::new(MakeWrapper {
+        stdenv: Stdenv::clone(&stdenv),
+    });
+    pkgs.insert("make-wrapper".to_string(), LazyDrv::clone(&make_wrapper));
+
+    let all = Box::new(AllPkgs {
+        stdenv,
+        trivial_builders,
+        docker_tools,
+        fetchurl,
+        fetchzip,
+        fetchpatch,
+        fetchgit,
+        forges,
+        zlib,
+        gnu_config,
+        update_autotools_gnu_config_scripts_hook,
+        libiconv,
+        pkg_config_unwrapped,
+        pkg_config,
+        perl,
+        perl_platform,
+        gnum4,
+        autoconf,
+        automake,
+        libtool,
+        autoreconf_hook,
+        curl,
+        hello,
+        git,
+        rust,
+        rust_platform,
+        oxide,
+        cmake,
+        ninja,
+        python3,
+        python_platform,
+        meson,
+        go,
+        go_platform,
+        make_wrapper,
+        tests,
+    });
+    // the tests are attributes of their own so that they build the same way
+    for (attr, tests) in all_tests(&all) {
+        for (name, test) in tests {
+            pkgs.insert(test_attr(attr, name), test);
+        }
+    }
+    (pkgs, all)
 }
 
-fn build_stdenv() -> Stdenv {
-    let system = current_system();
+fn build_stdenv(system: System) -> Stdenv {
     match system {
         System::x86_64_linux | System::i686_linux => {
             Stdenv::new(stdenv::linux::build_stdenv(system, true))
@@ -105,7 +333,9 @@ fn build_stdenv() -> Stdenv {
 }
 
 pub fn build_fetchurl(stdenv: &Stdenv) -> FetchUrl {
-    // to build fetchurl we must use builtins fetchurl to fetch its dependencies
+    // to build fetchurl we must use builtins fetchurl to fetch its dependencies.
+    // They keep the config.guess and config.sub of their tarballs, which know
+    // the build platform, so that only their sources have to be downloaded
     FetchUrl::new(StdenvFetchUrl {
         stdenv_no_cc: Stdenv::clone(&stdenv),
         curl: LazyDrv::new(Curl {
@@ -117,11 +347,12 @@ pub fn build_fetchurl(stdenv: &Stdenv) -> FetchUrl {
                 libiconv: LazyDrv::new(LibIConv {
                     stdenv: Stdenv::clone(&stdenv),
                     fetchurl: FetchUrl::Builtins,
-                    update_autotools_gnu_config_scripts: "".into(),
+                    update_autotools_gnu_config_scripts_hook: None,
                     r#static: None,
                     shared: None,
                 }),
                 vanilla: None,
+                update_autotools_gnu_config_scripts_hook: None,
             }),
             perl: LazyDrv::new(Perl {
                 stdenv: Stdenv::clone(&stdenv),
@@ -135,6 +366,7 @@ pub fn build_fetchurl(stdenv: &Stdenv) -> FetchUrl {
                 }),
                 enable_threading: true,
             }),
+            update_autotools_gnu_config_scripts_hook: None,
         }),
     })
 }
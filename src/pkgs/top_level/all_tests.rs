@@ -0,0 +1,95 @@
+use crate::top_level::all_packages::AllPkgs;
+use oxide_core::prelude::*;
+use std::collections::HashMap;
+
+// The attribute of the test `name` of the package `attr`
+pub fn test_attr(attr: &str, name: &str) -> String {
+    format!("{attr}.tests.{name}")
+}
+
+// The package and the name of a test attribute
+pub fn split_test_attr(attr: &str) -> Option<(&str, &str)> {
+    attr.split_once(".tests.")
+}
+
+// The tests of the packages of `all_pkgs`, by the same names. A test is a
+// derivation using the outputs of its package that fails to build when
+// the package does not work, building it runs it
+pub fn all_tests(pkgs: &AllPkgs) -> HashMap<&'static str, Vec<(&'static str, LazyDrv)>> {
+    let tests = &pkgs.tests;
+    HashMap::from([
+        (
+            "zlib",
+            vec![
+                ("pkg-config", tests.test_pkg_config(&pkgs.zlib, &["zlib"])),
+                (
+                    "linking",
+                    tests.test_linking(&pkgs.zlib, &["z"], "zlib.h", "zlibVersion()"),
+                ),
+            ],
+        ),
+        (
+            "libiconv",
+            vec![
+                (
+                    "version",
+                    tests.test_version(&pkgs.libiconv, "iconv --version"),
+                ),
+                (
+                    "linking",
+                    tests.test_linking(
+                        &pkgs.libiconv,
+                        &["iconv", "charset"],
+                        "iconv.h",
+                        r#"iconv_open("UTF-8", "ASCII")"#,
+                    ),
+                ),
+            ],
+        ),
+        (
+            "pkg-config",
+            vec![(
+                "version",
+                tests.test_version(&pkgs.pkg_config, "pkg-config --version"),
+            )],
+        ),
+        (
+            "perl",
+            vec![("version", tests.test_version(&pkgs.perl, "perl -v"))],
+        ),
+        (
+            "curl",
+            vec![
+                ("version", tests.test_version(&pkgs.curl, "curl --version")),
+                (
+                    "pkg-config",
+                    tests.test_pkg_config(&pkgs.curl, &["libcurl"]),
+                ),
+                (
+                    "linking",
+                    tests.test_linking(&pkgs.curl, &["curl"], "curl/curl.h", "curl_version()"),
+                ),
+            ],
+        ),
+        (
+            "hello",
+            vec![
+                (
+                    "version",
+                    tests.test_version(&pkgs.hello, "hello --version"),
+                ),
+                (
+                    "greeting",
+                    pkgs.trivial_builders
+                        .run_command(
+                            "hello-test-greeting",
+                            r#""$hello/bin/hello" | grep -x 'Hello, world!'
+touch "$out""#,
+                        )
+                        .input("hello", LazyDrv::clone(&pkgs.hello))
+                        .lazy(),
+                ),
+            ],
+        ),
+    ])
+}
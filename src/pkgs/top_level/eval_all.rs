@@ -0,0 +1,167 @@
+use crate::top_level::all_packages::{all_pkgs, all_pkgs_for};
+use oxide_core::prelude::*;
+use serde_json::{Value, json};
+use sha2::{Digest, Sha256};
+use std::{
+    any::Any,
+    collections::BTreeMap,
+    fs,
+    panic::{self, AssertUnwindSafe},
+    path::Path,
+};
+
+pub struct Evaluation {
+    pub system: System,
+    pub attr: String,
+    // the derivation hash, or the panic message
+    pub result: Result<String, String>,
+}
+
+// The contents of the file a `local_file!` path names. Those paths are
+// `file!()/../<path>`, relative to the crate root unless `file!()` is absolute
+fn local_file(s: &str) -> Option<Vec<u8>> {
+    let (source, path) = s.split_once(".rs/../")?;
+    let dir = Path::new(source).parent()?;
+    let path = Path::new(env!("CARGO_MANIFEST_DIR")).join(dir).join(path);
+    Some(fs::read(&path).unwrap_or_else(|e| panic!("cannot read {}: {e}", path.display())))
+}
+
+fn expr_json(expr: &Expr) -> Value {
+    match expr {
+        // what is built changes with the contents of a local file, not its path
+        Expr::Str(s) => match local_file(s) {
+            Some(contents) => json!({
+                "local_file": format!("{:x}", Sha256::digest(contents)),
+            }),
+            None => json!(s),
+        },
+        Expr::List(exprs) => exprs.iter().map(expr_json).collect(),
+        Expr::Drv(drv, output) => json!({
+            "drv": drv_hash(&LazyDrv::clone(drv).into_drv()),
+            "output": output,
+        }),
+        Expr::Suff(expr, suffix) => json!({
+            "expr": expr_json(expr),
+            "suffix": suffix,
+        }),
+    }
+}
+
+// Changes with anything that changes what `drv` builds, the derivations it
+// refers to included. Fixed output derivations only depend on their name,
+// outputs and hash, changing how a source is fetched rebuilds nothing
+pub fn drv_hash(drv: &Drv) -> String {
+    let value = match &drv.hash {
+        Some(hash) => json!({
+            "name": drv.name,
+            "outputs": drv.outputs,
+            "hash": hash.to_string(),
+        }),
+        None => json!({
+            "name": drv.name,
+            "system": drv.system.map(|system| system.to_string()),
+            "builder": drv.builder.as_ref().map(expr_json),
+            "args": drv.args.iter().map(expr_json).collect::<Vec<_>>(),
+            "outputs": drv.outputs,
+            "inputs": drv
+                .inputs
+                .iter()
+                .map(|(key, expr)| (key.clone(), expr_json(expr)))
+                .collect::<serde_json::Map<_, _>>(),
+        }),
+    };
+    format!("{:x}", Sha256::digest(value.to_string()))
+}
+
+fn panic_message(payload: Box<dyn Any + Send>) -> String {
+    match payload.downcast::<String>() {
+        Ok(message) => *message,
+        Err(payload) => match payload.downcast::<&'static str>() {
+            Ok(message) => message.to_string(),
+            Err(_) => "panicked".to_string(),
+        },
+    }
+}
+
+// Every package of the set evaluated for every system. Each package gets a
+// package set of its own so that a panic leaves nothing half evaluated
+pub fn eval_all(systems: &[System]) -> Vec<Evaluation> {
+    let (pkgs, _) = all_pkgs();
+    let mut attrs: Vec<String> = pkgs.into_keys().collect();
+    attrs.sort();
+    let mut evaluations = Vec::new();
+    for system in systems {
+        for attr in &attrs {
+            let result = panic::catch_unwind(AssertUnwindSafe(|| {
+                let (pkgs, _) = all_pkgs_for(*system);
+                drv_hash(&LazyDrv::clone(&pkgs[attr]).into_drv())
+            }));
+            evaluations.push(Evaluation {
+                system: *system,
+                attr: attr.clone(),
+                result: result.map_err(panic_message),
+            });
+        }
+    }
+    evaluations
+}
+
+// A row per package and a column per system, the panic messages below
+pub fn summary(evaluations: &[Evaluation]) -> String {
+    let mut systems: Vec<String> = Vec::new();
+    let mut rows: BTreeMap<&str, Vec<&str>> = BTreeMap::new();
+    let mut failures = Vec::new();
+    for evaluation in evaluations {
+        let system = evaluation.system.to_string();
+        if !systems.contains(&system) {
+            systems.push(system.clone());
+        }
+        let status = match &evaluation.result {
+            Ok(_) => "ok",
+            Err(message) => {
+                failures.push(format!("{system} {}: {message}", evaluation.attr));
+                "FAILED"
+            }
+        };
+        rows.entry(&evaluation.attr).or_default().push(status);
+    }
+    let attr_width = rows.keys().map(|attr| attr.len()).chain([4]).max().unwrap();
+    let mut lines = vec![format!("{:attr_width$}  {}", "ATTR", systems.join("  "))];
+    for (attr, statuses) in &rows {
+        let statuses: Vec<String> = statuses
+            .iter()
+            .zip(&systems)
+            .map(|(status, system)| format!("{status:width$}", width = system.len()))
+            .collect();
+        lines.push(format!("{attr:attr_width$}  {}", statuses.join("  ")));
+    }
+    lines.push(format!(
+        "{} packages, {} systems, {} failed",
+        rows.len(),
+        systems.len(),
+        failures.len()
+    ));
+    if !failures.is_empty() {
+        lines.push(String::new());
+        lines.extend(failures);
+    }
+    lines
+        .iter()
+        .map(|line| format!("{}\n", line.trim_end()))
+        .collect()
+}
+
+// One line per package and system, stable to be checked in: a change of a
+// hash is a rebuild
+pub fn snapshot(evaluations: &[Evaluation]) -> String {
+    evaluations
+        .iter()
+        .map(|evaluation| {
+            let hash = match &evaluation.result {
+                Ok(hash) => hash.as_str(),
+                Err(_) => "failed",
+            };
+            format!("{} {} {hash}\n", evaluation.system, evaluation.attr)
+        })
+        .collect()
+}
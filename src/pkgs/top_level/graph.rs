@@ -0,0 +1,382 @@
+use crate::{
+    lib::parse_drv_name,
+    top_level::{eval_all::drv_hash, registry::direct_drvs},
+};
+use oxide_core::prelude::*;
+use serde_json::{Value, json};
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap, VecDeque},
+    fs, io,
+    path::Path,
+};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum EdgeKind {
+    // an input, the builder or an argument of the derivation
+    Build,
+    // a store path named by the files of a built output
+    Runtime,
+    // from an output to the derivation building it, with `by_output`
+    Output,
+}
+
+impl EdgeKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            EdgeKind::Build => "build",
+            EdgeKind::Runtime => "runtime",
+            EdgeKind::Output => "output",
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Edge {
+    pub from: String,
+    pub to: String,
+    pub kind: EdgeKind,
+}
+
+// Derivations are identified by their `drv_hash`, as different derivations
+// may have the same name, store paths by their base name and outputs, with
+// `by_output`, by `id^output`. Nodes are labelled with their names
+pub struct Graph {
+    pub root: String,
+    // the shortest distance of each node from the root
+    pub nodes: BTreeMap<String, usize>,
+    pub labels: BTreeMap<String, String>,
+    pub edges: BTreeSet<Edge>,
+    // the path from the root to the `why` package, if it depends on it
+    pub why: Vec<String>,
+}
+
+pub struct GraphArgs {
+    pub depth: Option<usize>,
+    pub by_output: bool,
+    // name of the package, with or without its version, or of the node
+    pub why: Option<String>,
+}
+
+impl Default for GraphArgs {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl GraphArgs {
+    pub fn new() -> Self {
+        Self {
+            depth: None,
+            by_output: false,
+            why: None,
+        }
+    }
+
+    pub fn depth(mut self, depth: usize) -> Self {
+        self.depth = Some(depth);
+        self
+    }
+
+    pub fn by_output(mut self) -> Self {
+        self.by_output = true;
+        self
+    }
+
+    pub fn why<T>(mut self, why: T) -> Self
+    where
+        T: Into<String>,
+    {
+        self.why = Some(why.into());
+        self
+    }
+}
+
+// A reference found while walking the graph, `node` is its id
+struct Reference<T> {
+    node: String,
+    label: String,
+    output: Option<String>,
+    target: T,
+}
+
+// Breadth first so that every node gets its shortest distance, the depth
+// limit is applied once the `why` path is known so that it is kept whole
+fn walk<T, F>(root: Reference<T>, kind: EdgeKind, args: &GraphArgs, mut refs: F) -> Graph
+where
+    F: FnMut(&T) -> Vec<Reference<T>>,
+{
+    let mut graph = Graph {
+        root: root.node.clone(),
+        nodes: BTreeMap::from([(root.node.clone(), 0)]),
+        labels: BTreeMap::from([(root.node.clone(), root.label)]),
+        edges: BTreeSet::new(),
+        why: Vec::new(),
+    };
+    let mut queue = VecDeque::from([(root.node, root.target)]);
+    while let Some((from, target)) = queue.pop_front() {
+        let depth = graph.nodes[&from];
+        for reference in refs(&target) {
+            let to = match (args.by_output, kind) {
+                (true, EdgeKind::Build) => {
+                    let output = reference.output.as_deref().unwrap_or("out");
+                    let to = format!("{}^{output}", reference.node);
+                    graph.nodes.entry(to.clone()).or_insert(depth + 1);
+                    graph
+                        .labels
+                        .insert(to.clone(), format!("{}^{output}", reference.label));
+                    graph.edges.insert(Edge {
+                        from: to.clone(),
+                        to: reference.node.clone(),
+                        kind: EdgeKind::Output,
+                    });
+                    to
+                }
+                _ => reference.node.clone(),
+            };
+            graph.edges.insert(Edge {
+                from: from.clone(),
+                to,
+                kind,
+            });
+            if !graph.nodes.contains_key(&reference.node) {
+                graph.nodes.insert(reference.node.clone(), depth + 1);
+                graph.labels.insert(reference.node.clone(), reference.label);
+                queue.push_back((reference.node, reference.target));
+            }
+        }
+    }
+    if let Some(why) = &args.why {
+        graph.why = graph.path_to(why).unwrap_or_default();
+    }
+    if let Some(limit) = args.depth {
+        let why: BTreeSet<&String> = graph.why.iter().collect();
+        let keep: BTreeSet<String> = graph
+            .nodes
+            .iter()
+            .filter(|(node, depth)| **depth <= limit || why.contains(node))
+            .map(|(node, _)| node.clone())
+            .collect();
+        graph.nodes.retain(|node, _| keep.contains(node));
+        graph.labels.retain(|node, _| keep.contains(node));
+        graph
+            .edges
+            .retain(|edge| keep.contains(&edge.from) && keep.contains(&edge.to));
+    }
+    graph
+}
+
+// The build time graph, evaluating every derivation `drv` refers to
+pub fn build_graph(drv: &LazyDrv, args: &GraphArgs) -> Graph {
+    let reference = |drv: Drv, output| Reference {
+        node: drv_hash(&drv),
+        label: drv.name.clone(),
+        output,
+        target: drv,
+    };
+    let root = reference(LazyDrv::clone(drv).into_drv(), None);
+    walk(root, EdgeKind::Build, args, |drv| {
+        direct_drvs(drv)
+            .into_iter()
+            .map(|(dep, output)| reference(dep.into_drv(), output))
+            .collect()
+    })
+}
+
+// The runtime graph of a built output, from the store paths its files and
+// symlinks name, the store being the directory of `path`
+pub fn runtime_graph(path: &Path, args: &GraphArgs) -> io::Result<Graph> {
+    let path = path.canonicalize()?;
+    let store_dir = path.parent().unwrap_or(Path::new("/")).to_path_buf();
+    let root = Reference {
+        node: base_name(&path),
+        label: base_name(&path),
+        output: None,
+        target: path,
+    };
+    let mut error = None;
+    let graph = walk(root, EdgeKind::Runtime, args, |path| {
+        match references(path, &store_dir) {
+            Ok(refs) => refs
+                .into_iter()
+                .map(|name| Reference {
+                    target: store_dir.join(&name),
+                    label: name.clone(),
+                    node: name,
+                    output: None,
+                })
+                .collect(),
+            Err(e) => {
+                error.get_or_insert(e);
+                Vec::new()
+            }
+        }
+    });
+    match error {
+        Some(e) => Err(e),
+        None => Ok(graph),
+    }
+}
+
+fn base_name(path: &Path) -> String {
+    path.file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default()
+}
+
+fn is_store_name_byte(byte: u8) -> bool {
+    byte.is_ascii_alphanumeric() || b"+-._?=".contains(&byte)
+}
+
+// The existing store paths named in `bytes`, but `path` itself
+fn scan(bytes: &[u8], path: &Path, store_dir: &Path, found: &mut BTreeSet<String>) {
+    let prefix = format!("{}/", store_dir.display());
+    let prefix = prefix.as_bytes();
+    let mut start = 0;
+    while let Some(i) = bytes[start..]
+        .windows(prefix.len())
+        .position(|window| window == prefix)
+    {
+        let name_start = start + i + prefix.len();
+        let name_len = bytes[name_start..]
+            .iter()
+            .take_while(|byte| is_store_name_byte(**byte))
+            .count();
+        let name = String::from_utf8_lossy(&bytes[name_start..name_start + name_len]);
+        let reference = store_dir.join(name.as_ref());
+        if name_len > 0 && reference != path && reference.exists() {
+            found.insert(name.into_owned());
+        }
+        start = name_start + name_len;
+    }
+}
+
+fn references(path: &Path, store_dir: &Path) -> io::Result<BTreeSet<String>> {
+    let mut found = BTreeSet::new();
+    let mut pending = vec![path.to_path_buf()];
+    while let Some(file) = pending.pop() {
+        let metadata = fs::symlink_metadata(&file)?;
+        if metadata.is_symlink() {
+            let target = fs::read_link(&file)?;
+            scan(
+                target.as_os_str().as_encoded_bytes(),
+                path,
+                store_dir,
+                &mut found,
+            );
+        } else if metadata.is_dir() {
+            for entry in fs::read_dir(&file)? {
+                pending.push(entry?.path());
+            }
+        } else {
+            scan(&fs::read(&file)?, path, store_dir, &mut found);
+        }
+    }
+    Ok(found)
+}
+
+// `why` is the id or the label of `node`, or the name of its package
+fn matches(node: &str, label: &str, why: &str) -> bool {
+    let drv_name = label.split('^').next().unwrap_or(label);
+    node == why || label == why || drv_name == why || parse_drv_name(drv_name).0 == why
+}
+
+fn quote(s: &str) -> String {
+    format!("\"{}\"", s.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+impl Graph {
+    pub fn label<'a>(&'a self, node: &'a str) -> &'a str {
+        self.labels.get(node).map_or(node, String::as_str)
+    }
+
+    // The shortest path from the root to a node matching `why`
+    pub fn path_to(&self, why: &str) -> Option<Vec<String>> {
+        let mut next: HashMap<&str, Vec<&str>> = HashMap::new();
+        for edge in &self.edges {
+            next.entry(&edge.from).or_default().push(&edge.to);
+        }
+        let mut parents: HashMap<&str, &str> = HashMap::new();
+        let mut queue = VecDeque::from([self.root.as_str()]);
+        while let Some(node) = queue.pop_front() {
+            if node != self.root && matches(node, self.label(node), why) {
+                let mut path = vec![node.to_string()];
+                let mut node = node;
+                while let Some(parent) = parents.get(node) {
+                    path.push(parent.to_string());
+                    node = parent;
+                }
+                path.reverse();
+                return Some(path);
+            }
+            for to in next.get(node).into_iter().flatten() {
+                if *to != self.root && !parents.contains_key(to) {
+                    parents.insert(to, node);
+                    queue.push_back(to);
+                }
+            }
+        }
+        None
+    }
+
+    fn on_why_path(&self, edge: &Edge) -> bool {
+        self.why
+            .windows(2)
+            .any(|pair| pair[0] == edge.from && pair[1] == edge.to)
+    }
+
+    pub fn to_dot(&self) -> String {
+        let mut lines = vec![
+            format!("digraph {} {{", quote(self.label(&self.root))),
+            "  rankdir=LR;".to_string(),
+            "  node [shape=box];".to_string(),
+        ];
+        for node in self.nodes.keys() {
+            let label = format!("label={}", quote(self.label(node)));
+            let mut attrs = vec![label.as_str()];
+            if node.contains('^') {
+                attrs.push("shape=ellipse");
+            }
+            if *node == self.root {
+                attrs.push("style=bold");
+            }
+            if self.why.contains(node) {
+                attrs.push("color=red");
+            }
+            lines.push(format!("  {} [{}];", quote(node), attrs.join(", ")));
+        }
+        for edge in &self.edges {
+            let mut attrs = Vec::new();
+            if edge.kind == EdgeKind::Output {
+                attrs.push("style=dotted");
+            }
+            if self.on_why_path(edge) {
+                attrs.push("color=red");
+                attrs.push("penwidth=2");
+            }
+            let arrow = format!("{} -> {}", quote(&edge.from), quote(&edge.to));
+            match attrs.is_empty() {
+                true => lines.push(format!("  {arrow};")),
+                false => lines.push(format!("  {arrow} [{}];", attrs.join(", "))),
+            }
+        }
+        lines.push("}".to_string());
+        lines.iter().map(|line| format!("{line}\n")).collect()
+    }
+
+    pub fn to_json(&self) -> Value {
+        json!({
+            "root": self.root,
+            "nodes": self.nodes.iter().map(|(node, depth)| json!({
+                "id": node,
+                "name": self.label(node),
+                "depth": depth,
+            })).collect::<Vec<_>>(),
+            "edges": self.edges.iter().map(|edge| json!({
+                "from": edge.from,
+                "to": edge.to,
+                "kind": edge.kind.as_str(),
+            })).collect::<Vec<_>>(),
+            "why": (!self.why.is_empty()).then_some(&self.why),
+        })
+    }
+}
@@ -1 +1,6 @@
+pub mod all_meta;
 pub mod all_packages;
+pub mod all_tests;
+pub mod eval_all;
+pub mod graph;
+pub mod registry;
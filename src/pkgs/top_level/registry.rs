@@ -0,0 +1,133 @@
+use crate::{
+    lib::{meta::Meta, parse_drv_name},
+    top_level::{all_meta::all_meta, all_tests::split_test_attr},
+};
+use oxide_core::prelude::*;
+use serde_json::{Value, json};
+use std::collections::HashMap;
+
+// A package of `all_pkgs` as known from its evaluation, nothing is built
+pub struct PkgInfo {
+    pub attr: String,
+    pub name: String,
+    pub version: Option<String>,
+    pub outputs: Vec<String>,
+    pub system: Option<String>,
+    // TODO: add the .drv path for `show` once oxide_core exposes how it
+    // computes it, evaluating does not write the derivation to the store
+    pub meta: Option<Meta>,
+    // the derivations its inputs, builder and arguments refer to
+    pub deps: Vec<Dep>,
+    // the attributes of its tests
+    pub tests: Vec<String>,
+}
+
+#[derive(Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Dep {
+    pub name: String,
+    pub output: Option<String>,
+}
+
+impl PkgInfo {
+    pub fn to_json(&self) -> Value {
+        json!({
+            "attr": self.attr,
+            "name": self.name,
+            "version": self.version,
+            "outputs": self.outputs,
+            "system": self.system,
+            "meta": self.meta.map(|meta| json!({
+                "description": meta.description,
+                "homepage": meta.homepage,
+                "license": meta.license,
+            })),
+            "deps": self.deps.iter().map(|dep| json!({
+                "name": dep.name,
+                "output": dep.output,
+            })).collect::<Vec<_>>(),
+            "tests": self.tests,
+        })
+    }
+}
+
+fn collect_drvs(expr: &Expr, drvs: &mut Vec<(LazyDrv, Option<String>)>) {
+    match expr {
+        Expr::Str(_) => {}
+        Expr::List(exprs) => {
+            for expr in exprs {
+                collect_drvs(expr, drvs);
+            }
+        }
+        Expr::Drv(drv, output) => drvs.push((LazyDrv::clone(drv), output.clone())),
+        Expr::Suff(expr, _) => collect_drvs(expr, drvs),
+    }
+}
+
+// The derivations `drv` refers to with the output it uses, if not the default one
+pub fn direct_drvs(drv: &Drv) -> Vec<(LazyDrv, Option<String>)> {
+    let mut drvs = Vec::new();
+    if let Some(builder) = &drv.builder {
+        collect_drvs(builder, &mut drvs);
+    }
+    for expr in drv.args.iter().chain(drv.inputs.values()) {
+        collect_drvs(expr, &mut drvs);
+    }
+    drvs
+}
+
+pub fn evaluate(attr: &str, drv: &LazyDrv, meta: Option<Meta>) -> PkgInfo {
+    let drv = LazyDrv::clone(drv).into_drv();
+    let (name, version) = parse_drv_name(&drv.name);
+    let mut deps: Vec<Dep> = direct_drvs(&drv)
+        .into_iter()
+        .map(|(dep, output)| Dep {
+            name: dep.into_drv().name,
+            output,
+        })
+        .collect();
+    deps.sort();
+    deps.dedup();
+    let outputs = if drv.outputs.is_empty() {
+        vec!["out".to_string()]
+    } else {
+        drv.outputs.clone()
+    };
+    PkgInfo {
+        attr: attr.to_string(),
+        name: name.to_string(),
+        version: version.map(str::to_string),
+        outputs,
+        system: drv.system.map(|system| system.to_string()),
+        meta,
+        deps,
+        tests: Vec::new(),
+    }
+}
+
+// The attributes of the tests of the package `attr`, sorted
+pub fn tests_of(pkgs: &HashMap<String, LazyDrv>, attr: &str) -> Vec<String> {
+    let mut tests: Vec<String> = pkgs
+        .keys()
+        .filter(|test| split_test_attr(test).is_some_and(|(pkg, _)| pkg == attr))
+        .cloned()
+        .collect();
+    tests.sort();
+    tests
+}
+
+// Every package of `pkgs` but the tests evaluated, sorted by attribute name
+pub fn evaluate_all(pkgs: &HashMap<String, LazyDrv>) -> Vec<PkgInfo> {
+    let meta = all_meta();
+    let mut attrs: Vec<&String> = pkgs
+        .keys()
+        .filter(|attr| split_test_attr(attr).is_none())
+        .collect();
+    attrs.sort();
+    attrs
+        .into_iter()
+        .map(|attr| PkgInfo {
+            tests: tests_of(pkgs, attr),
+            ..evaluate(attr, &pkgs[attr], meta.get(attr.as_str()).copied())
+        })
+        .collect()
+}
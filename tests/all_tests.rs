@@ -0,0 +1,231 @@
+mod common;
+
+use common::{Sandbox, assert_success};
+use oxide_core::prelude::*;
+use oxide_pkgs::top_level::{
+    all_packages::all_pkgs,
+    all_tests::{all_tests, split_test_attr, test_attr},
+    registry::direct_drvs,
+};
+use serde_json::{Value, json};
+use std::process::{Command, Output};
+
+#[test]
+fn tests_are_attributes_of_the_set() {
+    let (pkgs, all) = all_pkgs();
+    for (attr, tests) in all_tests(&all) {
+        let pkg = LazyDrv::clone(&pkgs[attr]).into_drv().name;
+        for (name, _) in tests {
+            let test = test_attr(attr, name);
+            assert_eq!(split_test_attr(&test), Some((attr, name)));
+            let drv = LazyDrv::clone(&pkgs[&test]).into_drv();
+            // built against the package itself
+            assert!(
+                direct_drvs(&drv)
+                    .into_iter()
+                    .any(|(dep, _)| dep.into_drv().name == pkg),
+                "{test} does not use {pkg}"
+            );
+        }
+    }
+    assert_eq!(split_test_attr("zlib"), None);
+}
+
+#[test]
+fn builds_the_tests_from_the_cli() {
+    let sandbox = Sandbox::new("all-tests-cli");
+    // fails the linking test only
+    let build = sandbox.write(
+        "build",
+        "#!/bin/sh\necho \"built $2\"\n[ \"$2\" != zlib.tests.linking ]\n",
+    );
+    sandbox.sh("chmod +x build");
+    let registry = |args: &[&str]| {
+        Command::new(env!("CARGO_BIN_EXE_registry"))
+            .args(args)
+            .env("OXIDE_BUILD", format!("{} --quiet", build.display()))
+            .output()
+            .unwrap()
+    };
+
+    let output = registry(&["--json", "test", "perl"]);
+    assert_success(&output);
+    let results: Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(
+        results,
+        json!([{"test": "perl.tests.version", "passed": true}])
+    );
+    assert!(String::from_utf8_lossy(&output.stderr).contains("built perl.tests.version"));
+
+    let output = registry(&["test", "zlib"]);
+    assert!(!output.status.success());
+    assert_eq!(
+        String::from_utf8(output.stdout).unwrap(),
+        "TEST                   RESULT\n\
+         zlib.tests.linking     FAILED\n\
+         zlib.tests.pkg-config  passed\n"
+    );
+    assert!(String::from_utf8_lossy(&output.stderr).contains("1 of 2 tests failed"));
+
+    let output = registry(&["--json", "test"]);
+    assert!(!output.status.success());
+    let results: Value = serde_json::from_slice(&output.stdout).unwrap();
+    let (pkgs, _) = all_pkgs();
+    assert_eq!(
+        results.as_array().unwrap().len(),
+        pkgs.keys()
+            .filter(|attr| split_test_attr(attr).is_some())
+            .count()
+    );
+
+    assert_success(&registry(&["test", "hello.tests.greeting"]));
+    assert!(!registry(&["test", "no-such-package"]).status.success());
+}
+
+// Runs the build command of the test `drv` with its string inputs, `env`
+// standing in for the outputs of the package
+fn run_test(sandbox: &Sandbox, drv: &Drv, env: &[(&str, &str)]) -> Output {
+    let mut command = Command::new("bash");
+    command
+        .args(["-euo", "pipefail", "-c"])
+        .arg("eval \"$BUILD_COMMAND\"")
+        .current_dir(&sandbox.dir)
+        .env("out", sandbox.path("out"));
+    for (key, value) in &drv.inputs {
+        if let Expr::Str(value) = value {
+            command.env(key, value.as_ref());
+        }
+    }
+    command.envs(env.iter().copied()).output().unwrap()
+}
+
+fn input(drv: &Drv, key: &str) -> String {
+    match &drv.inputs[key] {
+        Expr::Str(value) => value.to_string(),
+        _ => panic!("{key} is not a string"),
+    }
+}
+
+fn output_of(drv: &Drv, key: &str) -> Option<String> {
+    match &drv.inputs[key] {
+        Expr::Drv(_, output) => output.clone(),
+        _ => panic!("{key} is not a derivation"),
+    }
+}
+
+#[test]
+fn checks_the_version_printed() {
+    let (_, all) = all_pkgs();
+    let drv = all
+        .tests
+        .test_version(&all.hello, "hello --version")
+        .into_drv();
+    assert_eq!(drv.name, "hello-test-version");
+    assert_eq!(input(&drv, "VERSION"), "2.12.1");
+    assert_eq!(
+        all.tests.test_version(&all.perl, "perl -v").into_drv().name,
+        "perl-test-version"
+    );
+    assert_eq!(
+        input(
+            &all.tests
+                .test_version(&all.pkg_config, "pkg-config --version")
+                .into_drv(),
+            "VERSION"
+        ),
+        "0.29.2"
+    );
+
+    let sandbox = Sandbox::new("all-tests-version");
+    sandbox.write("bin/hello", "#!/bin/sh\necho 'hello (GNU Hello) 2.12.1'\n");
+    sandbox.sh("chmod +x bin/hello");
+    let path = format!("{}:/usr/bin:/bin", sandbox.path("bin").display());
+    let output = run_test(&sandbox, &drv, &[("PATH", &path)]);
+    assert_success(&output);
+    assert!(sandbox.path("out").exists());
+
+    let output = run_test(&sandbox, &drv, &[("PATH", &path), ("VERSION", "2.13")]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stdout).contains("does not print 2.13"));
+}
+
+#[test]
+fn checks_the_pc_files() {
+    let (_, all) = all_pkgs();
+    let drv = all.tests.test_pkg_config(&all.zlib, &["zlib"]).into_drv();
+    assert_eq!(drv.name, "zlib-test-pkg-config");
+    assert_eq!(output_of(&drv, "pkg_dev").as_deref(), Some("dev"));
+    assert_eq!(input(&drv, "MODULES"), "zlib");
+    assert_eq!(input(&drv, "VERSION"), "1.3.1");
+
+    let sandbox = Sandbox::new("all-tests-pkg-config");
+    sandbox.write(
+        "dev/lib/pkgconfig/zlib.pc",
+        "Name: zlib\nDescription: zlib\nVersion: 1.3.1\nLibs: -lz\n",
+    );
+    let dev = sandbox.path("dev").display().to_string();
+    assert_success(&run_test(&sandbox, &drv, &[("pkg_dev", &dev)]));
+    let output = run_test(&sandbox, &drv, &[("pkg_dev", &dev), ("VERSION", "1.3")]);
+    assert!(!output.status.success());
+    assert!(
+        String::from_utf8_lossy(&output.stdout).contains("zlib has version 1.3.1 instead of 1.3")
+    );
+}
+
+#[test]
+fn checks_the_libraries_link() {
+    let (_, all) = all_pkgs();
+    let drv = all
+        .tests
+        .test_linking(&all.zlib, &["z"], "zlib.h", "zlibVersion()")
+        .into_drv();
+    assert_eq!(drv.name, "zlib-test-linking");
+    assert_eq!(output_of(&drv, "pkg_dev").as_deref(), Some("dev"));
+    assert_eq!(output_of(&drv, "pkg_lib").as_deref(), Some("out"));
+    assert_eq!(input(&drv, "LIBS"), "-lz");
+    assert_eq!(input(&drv, "HEADER"), "zlib.h");
+    assert_eq!(input(&drv, "CALL"), "zlibVersion()");
+    // libiconv only has its default output
+    let drv = all
+        .tests
+        .test_linking(
+            &all.libiconv,
+            &["iconv", "charset"],
+            "iconv.h",
+            r#"iconv_open("UTF-8", "ASCII")"#,
+        )
+        .into_drv();
+    assert_eq!(output_of(&drv, "pkg_dev"), None);
+    assert_eq!(input(&drv, "LIBS"), "-liconv -lcharset");
+
+    let sandbox = Sandbox::new("all-tests-linking");
+    sandbox.write("pkg.c", "int pkg(void) { return 0; }\n");
+    sandbox.write("pkg/include/pkg.h", "int pkg(void);\nint missing(void);\n");
+    sandbox.sh("mkdir -p pkg/lib && cc -shared -fPIC pkg.c -o pkg/lib/libpkg.so");
+    let pkg = sandbox.path("pkg").display().to_string();
+    let env = [
+        ("pkg_dev", pkg.as_str()),
+        ("pkg_lib", pkg.as_str()),
+        ("HEADER", "pkg.h"),
+        ("CALL", "pkg()"),
+    ];
+    assert_success(&run_test(
+        &sandbox,
+        &drv,
+        &[&env[..], &[("LIBS", "-lpkg")]].concat(),
+    ));
+    let output = run_test(
+        &sandbox,
+        &drv,
+        &[&env[..], &[("LIBS", "-lmissing")]].concat(),
+    );
+    assert!(!output.status.success());
+    // the symbol called must be in the libraries
+    let output = run_test(
+        &sandbox,
+        &drv,
+        &[&env[..], &[("LIBS", "-lpkg"), ("CALL", "missing()")]].concat(),
+    );
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("missing"));
+}
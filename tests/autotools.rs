@@ -0,0 +1,147 @@
+mod common;
+
+use common::{Sandbox, assert_success, repo_file};
+use std::{fs, path::PathBuf, process::Output};
+
+// An autoreconf stand-in generating a configure recording how it ran
+const FAKE_AUTORECONF: &str = r#"#!/bin/bash
+echo "autoreconf $* ACLOCAL_PATH=${ACLOCAL_PATH:-}" >> "$LOG"
+printf '#!/bin/sh\necho configure >> "$LOG"\n' > configure
+chmod +x configure
+"#;
+
+fn default_builder() -> PathBuf {
+    repo_file("src/pkgs/stdenv/generic/scripts/default-builder.sh")
+}
+
+fn build(sandbox: &Sandbox, deps: &str, env: &[(&str, &str)]) -> Output {
+    let src = sandbox.path("src");
+    let log = sandbox.path("build.log");
+    let mut vars = vec![
+        ("SRC", src.to_str().unwrap()),
+        ("UNPACK", "1"),
+        ("CONFIGURE", "1"),
+        ("DEPS_BUILD_HOST", deps),
+        ("LOG", log.to_str().unwrap()),
+    ];
+    vars.extend_from_slice(env);
+    sandbox.build(&default_builder(), "out", &vars)
+}
+
+// Same derivation as `AutoreconfHook`, with autoconf and libtool stand-ins
+// and the automake setup hook
+fn autoreconf_hook(sandbox: &Sandbox) -> String {
+    let autoconf = sandbox.tool(
+        "autoreconf",
+        FAKE_AUTORECONF,
+        "src/pkgs/development/tools/misc/automake/setup-hook.sh",
+    );
+    sandbox.write("libtool/share/aclocal/libtool.m4", "");
+    let libtool = sandbox.path("libtool");
+    let propagated = format!("{} {}", autoconf.display(), libtool.display());
+    let hook = repo_file("src/pkgs/build/autotools/autoreconf-hook.sh");
+    assert_success(&sandbox.build(
+        &default_builder(),
+        "autoreconf-hook",
+        &[
+            ("SETUP_HOOK", hook.to_str().unwrap()),
+            ("PROPAGATED_HOST_TARGET", &propagated),
+            ("FIX", "1"),
+        ],
+    ));
+    sandbox
+        .path("autoreconf-hook")
+        .to_str()
+        .unwrap()
+        .to_string()
+}
+
+// Same derivation as `UpdateAutotoolsGnuConfigScriptsHook`
+fn update_hook(sandbox: &Sandbox) -> String {
+    sandbox.write("gnu-config/config.guess", "new guess\n");
+    sandbox.write("gnu-config/config.sub", "new sub\n");
+    let gnu_config = sandbox.path("gnu-config");
+    let hook = repo_file("src/pkgs/build/autotools/update-autotools-gnu-config-scripts-hook.sh");
+    assert_success(&sandbox.build(
+        &default_builder(),
+        "update-hook",
+        &[
+            ("SETUP_HOOK", hook.to_str().unwrap()),
+            ("GNU_CONFIG", gnu_config.to_str().unwrap()),
+            ("FIX", "1"),
+            (
+                "POST_FIX",
+                r#"substituteInPlace "$out/nix-support/setup-hook" --replace-fail @gnu_config@ "$GNU_CONFIG""#,
+            ),
+        ],
+    ));
+    sandbox.path("update-hook").to_str().unwrap().to_string()
+}
+
+#[test]
+fn autoreconf_runs_before_configure() {
+    let sandbox = Sandbox::new("autotools-autoreconf");
+    sandbox.write("src/configure.ac", "AC_INIT([hello], [1.0])\n");
+    // the hook alone brings autoreconf and the aclocal macros of libtool
+    let hook = autoreconf_hook(&sandbox);
+    assert_success(&build(&sandbox, &hook, &[]));
+    assert_eq!(
+        fs::read_to_string(sandbox.path("build.log")).unwrap(),
+        format!(
+            "autoreconf -fi ACLOCAL_PATH={}/share/aclocal\nconfigure\n",
+            sandbox.path("libtool").display()
+        )
+    );
+}
+
+#[test]
+fn autoreconf_flags_replace_the_default() {
+    let sandbox = Sandbox::new("autotools-autoreconf-flags");
+    sandbox.write("src/configure.ac", "AC_INIT([hello], [1.0])\n");
+    let hook = autoreconf_hook(&sandbox);
+    assert_success(&build(
+        &sandbox,
+        &hook,
+        &[("AUTORECONF_FLAGS", "--install --verbose")],
+    ));
+    let log = fs::read_to_string(sandbox.path("build.log")).unwrap();
+    assert!(log.starts_with("autoreconf --install --verbose "), "{log}");
+}
+
+#[test]
+fn updates_every_config_script() {
+    let sandbox = Sandbox::new("autotools-gnu-config");
+    sandbox.write("src/configure", "#!/bin/sh\n");
+    sandbox.write("src/config.guess", "old guess\n");
+    sandbox.write("src/config.sub", "old sub\n");
+    sandbox.write("src/glib/config.guess", "old guess\n");
+    sandbox.sh("chmod +x src/configure");
+    let hook = update_hook(&sandbox);
+    assert_success(&build(&sandbox, &hook, &[]));
+    let src = sandbox.path("build-out/src");
+    for (file, content) in [
+        ("config.guess", "new guess\n"),
+        ("config.sub", "new sub\n"),
+        ("glib/config.guess", "new guess\n"),
+    ] {
+        assert_eq!(fs::read_to_string(src.join(file)).unwrap(), content);
+    }
+}
+
+#[test]
+fn config_scripts_update_can_be_disabled() {
+    let sandbox = Sandbox::new("autotools-gnu-config-opt-out");
+    sandbox.write("src/configure", "#!/bin/sh\n");
+    sandbox.write("src/config.guess", "old guess\n");
+    sandbox.sh("chmod +x src/configure");
+    let hook = update_hook(&sandbox);
+    assert_success(&build(
+        &sandbox,
+        &hook,
+        &[("DONT_UPDATE_AUTOTOOLS_GNU_CONFIG_SCRIPTS", "1")],
+    ));
+    assert_eq!(
+        fs::read_to_string(sandbox.path("build-out/src/config.guess")).unwrap(),
+        "old guess\n"
+    );
+}
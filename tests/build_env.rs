@@ -0,0 +1,113 @@
+mod common;
+
+use common::{Sandbox, assert_success, repo_file};
+use std::{fs, process::Output};
+
+fn build(sandbox: &Sandbox, paths: &[&str], env: &[(&str, &str)]) -> Output {
+    let paths: Vec<String> = paths
+        .iter()
+        .map(|path| sandbox.path(path).to_str().unwrap().to_string())
+        .collect();
+    let paths = paths.join(" ");
+    let mut vars = vec![("PATHS", paths.as_str()), ("PATHS_TO_LINK", "/")];
+    vars.extend_from_slice(env);
+    sandbox.build(
+        &repo_file("src/pkgs/build/build_env/builder.sh"),
+        "env",
+        &vars,
+    )
+}
+
+// Two packages with a man output and a conflicting bin/tool
+fn packages(sandbox: &Sandbox) {
+    sandbox.write("curl/bin/curl", "curl\n");
+    sandbox.write("curl/bin/tool", "curl tool\n");
+    sandbox.write("curl/share/doc/curl", "");
+    sandbox.write("curl-man/share/man/man1/curl.1", "");
+    sandbox.write("zlib/lib/libz.so", "");
+    sandbox.write("zlib/bin/tool", "zlib tool\n");
+    sandbox.write("zlib/share/doc/zlib", "");
+}
+
+#[test]
+fn merges_the_outputs_of_the_paths() {
+    let sandbox = Sandbox::new("build-env-merge");
+    packages(&sandbox);
+    assert_success(&build(
+        &sandbox,
+        &["curl", "curl-man", "zlib"],
+        &[("COLLISIONS", "ignore")],
+    ));
+    let env = sandbox.path("env");
+    for (file, path) in [
+        ("bin/curl", "curl/bin/curl"),
+        ("bin/tool", "curl/bin/tool"),
+        ("lib/libz.so", "zlib/lib/libz.so"),
+        ("share/doc/zlib", "zlib/share/doc/zlib"),
+        ("share/man/man1/curl.1", "curl-man/share/man/man1/curl.1"),
+    ] {
+        assert_eq!(fs::read_link(env.join(file)).unwrap(), sandbox.path(path));
+    }
+    assert!(!env.join("share").is_symlink());
+}
+
+#[test]
+fn links_only_the_paths_to_link() {
+    let sandbox = Sandbox::new("build-env-paths-to-link");
+    packages(&sandbox);
+    assert_success(&build(
+        &sandbox,
+        &["curl", "curl-man", "zlib"],
+        &[
+            ("PATHS_TO_LINK", "/bin /share/man"),
+            ("COLLISIONS", "ignore"),
+        ],
+    ));
+    let env = sandbox.path("env");
+    assert!(env.join("bin/curl").is_symlink());
+    assert!(env.join("share/man/man1/curl.1").is_symlink());
+    assert!(!env.join("lib").exists());
+    assert!(!env.join("share/doc").exists());
+}
+
+#[test]
+fn collisions_name_both_paths() {
+    let sandbox = Sandbox::new("build-env-collisions");
+    packages(&sandbox);
+    let report = format!(
+        "collision between '{}' and '{}'",
+        sandbox.path("curl/bin/tool").display(),
+        sandbox.path("zlib/bin/tool").display()
+    );
+
+    let output = build(&sandbox, &["curl", "zlib"], &[]);
+    assert!(!output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains(&format!("error: {report}")), "{stdout}");
+
+    fs::remove_dir_all(sandbox.path("env")).unwrap();
+    let output = build(&sandbox, &["curl", "zlib"], &[("COLLISIONS", "warn")]);
+    assert_success(&output);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains(&format!("warning: {report}")), "{stdout}");
+    assert_eq!(
+        fs::read_to_string(sandbox.path("env/bin/tool")).unwrap(),
+        "curl tool\n"
+    );
+}
+
+#[test]
+fn identical_files_do_not_collide_and_post_build_runs_in_out() {
+    let sandbox = Sandbox::new("build-env-post-build");
+    sandbox.write("a/etc/motd", "same\n");
+    sandbox.write("b/etc/motd", "same\n");
+    assert_success(&build(
+        &sandbox,
+        &["a", "b"],
+        &[("POST_BUILD", "ls etc > contents")],
+    ));
+    assert_eq!(
+        fs::read_to_string(sandbox.path("env/contents")).unwrap(),
+        "motd\n"
+    );
+}
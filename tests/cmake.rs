@@ -0,0 +1,141 @@
+mod common;
+
+use common::{Sandbox, assert_success, repo_file};
+use std::{fs, process::Output};
+
+// cmake and ninja stand-ins recording how the setup hooks call them
+const FAKE_CMAKE: &str = r#"#!/bin/bash
+printf '%s\n' "$PWD" > "$LOG/cmake.cwd"
+printf '%s\n' "$CMAKE_PREFIX_PATH" > "$LOG/cmake.prefix"
+printf '%s\n' "$@" > "$LOG/cmake.args"
+"#;
+
+const FAKE_NINJA: &str = r#"#!/bin/bash
+if [ "$1" = -t ]; then
+    [ -n "${HAS_TEST:-}" ]
+    exit
+fi
+echo "$*" >> "$LOG/ninja.calls"
+"#;
+
+fn setup(sandbox: &Sandbox) {
+    sandbox.tool("cmake", FAKE_CMAKE, "src/pkgs/build/cmake/setup-hook.sh");
+    sandbox.tool("ninja", FAKE_NINJA, "src/pkgs/build/ninja/setup-hook.sh");
+    sandbox.write("src/sub/CMakeLists.txt", "project(hello C)\n");
+    sandbox.write("lib/include/lib.h", "");
+    fs::create_dir_all(sandbox.path("log")).unwrap();
+}
+
+fn build(sandbox: &Sandbox, env: &[(&str, &str)]) -> Output {
+    let src = sandbox.path("src");
+    let log = sandbox.path("log");
+    let tools = format!(
+        "{} {}",
+        sandbox.path("cmake").display(),
+        sandbox.path("ninja").display()
+    );
+    let lib = sandbox.path("lib");
+    let out = sandbox.path("out");
+    let dev = sandbox.path("dev");
+    let mut vars = vec![
+        ("SRC", src.to_str().unwrap()),
+        ("UNPACK", "1"),
+        ("CONFIGURE", "1"),
+        ("BUILD", "1"),
+        ("CHECK", "1"),
+        ("INSTALL", "1"),
+        ("outputs", "out dev"),
+        ("dev", dev.to_str().unwrap()),
+        ("LOG", log.to_str().unwrap()),
+        ("DEPS_BUILD_HOST", &tools),
+        ("DEPS_HOST_TARGET", lib.to_str().unwrap()),
+        ("OXIDE_BUILD_CORES", "4"),
+        ("CMAKE_DIR", "sub"),
+        ("CMAKE_FLAGS", "-DFOO=ON"),
+    ];
+    vars.extend_from_slice(env);
+    let _ = fs::remove_dir_all(&out);
+    let _ = fs::remove_dir_all(&dev);
+    sandbox.build(
+        &repo_file("src/pkgs/stdenv/generic/scripts/default-builder.sh"),
+        "out",
+        &vars,
+    )
+}
+
+fn log(sandbox: &Sandbox, file: &str) -> String {
+    fs::read_to_string(sandbox.path(&format!("log/{file}"))).unwrap_or_default()
+}
+
+#[test]
+fn installs_the_setup_hooks() {
+    let sandbox = Sandbox::new("cmake-hooks");
+    setup(&sandbox);
+    let hook = fs::read_to_string(sandbox.path("cmake/nix-support/setup-hook")).unwrap();
+    assert!(hook.contains("cmake_configure_phase()"));
+    let hook = fs::read_to_string(sandbox.path("ninja/nix-support/setup-hook")).unwrap();
+    assert!(hook.contains("ninja_build_phase()"));
+}
+
+#[test]
+fn configures_out_of_tree_with_ninja() {
+    let sandbox = Sandbox::new("cmake-configure");
+    setup(&sandbox);
+    assert_success(&build(&sandbox, &[]));
+
+    let out = sandbox.path("out");
+    let dev = sandbox.path("dev");
+    let args: Vec<String> = log(&sandbox, "cmake.args")
+        .lines()
+        .map(str::to_string)
+        .collect();
+    assert!(args[0].ends_with("/sub"));
+    assert!(log(&sandbox, "cmake.cwd").trim_end().ends_with("/build"));
+    assert_eq!(args[1..3], ["-G", "Ninja"]);
+    for flag in [
+        "-DCMAKE_BUILD_TYPE=Release".to_string(),
+        format!("-DCMAKE_INSTALL_PREFIX={}", out.display()),
+        format!("-DCMAKE_INSTALL_BINDIR={}/bin", out.display()),
+        format!("-DCMAKE_INSTALL_INCLUDEDIR={}/include", dev.display()),
+        format!("-DCMAKE_INSTALL_LIBDIR={}/lib", out.display()),
+    ] {
+        assert!(args.contains(&flag), "missing {flag} in {args:?}");
+    }
+    assert_eq!(args.last().unwrap(), "-DFOO=ON");
+    assert!(log(&sandbox, "cmake.prefix").contains(sandbox.path("lib").to_str().unwrap()));
+
+    // no test target, ninja is only queried
+    assert_eq!(log(&sandbox, "ninja.calls"), "-j4\n-j4 install\n");
+}
+
+#[test]
+fn runs_the_test_target_and_honours_parallel_settings() {
+    let sandbox = Sandbox::new("cmake-parallel");
+    setup(&sandbox);
+    assert_success(&build(
+        &sandbox,
+        &[
+            ("HAS_TEST", "1"),
+            ("ENABLE_PARALLEL_BUILDING", ""),
+            ("CMAKE_BUILD_TYPE", "Debug"),
+        ],
+    ));
+    assert_eq!(log(&sandbox, "ninja.calls"), "-j1\n-j4 test\n-j4 install\n");
+    assert!(log(&sandbox, "cmake.args").contains("-DCMAKE_BUILD_TYPE=Debug\n"));
+}
+
+#[test]
+fn phases_can_opt_out() {
+    let sandbox = Sandbox::new("cmake-opt-out");
+    setup(&sandbox);
+    assert_success(&build(
+        &sandbox,
+        &[
+            ("DONT_USE_CMAKE_CONFIGURE", "1"),
+            ("DONT_USE_NINJA_INSTALL", "1"),
+            ("INSTALL_PHASE", "echo custom >> $LOG/ninja.calls"),
+        ],
+    ));
+    assert_eq!(log(&sandbox, "cmake.args"), "");
+    assert_eq!(log(&sandbox, "ninja.calls"), "-j4\ncustom\n");
+}
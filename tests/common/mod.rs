@@ -0,0 +1,348 @@
+// Runs builder scripts through the real `setup.sh` outside of oxide,
+// with the host tools in /usr/bin standing in for the bootstrap tools.
+#![allow(dead_code)]
+
+use oxide_core::prelude::*;
+use std::{
+    collections::HashMap,
+    fs,
+    io::{BufRead, BufReader, Write},
+    net::TcpListener,
+    path::{Path, PathBuf},
+    process::{Command, Output},
+    thread,
+};
+
+pub const ROOT: &str = env!("CARGO_MANIFEST_DIR");
+
+pub fn repo_file(path: &str) -> PathBuf {
+    Path::new(ROOT).join(path)
+}
+
+// A derivation built by hand, without a recipe
+pub struct Plain(pub Drv);
+
+impl IntoDrv for Plain {
+    fn into_drv(self) -> Drv {
+        self.0
+    }
+}
+
+pub struct Sandbox {
+    pub dir: PathBuf,
+}
+
+impl Sandbox {
+    pub fn new(name: &str) -> Self {
+        let dir = Path::new(env!("CARGO_TARGET_TMPDIR")).join(name);
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        Self { dir }
+    }
+
+    pub fn path(&self, path: &str) -> PathBuf {
+        self.dir.join(path)
+    }
+
+    pub fn write(&self, path: &str, content: &str) -> PathBuf {
+        let path = self.path(path);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(&path, content).unwrap();
+        path
+    }
+
+    pub fn sh(&self, script: &str) -> Output {
+        let output = Command::new("bash")
+            .arg("-euc")
+            .arg(script)
+            .current_dir(&self.dir)
+            .output()
+            .unwrap();
+        assert_success(&output);
+        output
+    }
+
+    // The stdout of `script`, which must succeed
+    pub fn run(&self, script: &str) -> String {
+        String::from_utf8(self.sh(script).stdout).unwrap()
+    }
+
+    // Same layout as the stdenv derivation produced by `scripts/builder.sh`
+    fn stdenv(&self) -> PathBuf {
+        let stdenv = self.path("stdenv");
+        if !stdenv.exists() {
+            fs::create_dir_all(&stdenv).unwrap();
+            let setup =
+                fs::read_to_string(repo_file("src/pkgs/stdenv/generic/scripts/setup.sh")).unwrap();
+            fs::write(
+                stdenv.join("setup"),
+                format!(
+                    "export SHELL=/bin/bash\ninitial_path=\"/usr\"\ndefault_build_host=\"\"\ndefault_host_target=\"\"\n{setup}"
+                ),
+            )
+            .unwrap();
+        }
+        stdenv
+    }
+
+    // Runs `builder` like a `StdenvBuilder` derivation building `out`
+    pub fn build(&self, builder: &Path, out: &str, env: &[(&str, &str)]) -> Output {
+        let build_dir = self.path(&format!("build-{out}"));
+        let tmp_dir = self.path(&format!("tmp-{out}"));
+        fs::create_dir_all(&build_dir).unwrap();
+        fs::create_dir_all(&tmp_dir).unwrap();
+        Command::new("bash")
+            .arg("-e")
+            .arg(repo_file(
+                "src/pkgs/stdenv/generic/scripts/source-stdenv.sh",
+            ))
+            .arg(builder)
+            .env_clear()
+            .env("stdenv", self.stdenv())
+            .env("out", self.path(out))
+            .env("outputs", "out")
+            .env("name", out)
+            .env("TMPDIR", &tmp_dir)
+            .env("HOME", "/homeless-shelter")
+            .envs(env.iter().copied())
+            .current_dir(&build_dir)
+            .output()
+            .unwrap()
+    }
+
+    // A package providing `script` as bin/`out` and installing `hook` as
+    // its setup hook, stands in for build tools that are not on the host
+    pub fn tool(&self, out: &str, script: &str, hook: &str) -> PathBuf {
+        let script = self.write(&format!("{out}.sh"), script);
+        let hook = repo_file(hook);
+        let output = self.build(
+            &repo_file("src/pkgs/stdenv/generic/scripts/default-builder.sh"),
+            out,
+            &[
+                ("INSTALL", "1"),
+                (
+                    "INSTALL_PHASE",
+                    &format!("install -D -m755 {} $out/bin/{out}", script.display()),
+                ),
+                ("FIX", "1"),
+                ("SETUP_HOOK", hook.to_str().unwrap()),
+            ],
+        );
+        assert_success(&output);
+        self.path(out)
+    }
+}
+
+pub fn assert_success(output: &Output) {
+    assert!(
+        output.status.success(),
+        "command failed\nstdout:\n{}\nstderr:\n{}",
+        String::from_utf8_lossy(&output.stdout),
+        String::from_utf8_lossy(&output.stderr)
+    );
+}
+
+// Every path of the tree with its type, permissions, mtime and content
+pub fn fingerprint(dir: &Path) -> Vec<String> {
+    let output = Command::new("find")
+        .arg(".")
+        .arg("-printf")
+        .arg("%p %y %m %T@ %l\n")
+        .current_dir(dir)
+        .output()
+        .unwrap();
+    assert_success(&output);
+    let mut lines: Vec<String> = String::from_utf8(output.stdout)
+        .unwrap()
+        .lines()
+        .map(|line| {
+            let path = line.split(' ').next().unwrap();
+            let file = dir.join(path);
+            if file.is_file() && !file.is_symlink() {
+                format!("{line} {:?}", fs::read(file).unwrap())
+            } else {
+                line.to_string()
+            }
+        })
+        .collect();
+    lines.sort();
+    lines
+}
+
+// Compares `actual` with the checked in tests/snapshots/<name>, rewritten
+// instead when UPDATE_SNAPSHOTS is set
+pub fn assert_snapshot(name: &str, actual: &str) {
+    let path = repo_file("tests/snapshots").join(name);
+    if std::env::var_os("UPDATE_SNAPSHOTS").is_some() {
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(&path, actual).unwrap();
+        return;
+    }
+    let expected = fs::read_to_string(&path).unwrap_or_default();
+    if expected != actual {
+        let expected: Vec<&str> = expected.lines().collect();
+        let actual: Vec<&str> = actual.lines().collect();
+        let removed: Vec<String> = expected
+            .iter()
+            .filter(|line| !actual.contains(line))
+            .map(|line| format!("-{line}"))
+            .collect();
+        let added: Vec<String> = actual
+            .iter()
+            .filter(|line| !expected.contains(line))
+            .map(|line| format!("+{line}"))
+            .collect();
+        panic!(
+            "snapshot {} differs, rerun with UPDATE_SNAPSHOTS=1 if intended\n{}\n{}",
+            path.display(),
+            removed.join("\n"),
+            added.join("\n")
+        );
+    }
+}
+
+fn render_expr(expr: &Expr) -> String {
+    match expr {
+        Expr::Str(s) => s.to_string(),
+        Expr::List(exprs) => {
+            let exprs: Vec<String> = exprs.iter().map(render_expr).collect();
+            format!("[{}]", exprs.join(", "))
+        }
+        Expr::Drv(drv, output) => {
+            let name = LazyDrv::clone(drv).into_drv().name;
+            match output {
+                Some(output) => format!("<{name}^{output}>"),
+                None => format!("<{name}>"),
+            }
+        }
+        Expr::Suff(expr, suffix) => format!("{}{suffix}", render_expr(expr)),
+    }
+}
+
+// Every field of `drv` and its inputs by key, the derivations it refers to
+// by their name rather than their whole text
+fn drv_entries(drv: &Drv) -> Vec<(String, String)> {
+    let or_none = |value: Option<String>| value.unwrap_or_else(|| "-".to_string());
+    let mut entries = vec![
+        ("name".to_string(), drv.name.clone()),
+        (
+            "system".to_string(),
+            or_none(drv.system.map(|system| system.to_string())),
+        ),
+        (
+            "builder".to_string(),
+            or_none(drv.builder.as_ref().map(render_expr)),
+        ),
+        (
+            "args".to_string(),
+            drv.args
+                .iter()
+                .map(render_expr)
+                .collect::<Vec<_>>()
+                .join("\n"),
+        ),
+        (
+            "outputs".to_string(),
+            or_none((!drv.outputs.is_empty()).then(|| drv.outputs.join(" "))),
+        ),
+        (
+            "hash".to_string(),
+            or_none(drv.hash.as_ref().map(|hash| hash.to_string())),
+        ),
+    ];
+    for (key, expr) in &drv.inputs {
+        entries.push((format!("inputs.{key}"), render_expr(expr)));
+    }
+    entries
+}
+
+fn render_entry(marker: &str, key: &str, value: &str) -> String {
+    if !value.contains('\n') {
+        return format!("{marker}{key} = {value}\n");
+    }
+    let mut text = format!("{marker}{key} =\n");
+    for line in value.lines() {
+        match line.is_empty() {
+            true => text += &format!("{}\n", marker.trim_end()),
+            false => text += &format!("{marker}    {line}\n"),
+        }
+    }
+    text
+}
+
+// A stable and readable text of `drv`, one `key = value` per field and
+// input, the lines of multiline values indented below their key
+pub fn render_drv(drv: &Drv) -> String {
+    drv_entries(drv)
+        .iter()
+        .map(|(key, value)| render_entry("", key, value))
+        .collect()
+}
+
+// The entries of `drv` that are not the same in `base`, removed ones
+// prefixed by '-' and added ones by '+'
+pub fn render_drv_changes(base: &Drv, drv: &Drv) -> String {
+    let base = drv_entries(base);
+    let entries = drv_entries(drv);
+    let mut keys: Vec<&String> = base.iter().chain(&entries).map(|(key, _)| key).collect();
+    keys.sort();
+    keys.dedup();
+    let value = |entries: &[(String, String)], key: &str| {
+        entries
+            .iter()
+            .find(|(k, _)| k == key)
+            .map(|(_, value)| value.clone())
+    };
+    let mut text = String::new();
+    for key in keys {
+        let (old, new) = (value(&base, key), value(&entries, key));
+        if old == new {
+            continue;
+        }
+        if let Some(old) = old {
+            text += &render_entry("- ", key, &old);
+        }
+        if let Some(new) = new {
+            text += &render_entry("+ ", key, &new);
+        }
+    }
+    text
+}
+
+// A local stand-in for download servers, answers GET requests for `files`
+// and 404 for everything else. Returns the base url, e.g. http://127.0.0.1:1234
+pub fn serve(files: HashMap<String, Vec<u8>>) -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    thread::spawn(move || {
+        for stream in listener.incoming() {
+            let Ok(mut stream) = stream else { continue };
+            let mut reader = BufReader::new(&stream);
+            let mut request = String::new();
+            if reader.read_line(&mut request).is_err() {
+                continue;
+            }
+            // skip the headers
+            let mut line = String::new();
+            while reader.read_line(&mut line).is_ok_and(|n| n > 2) {
+                line.clear();
+            }
+            let path = request.split(' ').nth(1).unwrap_or("");
+            let response = match files.get(path) {
+                Some(body) => {
+                    let mut response = format!(
+                        "HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+                        body.len()
+                    )
+                    .into_bytes();
+                    response.extend_from_slice(body);
+                    response
+                }
+                None => b"HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\nConnection: close\r\n\r\n"
+                    .to_vec(),
+            };
+            let _ = stream.write_all(&response);
+        }
+    });
+    format!("http://{addr}")
+}
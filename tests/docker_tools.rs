@@ -0,0 +1,195 @@
+mod common;
+
+use common::{Sandbox, assert_success, repo_file};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    process::Output,
+};
+
+// app -> lib-b -> lib-a, app -> lib-a, nothing refers to unrelated
+fn store(sandbox: &Sandbox) -> String {
+    let lib_a = sandbox.write("lib-a/lib/liba.so", "a\n");
+    let lib_a = lib_a
+        .parent()
+        .unwrap()
+        .parent()
+        .unwrap()
+        .display()
+        .to_string();
+    sandbox.write("lib-b/lib/libb.so", &format!("needs {lib_a}/lib/liba.so\0"));
+    let lib_b = sandbox.path("lib-b").display().to_string();
+    sandbox.write(
+        "app/bin/app",
+        &format!("#!/bin/sh\nexec {lib_b}/lib/libb.so {lib_a}\n"),
+    );
+    sandbox.write("unrelated/share/doc", "");
+    sandbox.path("app").display().to_string()
+}
+
+fn build(sandbox: &Sandbox, out: &str, contents: &str, max_layers: &str) -> Output {
+    sandbox.build(
+        &repo_file("src/pkgs/build/docker_tools/build-layered-image.sh"),
+        out,
+        &[
+            ("CONTENTS", contents),
+            ("MAX_LAYERS", max_layers),
+            ("IMAGE_NAME", "app"),
+            ("IMAGE_TAG", "1.0"),
+            ("ARCHITECTURE", "amd64"),
+            ("ENTRYPOINT_0", "/bin/app"),
+            ("ENTRYPOINT_1", "--listen on"),
+            ("ENV_NAME_0", "GREETING"),
+            ("ENV_VALUE_0", "say \"hi\""),
+            ("IMAGE_USER", "1000:1000"),
+            ("EXPOSED_PORTS", "80/tcp 443/tcp"),
+        ],
+    )
+}
+
+fn extract(sandbox: &Sandbox, image: &str) -> PathBuf {
+    let dir = sandbox.path(&format!("{image}-extracted"));
+    fs::create_dir_all(&dir).unwrap();
+    sandbox.sh(&format!("tar -xf {image} -C {}", dir.display()));
+    dir
+}
+
+fn blob(image: &Path, digest: &Value) -> Vec<u8> {
+    let digest = digest.as_str().unwrap().strip_prefix("sha256:").unwrap();
+    let blob = fs::read(image.join("blobs/sha256").join(digest)).unwrap();
+    assert_eq!(format!("{:x}", Sha256::digest(&blob)), digest);
+    blob
+}
+
+fn json(bytes: &[u8]) -> Value {
+    serde_json::from_slice(bytes).unwrap()
+}
+
+// The store paths of each layer, or the files of the root layer
+fn layers(sandbox: &Sandbox, image: &Path, manifest: &Value) -> Vec<Vec<String>> {
+    let store = format!(
+        "{}/",
+        sandbox.dir.display().to_string().trim_start_matches('/')
+    );
+    manifest["layers"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|layer| {
+            assert_eq!(layer["mediaType"], "application/vnd.oci.image.layer.v1.tar");
+            fs::write(sandbox.path("layer.tar"), blob(image, &layer["digest"])).unwrap();
+            // fixed timestamps and owners
+            let listing = sandbox.sh("tar --full-time -tvf layer.tar");
+            for line in String::from_utf8(listing.stdout).unwrap().lines() {
+                assert!(line.contains(" 0/0 "), "{line}");
+                assert!(line.contains(" 1970-01-01 00:00:01 "), "{line}");
+            }
+            let names = sandbox.run("tar -tf layer.tar");
+            let names: Vec<&str> = names.lines().collect();
+            if !names.iter().any(|name| name.starts_with(&store)) {
+                return names.iter().map(|name| name.to_string()).collect();
+            }
+            let mut paths: Vec<String> = names
+                .iter()
+                .filter_map(|name| name.strip_prefix(&store))
+                .map(|rel| rel.split('/').next().unwrap().to_string())
+                .filter(|entry| !entry.is_empty())
+                .collect();
+            paths.dedup();
+            paths
+        })
+        .collect()
+}
+
+#[test]
+fn writes_an_oci_archive_of_the_closure() {
+    let sandbox = Sandbox::new("docker-tools-layout");
+    let app = store(&sandbox);
+    assert_success(&build(&sandbox, "image.tar", &app, "100"));
+    let image = extract(&sandbox, "image.tar");
+
+    assert_eq!(
+        json(&fs::read(image.join("oci-layout")).unwrap()),
+        serde_json::json!({"imageLayoutVersion": "1.0.0"})
+    );
+    let index = json(&fs::read(image.join("index.json")).unwrap());
+    let descriptor = &index["manifests"][0];
+    assert_eq!(
+        descriptor["annotations"]["io.containerd.image.name"],
+        "app:1.0"
+    );
+    assert_eq!(
+        descriptor["annotations"]["org.opencontainers.image.ref.name"],
+        "1.0"
+    );
+    let manifest = json(&blob(&image, &descriptor["digest"]));
+    let config = json(&blob(&image, &manifest["config"]["digest"]));
+
+    assert_eq!(config["created"], "1970-01-01T00:00:01Z");
+    assert_eq!(config["architecture"], "amd64");
+    assert_eq!(
+        config["config"],
+        serde_json::json!({
+            "Entrypoint": ["/bin/app", "--listen on"],
+            "Env": ["GREETING=say \"hi\""],
+            "User": "1000:1000",
+            "ExposedPorts": {"80/tcp": {}, "443/tcp": {}},
+        })
+    );
+    let layer_digests: Vec<&Value> = manifest["layers"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|layer| &layer["digest"])
+        .collect();
+    let diff_ids: Vec<&Value> = config["rootfs"]["diff_ids"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .collect();
+    assert_eq!(diff_ids, layer_digests);
+
+    // the most popular first, one layer each, then the root links
+    let layers = layers(&sandbox, &image, &manifest);
+    assert_eq!(
+        layers,
+        vec![
+            vec!["lib-a".to_string()],
+            vec!["lib-b".to_string()],
+            vec!["app".to_string()],
+            vec!["bin/".to_string(), "bin/app".to_string()],
+        ]
+    );
+}
+
+#[test]
+fn spreads_the_less_popular_paths_up_to_the_layer_limit() {
+    let sandbox = Sandbox::new("docker-tools-max-layers");
+    let app = store(&sandbox);
+    assert_success(&build(&sandbox, "image.tar", &app, "3"));
+    let image = extract(&sandbox, "image.tar");
+    let index = json(&fs::read(image.join("index.json")).unwrap());
+    let manifest = json(&blob(&image, &index["manifests"][0]["digest"]));
+    let layers = layers(&sandbox, &image, &manifest);
+    assert_eq!(layers.len(), 3);
+    assert_eq!(layers[0], vec!["lib-a".to_string()]);
+    assert_eq!(layers[1], vec!["app".to_string(), "lib-b".to_string()]);
+
+    let output = build(&sandbox, "too-few.tar", &app, "1");
+    assert!(!output.status.success());
+}
+
+#[test]
+fn the_same_closure_gives_the_same_image() {
+    let sandbox = Sandbox::new("docker-tools-reproducible");
+    let app = store(&sandbox);
+    assert_success(&build(&sandbox, "first.tar", &app, "100"));
+    sandbox.sh("touch -d '2001-01-01' app/bin/app lib-a/lib/liba.so");
+    assert_success(&build(&sandbox, "second.tar", &app, "100"));
+    assert_eq!(
+        fs::read(sandbox.path("first.tar")).unwrap(),
+        fs::read(sandbox.path("second.tar")).unwrap()
+    );
+}
@@ -0,0 +1,89 @@
+mod common;
+
+use common::{Plain, Sandbox, assert_snapshot};
+use oxide_core::prelude::*;
+use oxide_pkgs::{
+    lib::systems::SYSTEMS,
+    top_level::eval_all::{drv_hash, eval_all, snapshot, summary},
+};
+
+#[test]
+fn every_package_evaluates_for_every_system() {
+    let evaluations = eval_all(&SYSTEMS);
+    let summary = summary(&evaluations);
+    assert!(
+        evaluations
+            .iter()
+            .all(|evaluation| evaluation.result.is_ok()),
+        "{summary}"
+    );
+    assert_snapshot("eval-all.txt", &snapshot(&evaluations));
+}
+
+#[test]
+fn reports_the_packages_that_panic() {
+    let evaluations = eval_all(&[System::aarch64_linux]);
+    assert!(!evaluations.is_empty());
+    let summary = summary(&evaluations);
+    assert!(summary.starts_with("ATTR"), "{summary}");
+    assert!(
+        summary.contains(&format!(
+            "{} packages, 1 systems, {0} failed",
+            evaluations.len()
+        )),
+        "{summary}"
+    );
+    assert!(
+        summary.contains("aarch64_linux zlib: not implemented"),
+        "{summary}"
+    );
+    assert!(snapshot(&evaluations).contains("aarch64_linux zlib failed\n"));
+}
+
+fn src(builder: &str) -> LazyDrv {
+    LazyDrv::new(Plain(
+        DrvBuilder::new()
+            .name("src.tar.gz")
+            .builder(builder.to_string())
+            .fixed_hash(hash!("sha256:src"))
+            .build(),
+    ))
+}
+
+fn pkg(src: LazyDrv, flags: &str) -> Drv {
+    DrvBuilder::new()
+        .name("pkg-1.0")
+        .builder("/bin/sh")
+        .input("SRC", Expr::Drv(src, None))
+        .input("FLAGS", flags.to_string())
+        .build()
+}
+
+#[test]
+fn hashes_what_is_built() {
+    let hash = drv_hash(&pkg(src("/bin/curl"), ""));
+    assert_eq!(hash.len(), 64);
+    assert_eq!(hash, drv_hash(&pkg(src("/bin/curl"), "")));
+    assert_ne!(hash, drv_hash(&pkg(src("/bin/curl"), "--enable-foo")));
+    // fetched the same whatever the fetcher
+    assert_eq!(hash, drv_hash(&pkg(src("/bin/wget"), "")));
+}
+
+#[test]
+fn hashes_the_contents_of_local_files() {
+    let sandbox = Sandbox::new("eval-all-local-files");
+    // the form `local_file!` gives paths
+    let local_file = |name: &str, contents: &str| {
+        sandbox.write(name, contents);
+        format!("{}/mod.rs/../{name}", sandbox.dir.display())
+    };
+    let hash = drv_hash(&pkg(src("/bin/curl"), &local_file("a.sh", "make")));
+    assert_eq!(
+        hash,
+        drv_hash(&pkg(src("/bin/curl"), &local_file("b.sh", "make")))
+    );
+    assert_ne!(
+        hash,
+        drv_hash(&pkg(src("/bin/curl"), &local_file("a.sh", "make install")))
+    );
+}
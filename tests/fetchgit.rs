@@ -0,0 +1,143 @@
+mod common;
+
+use common::{Sandbox, assert_success, fingerprint, repo_file};
+use std::process::Output;
+
+const GIT: &str = "git -c user.name=test -c user.email=test@example.com -c init.defaultBranch=main";
+
+fn fetchgit(sandbox: &Sandbox, out: &str, env: &[(&str, &str)]) -> Output {
+    let url = format!("file://{}", sandbox.path("repo").display());
+    let normalise = repo_file("src/pkgs/build/fetchers/normalise.sh");
+    let mut all_env = vec![
+        ("url", url.as_str()),
+        ("NORMALISE", normalise.to_str().unwrap()),
+        // submodules are fetched over file:// too
+        ("GIT_CONFIG_COUNT", "1"),
+        ("GIT_CONFIG_KEY_0", "protocol.file.allow"),
+        ("GIT_CONFIG_VALUE_0", "always"),
+    ];
+    all_env.extend_from_slice(env);
+    sandbox.build(
+        &repo_file("src/pkgs/build/fetchers/fetchgit/builder.sh"),
+        out,
+        &all_env,
+    )
+}
+
+// Returns the revision of the first commit, a second commit is made on top of it
+fn make_repo(sandbox: &Sandbox) -> String {
+    sandbox.sh(&format!(
+        r#"{GIT} init -q lib
+echo lib > lib/lib.txt
+{GIT} -C lib add . && {GIT} -C lib commit -q -m lib
+
+{GIT} init -q repo
+mkdir -p repo/src repo/docs
+echo 'fn main() {{}}' > repo/src/main.rs
+echo '#!/bin/sh' > repo/configure
+chmod 700 repo/configure
+echo docs > repo/docs/index.md
+{GIT} -C repo add . && {GIT} -C repo commit -q -m first
+{GIT} -C repo tag v1.0
+{GIT} -C repo -c protocol.file.allow=always submodule add -q "file://$PWD/lib" vendor/lib
+{GIT} -C repo commit -q -m second"#
+    ));
+    let output = sandbox.sh(&format!("{GIT} -C repo rev-parse v1.0"));
+    String::from_utf8(output.stdout).unwrap().trim().to_string()
+}
+
+fn head(sandbox: &Sandbox) -> String {
+    let output = sandbox.sh(&format!("{GIT} -C repo rev-parse HEAD"));
+    String::from_utf8(output.stdout).unwrap().trim().to_string()
+}
+
+#[test]
+fn checks_out_a_revision_without_dot_git() {
+    let sandbox = Sandbox::new("fetchgit-rev");
+    let rev = make_repo(&sandbox);
+
+    assert_success(&fetchgit(&sandbox, "out", &[("REV", &rev)]));
+    let out = sandbox.path("out");
+    assert!(!out.join(".git").exists());
+    assert!(!out.join("vendor").exists());
+    let tree = fingerprint(&out);
+    assert!(
+        tree.iter()
+            .any(|line| line.starts_with("./configure f 755 315532800"))
+    );
+    assert!(
+        tree.iter()
+            .any(|line| line.starts_with("./src/main.rs f 644 315532800"))
+    );
+}
+
+#[test]
+fn tag_and_rev_give_the_same_tree() {
+    let sandbox = Sandbox::new("fetchgit-tag");
+    let rev = make_repo(&sandbox);
+
+    assert_success(&fetchgit(&sandbox, "out-rev", &[("REV", &rev)]));
+    assert_success(&fetchgit(&sandbox, "out-tag", &[("TAG", "v1.0")]));
+    // forges pass tags as revisions
+    assert_success(&fetchgit(&sandbox, "out-tag-rev", &[("REV", "v1.0")]));
+    assert_success(&fetchgit(
+        &sandbox,
+        "out-deep",
+        &[("REV", &rev), ("DEEP_CLONE", "1")],
+    ));
+    let expected = fingerprint(&sandbox.path("out-rev"));
+    assert_eq!(expected, fingerprint(&sandbox.path("out-tag")));
+    assert_eq!(expected, fingerprint(&sandbox.path("out-tag-rev")));
+    assert_eq!(expected, fingerprint(&sandbox.path("out-deep")));
+}
+
+#[test]
+fn fetches_submodules() {
+    let sandbox = Sandbox::new("fetchgit-submodules");
+    make_repo(&sandbox);
+    let rev = head(&sandbox);
+
+    assert_success(&fetchgit(
+        &sandbox,
+        "out",
+        &[("REV", &rev), ("FETCH_SUBMODULES", "1")],
+    ));
+    let out = sandbox.path("out");
+    assert!(out.join("vendor/lib/lib.txt").is_file());
+    assert!(!out.join("vendor/lib/.git").exists());
+}
+
+#[test]
+fn sparse_checkout_only_keeps_the_requested_paths() {
+    let sandbox = Sandbox::new("fetchgit-sparse");
+    let rev = make_repo(&sandbox);
+
+    assert_success(&fetchgit(
+        &sandbox,
+        "out",
+        &[("REV", &rev), ("SPARSE_CHECKOUT", "/src/")],
+    ));
+    let out = sandbox.path("out");
+    assert!(out.join("src/main.rs").is_file());
+    assert!(!out.join("docs").exists());
+}
+
+#[test]
+fn leave_dot_git_is_reproducible() {
+    let sandbox = Sandbox::new("fetchgit-dot-git");
+    let rev = make_repo(&sandbox);
+
+    for out in ["out-1", "out-2"] {
+        assert_success(&fetchgit(
+            &sandbox,
+            out,
+            &[("REV", &rev), ("LEAVE_DOT_GIT", "1")],
+        ));
+    }
+    let output = sandbox.sh(&format!("{GIT} -C out-1 rev-parse HEAD"));
+    assert_eq!(String::from_utf8(output.stdout).unwrap().trim(), rev);
+    assert_eq!(
+        fingerprint(&sandbox.path("out-1")),
+        fingerprint(&sandbox.path("out-2"))
+    );
+}
@@ -0,0 +1,240 @@
+mod common;
+
+use common::{Sandbox, assert_success, repo_file};
+use oxide_core::prelude::*;
+use oxide_pkgs::{
+    build::fetchpatch::FetchPatchArgs, lib::fake_hash, top_level::all_packages::all_pkgs,
+};
+use std::{
+    fs,
+    panic::{self, AssertUnwindSafe},
+};
+
+fn fetchpatch(
+    sandbox: &Sandbox,
+    patch: &str,
+    out: &str,
+    env: &[(&str, &str)],
+) -> std::process::Output {
+    let url = format!("file://{}", sandbox.path(patch).display());
+    let normalise = repo_file("src/pkgs/build/fetchers/fetchpatch/normalise.awk");
+    let post_fetch = repo_file("src/pkgs/build/fetchers/fetchpatch/post-fetch.sh");
+    let mut full_env = vec![
+        ("url", url.as_str()),
+        ("DOWNLOAD_TO_TEMP", "1"),
+        ("NORMALISE_PATCH", normalise.to_str().unwrap()),
+        ("FETCHPATCH", post_fetch.to_str().unwrap()),
+        ("STRIP_LEN", "0"),
+        ("INCLUDES", ""),
+        ("EXCLUDES", ""),
+        ("POST_FETCH", "source \"$FETCHPATCH\""),
+    ];
+    full_env.retain(|(key, _)| !env.iter().any(|(k, _)| k == key));
+    full_env.extend_from_slice(env);
+    sandbox.build(
+        &repo_file("src/pkgs/build/fetchers/fetchurl/builder.sh"),
+        out,
+        &full_env,
+    )
+}
+
+// Same change as a git format-patch mail and as a plain `diff -ru`
+fn make_patches(sandbox: &Sandbox) {
+    sandbox.sh(
+        r#"export GIT_CONFIG_NOSYSTEM=1 HOME="$PWD" GIT_AUTHOR_NAME=test GIT_AUTHOR_EMAIL=test@example.com
+export GIT_COMMITTER_NAME=test GIT_COMMITTER_EMAIL=test@example.com
+git init -q repo
+cd repo
+mkdir -p lib/foo docs
+printf 'int main(void)\n{\n    return 0;\n}\n' > lib/foo/main.c
+printf 'old docs\n' > docs/README
+printf 'no newline' > lib/foo/data
+git add -A
+git commit -qm init
+cp -r . ../a
+rm -rf ../a/.git
+printf 'int main(void)\n{\n    return 1;\n}\n' > lib/foo/main.c
+printf 'new docs\n' > docs/README
+printf 'no newline either' > lib/foo/data
+git add -A
+git commit -qm 'Fix everything' -m '--- a/fake
++++ b/fake
+this is only the commit message'
+git format-patch -1 --stdout > ../git.patch
+cp -r . ../b
+rm -rf ../b/.git
+cd ..
+diff -ru a b > diff.patch || true"#,
+    );
+}
+
+fn read(sandbox: &Sandbox, path: &str) -> String {
+    fs::read_to_string(sandbox.path(path)).unwrap()
+}
+
+#[test]
+fn git_and_plain_diffs_normalise_identically() {
+    let sandbox = Sandbox::new("fetchpatch-identical");
+    make_patches(&sandbox);
+    assert_success(&fetchpatch(&sandbox, "git.patch", "out-git", &[]));
+    assert_success(&fetchpatch(&sandbox, "diff.patch", "out-diff", &[]));
+
+    let patch = read(&sandbox, "out-git");
+    assert_eq!(patch, read(&sandbox, "out-diff"));
+    assert!(!patch.contains("Fix everything"));
+    assert!(!patch.contains("index "));
+    assert!(!patch.contains("fake"));
+    assert!(patch.contains("--- a/lib/foo/main.c\n+++ b/lib/foo/main.c\n"));
+    assert!(patch.contains("\\ No newline at end of file\n"));
+    assert!(!patch.ends_with("-- \n"));
+
+    sandbox.sh("cp -r a applied && cd applied && patch -p1 < ../out-git && diff -r . ../b");
+}
+
+#[test]
+fn includes_and_excludes_select_files() {
+    let sandbox = Sandbox::new("fetchpatch-filter");
+    make_patches(&sandbox);
+    assert_success(&fetchpatch(
+        &sandbox,
+        "git.patch",
+        "out-include",
+        &[("INCLUDES", "lib/*")],
+    ));
+    assert_success(&fetchpatch(
+        &sandbox,
+        "git.patch",
+        "out-exclude",
+        &[("EXCLUDES", "docs/*\nlib/foo/data")],
+    ));
+
+    let include = read(&sandbox, "out-include");
+    assert!(include.contains("lib/foo/main.c"));
+    assert!(include.contains("lib/foo/data"));
+    assert!(!include.contains("docs/README"));
+
+    let exclude = read(&sandbox, "out-exclude");
+    assert!(exclude.contains("lib/foo/main.c"));
+    assert!(!exclude.contains("lib/foo/data"));
+    assert!(!exclude.contains("docs/README"));
+}
+
+#[test]
+fn strip_len_and_extra_prefix_rewrite_paths() {
+    let sandbox = Sandbox::new("fetchpatch-strip");
+    make_patches(&sandbox);
+    // what `FetchPatchArgs::relative("lib")` passes down
+    assert_success(&fetchpatch(
+        &sandbox,
+        "git.patch",
+        "out-relative",
+        &[
+            ("STRIP_LEN", "2"),
+            ("EXTRA_PREFIX", ""),
+            ("INCLUDES", "lib/*"),
+        ],
+    ));
+    assert_success(&fetchpatch(
+        &sandbox,
+        "git.patch",
+        "out-prefix",
+        &[("STRIP_LEN", "1"), ("EXTRA_PREFIX", "src/")],
+    ));
+
+    let relative = read(&sandbox, "out-relative");
+    assert!(relative.contains("--- a/foo/main.c\n+++ b/foo/main.c\n"));
+    assert!(!relative.contains("README"));
+    sandbox.sh("cp -r a/lib lib && cd lib && patch -p1 < ../out-relative && diff -r . ../b/lib");
+
+    let prefix = read(&sandbox, "out-prefix");
+    assert!(prefix.contains("--- a/src/lib/foo/main.c\n+++ b/src/lib/foo/main.c\n"));
+}
+
+#[test]
+fn refuses_patches_that_filter_to_nothing() {
+    let sandbox = Sandbox::new("fetchpatch-empty");
+    make_patches(&sandbox);
+    let output = fetchpatch(&sandbox, "git.patch", "out", &[("INCLUDES", "nothing/*")]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stdout).contains("is empty"));
+}
+
+fn input(drv: &Drv, key: &str) -> String {
+    match &drv.inputs[key] {
+        Expr::Str(value) => value.to_string(),
+        _ => panic!("{key} is not a string"),
+    }
+}
+
+#[test]
+fn relative_strips_its_own_components() {
+    let (_, pkgs) = all_pkgs();
+    let args = || FetchPatchArgs::new("https://example.org/fix.patch", fake_hash());
+    let drv = pkgs
+        .fetchpatch
+        .fetch_with(args().relative("/lib/foo/").include("*.c"))
+        .into_drv();
+    assert_eq!(input(&drv, "STRIP_LEN"), "3");
+    assert_eq!(input(&drv, "EXTRA_PREFIX"), "");
+    assert_eq!(input(&drv, "INCLUDES"), "*.c\nlib/foo/*");
+
+    let drv = pkgs
+        .fetchpatch
+        .fetch_with(args().relative("lib").extra_prefix("src/"))
+        .into_drv();
+    assert_eq!(input(&drv, "STRIP_LEN"), "2");
+    assert_eq!(input(&drv, "EXTRA_PREFIX"), "src/");
+
+    let both = panic::catch_unwind(AssertUnwindSafe(|| {
+        pkgs.fetchpatch
+            .fetch_with(args().relative("lib").strip_len(1))
+    }));
+    assert!(both.is_err());
+}
+
+#[test]
+fn warns_about_renames_and_binary_changes() {
+    let sandbox = Sandbox::new("fetchpatch-rename");
+    sandbox.sh(
+        r#"export GIT_CONFIG_NOSYSTEM=1 HOME="$PWD" GIT_AUTHOR_NAME=test GIT_AUTHOR_EMAIL=test@example.com
+export GIT_COMMITTER_NAME=test GIT_COMMITTER_EMAIL=test@example.com
+git init -q repo
+cd repo
+printf 'one\ntwo\nthree\nfour\nfive\n' > moved.txt
+printf 'same\n' > renamed.txt
+printf 'edit\n' > edited.txt
+printf '\0\1' > data.bin
+git add -A
+git commit -qm init
+git mv moved.txt moved-to.txt
+printf 'one\ntwo\nthree\nfour\nsix\n' > moved-to.txt
+git mv renamed.txt renamed-to.txt
+printf 'edited\n' > edited.txt
+printf '\0\2' > data.bin
+git add -A
+git commit -qm 'Move things'
+git format-patch -1 -M --binary --stdout > ../rename.patch"#,
+    );
+    let output = fetchpatch(&sandbox, "rename.patch", "out", &[]);
+    assert_success(&output);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.contains("warning: dropping the rename of a/renamed.txt b/renamed-to.txt"),
+        "{stderr}"
+    );
+    assert!(
+        stderr.contains("warning: dropping the rename of a/moved.txt b/moved-to.txt"),
+        "{stderr}"
+    );
+    assert!(
+        stderr.contains("warning: dropping the binary change of a/data.bin b/data.bin"),
+        "{stderr}"
+    );
+    assert!(!stderr.contains("edited.txt"), "{stderr}");
+
+    // the changes with a diff are kept
+    let patch = read(&sandbox, "out");
+    assert!(patch.contains("--- a/edited.txt\n+++ b/edited.txt\n"));
+    assert!(patch.contains("--- a/moved.txt\n+++ b/moved-to.txt\n"));
+    assert!(!patch.contains("renamed"));
+}
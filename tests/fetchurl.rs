@@ -0,0 +1,84 @@
+mod common;
+
+use base64::{Engine, engine::general_purpose::URL_SAFE_NO_PAD};
+use common::{Sandbox, assert_success, repo_file};
+use oxide_core::prelude::*;
+use oxide_pkgs::{
+    lib::fake_hash,
+    top_level::{all_packages::all_pkgs, registry::direct_drvs},
+};
+use sha2::{Digest, Sha512};
+use std::process::Output;
+
+const CONTENT: &str = "hello world\n";
+const FAKE_HASH: &str =
+    "sha512:AAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAA";
+
+fn real_hash() -> String {
+    format!(
+        "sha512:{}",
+        URL_SAFE_NO_PAD.encode(Sha512::digest(CONTENT.as_bytes()))
+    )
+}
+
+fn fetchurl(sandbox: &Sandbox, out: &str, hash: &str) -> (Output, String) {
+    sandbox.write("hello.txt", CONTENT);
+    let url = format!("file://{}", sandbox.path("hello.txt").display());
+    let output = sandbox.build(
+        &repo_file("src/pkgs/build/fetchers/fetchurl/builder.sh"),
+        out,
+        &[("url", &url), ("EXPECTED_HASH", hash)],
+    );
+    (output, url)
+}
+
+#[test]
+fn accepts_the_right_hash() {
+    let sandbox = Sandbox::new("fetchurl-right-hash");
+    let (output, _) = fetchurl(&sandbox, "hello.txt", &real_hash());
+    assert_success(&output);
+}
+
+#[test]
+fn reports_mismatches_in_hash_syntax() {
+    let sandbox = Sandbox::new("fetchurl-mismatch");
+    let wrong = "sha512:z4PhNX7vuL3xVChQ1m2AB9Yg5AULVxXcg_SpIdNs6c5H0NE8XYXysP-DGNKHfuwvY7kxvUdBeoGlODJ6-SfaPg";
+    let (output, url) = fetchurl(&sandbox, "hello.txt", wrong);
+    assert!(!output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("hash mismatch in fixed output derivation hello.txt"));
+    assert!(stdout.contains(&format!("url:       {url}")));
+    assert!(stdout.contains(&format!("specified: hash!(\"{wrong}\")")));
+    assert!(stdout.contains(&format!("got:       hash!(\"{}\")", real_hash())));
+}
+
+#[test]
+fn fake_hash_prints_the_real_one() {
+    let sandbox = Sandbox::new("fetchurl-fake-hash");
+    let (output, _) = fetchurl(&sandbox, "hello.txt", FAKE_HASH);
+    assert!(!output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("hello.txt uses fake_hash()"));
+    assert!(stdout.contains(&format!("hash!(\"{}\")", real_hash())));
+}
+
+#[test]
+fn bootstrap_only_downloads_with_builtins() {
+    let (_, pkgs) = all_pkgs();
+    let src = pkgs
+        .fetchurl
+        .fetch("https://example.org/hello-1.0.tar.gz", fake_hash());
+    let mut names = Vec::new();
+    let mut pending = vec![src];
+    while let Some(drv) = pending.pop() {
+        let drv = drv.into_drv();
+        pending.extend(direct_drvs(&drv).into_iter().map(|(dep, _)| dep));
+        names.push(drv.name);
+    }
+    assert!(names.iter().any(|name| name.starts_with("curl-")));
+    // gnu-config would need the fetchurl being built
+    assert!(
+        !names.iter().any(|name| name.starts_with("gnu-config")),
+        "{names:?}"
+    );
+}
@@ -0,0 +1,186 @@
+mod common;
+
+use common::{Plain, Sandbox, assert_success, fingerprint, repo_file};
+use oxide_core::prelude::*;
+use oxide_pkgs::{
+    lib::fake_hash,
+    top_level::{all_packages::all_pkgs, registry::direct_drvs},
+};
+use std::{
+    panic::{self, AssertUnwindSafe},
+    process::{Command, Output},
+};
+
+fn fetchzip(sandbox: &Sandbox, archive: &str, out: &str, strip_root: bool) -> Output {
+    fetchzip_with_hash(sandbox, archive, out, strip_root, None)
+}
+
+fn fetchzip_with_hash(
+    sandbox: &Sandbox,
+    archive: &str,
+    out: &str,
+    strip_root: bool,
+    hash: Option<&str>,
+) -> Output {
+    let url = format!("file://{}", sandbox.path(archive).display());
+    let unpack = repo_file("src/pkgs/build/fetchers/fetchzip/unpack.sh");
+    let normalise = repo_file("src/pkgs/build/fetchers/normalise.sh");
+    let mut env = vec![
+        ("url", url.as_str()),
+        ("DOWNLOAD_TO_TEMP", "1"),
+        ("FETCHZIP", unpack.to_str().unwrap()),
+        ("NORMALISE", normalise.to_str().unwrap()),
+        ("ARCHIVE_NAME", archive),
+        (
+            "POST_FETCH",
+            "source \"$FETCHZIP\"\nnormalise_tree \"$out\"",
+        ),
+    ];
+    if strip_root {
+        env.push(("STRIP_ROOT", "1"));
+    }
+    if let Some(hash) = hash {
+        env.push(("EXPECTED_HASH", hash));
+    }
+    sandbox.build(
+        &repo_file("src/pkgs/build/fetchers/fetchurl/builder.sh"),
+        out,
+        &env,
+    )
+}
+
+fn make_tree(sandbox: &Sandbox, epoch: u32) {
+    sandbox.sh(&format!(
+        r#"rm -rf tree
+mkdir -p tree/hello-1.0/bin tree/hello-1.0/share/doc
+echo '#!/bin/sh' > tree/hello-1.0/bin/hello
+echo 'hello world' > tree/hello-1.0/share/doc/README
+ln -s share/doc/README tree/hello-1.0/README
+chmod 700 tree/hello-1.0/bin/hello tree/hello-1.0/share
+chmod 600 tree/hello-1.0/share/doc/README
+find tree -exec touch -h -d @{epoch} {{}} +"#
+    ));
+}
+
+#[test]
+fn recompressed_archives_unpack_to_the_same_tree() {
+    let sandbox = Sandbox::new("fetchzip-recompressed");
+    make_tree(&sandbox, 1000000000);
+    sandbox.sh("tar -C tree -czf hello.tar.gz hello-1.0");
+    make_tree(&sandbox, 1700000000);
+    sandbox.sh("tar -C tree -cJf hello.tar.xz hello-1.0");
+    sandbox.sh("cd tree && zip -qry ../hello.zip hello-1.0");
+
+    for (archive, out) in [
+        ("hello.tar.gz", "out-gz"),
+        ("hello.tar.xz", "out-xz"),
+        ("hello.zip", "out-zip"),
+    ] {
+        assert_success(&fetchzip(&sandbox, archive, out, true));
+    }
+
+    let expected = fingerprint(&sandbox.path("out-gz"));
+    assert!(
+        expected
+            .iter()
+            .any(|line| line.starts_with("./bin/hello f 755 315532800"))
+    );
+    assert!(
+        expected
+            .iter()
+            .any(|line| line.starts_with("./share/doc/README f 644 315532800"))
+    );
+    assert!(expected.iter().any(|line| line.starts_with("./README l ")));
+    assert_eq!(expected, fingerprint(&sandbox.path("out-xz")));
+    assert_eq!(expected, fingerprint(&sandbox.path("out-zip")));
+}
+
+#[test]
+fn checks_the_nar_hash_of_the_tree() {
+    let sandbox = Sandbox::new("fetchzip-nar-hash");
+    make_tree(&sandbox, 1000000000);
+    sandbox.sh("tar -C tree -czf hello.tar.gz hello-1.0");
+    // the prefetch tool hashes a checkout of the same tree with its own NAR
+    sandbox.sh(
+        r#"export GIT_CONFIG_NOSYSTEM=1 HOME="$PWD" GIT_AUTHOR_NAME=test GIT_AUTHOR_EMAIL=test@example.com
+export GIT_COMMITTER_NAME=test GIT_COMMITTER_EMAIL=test@example.com
+cp -r tree/hello-1.0 repo
+cd repo
+git init -q
+git add -A
+git commit -qm init"#,
+    );
+    let prefetch = Command::new(env!("CARGO_BIN_EXE_prefetch"))
+        .args([
+            "--git",
+            &format!("file://{}", sandbox.path("repo").display()),
+        ])
+        .args(["--rev", "HEAD"])
+        .output()
+        .unwrap();
+    assert_success(&prefetch);
+    let hash = String::from_utf8(prefetch.stdout).unwrap();
+    let hash = hash.trim();
+
+    assert_success(&fetchzip_with_hash(
+        &sandbox,
+        "hello.tar.gz",
+        "right",
+        true,
+        Some(hash),
+    ));
+
+    let fake = "sha512:".to_string() + &"A".repeat(86);
+    let output = fetchzip_with_hash(&sandbox, "hello.tar.gz", "fake", true, Some(&fake));
+    assert!(!output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("fake uses fake_hash()"), "{stdout}");
+    assert!(stdout.contains(&format!("hash!(\"{hash}\")")), "{stdout}");
+
+    let wrong = "sha512:".to_string() + &"B".repeat(86);
+    let output = fetchzip_with_hash(&sandbox, "hello.tar.gz", "wrong", true, Some(&wrong));
+    assert!(!output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("hash mismatch in fixed output derivation wrong"));
+    assert!(stdout.contains(&format!("got:       hash!(\"{hash}\")")));
+}
+
+#[test]
+fn keeps_the_root_when_asked() {
+    let sandbox = Sandbox::new("fetchzip-keep-root");
+    make_tree(&sandbox, 1000000000);
+    sandbox.sh("tar -C tree -czf hello.tar.gz hello-1.0");
+
+    assert_success(&fetchzip(&sandbox, "hello.tar.gz", "out", false));
+    assert!(sandbox.path("out/hello-1.0/bin/hello").is_file());
+}
+
+#[test]
+fn refuses_to_strip_multiple_roots() {
+    let sandbox = Sandbox::new("fetchzip-multiple-roots");
+    sandbox.sh("mkdir -p tree/a tree/b && tar -C tree -czf multi.tar.gz a b");
+
+    let output = fetchzip(&sandbox, "multi.tar.gz", "out", true);
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stdout).contains("single file or directory"));
+}
+
+#[test]
+fn zip_archives_need_unzip() {
+    let (_, pkgs) = all_pkgs();
+    let url = "https://example.org/hello-1.0.zip";
+    assert!(
+        panic::catch_unwind(AssertUnwindSafe(|| pkgs.fetchzip.fetch(url, fake_hash()))).is_err()
+    );
+
+    let unzip = LazyDrv::new(Plain(DrvBuilder::new().name("unzip-6.0").build()));
+    let src = pkgs.fetchzip.clone().unzip(unzip).fetch(url, fake_hash());
+    assert!(
+        direct_drvs(&src.into_drv())
+            .into_iter()
+            .any(|(dep, _)| dep.into_drv().name == "unzip-6.0")
+    );
+    // tarballs unpack with the tools of the stdenv
+    pkgs.fetchzip
+        .fetch("https://example.org/hello-1.0.tar.gz", fake_hash());
+}
@@ -0,0 +1,237 @@
+mod common;
+
+use common::Sandbox;
+use oxide_core::prelude::*;
+use oxide_pkgs::{
+    build::forges::{FetchFromForge, ForgeArgs},
+    lib::fake_hash,
+    top_level::all_packages::all_pkgs,
+};
+use std::{
+    fs,
+    panic::{self, AssertUnwindSafe},
+    process::Command,
+};
+
+const REV: &str = "0123456789abcdef0123456789abcdef01234567";
+
+fn forges() -> FetchFromForge {
+    let (_, pkgs) = all_pkgs();
+    FetchFromForge::clone(&pkgs.forges)
+}
+
+fn args(rev: &str) -> ForgeArgs {
+    ForgeArgs::new("owner", "repo", rev.to_string(), fake_hash())
+}
+
+fn input(drv: &Drv, key: &str) -> String {
+    match drv.inputs.get(key) {
+        Some(Expr::Str(value)) => value.to_string(),
+        Some(_) => panic!("{key} is not a string"),
+        None => panic!("{} has no {key}", drv.name),
+    }
+}
+
+fn url(drv: LazyDrv) -> String {
+    input(&drv.into_drv(), "url")
+}
+
+fn impure_env_vars(drv: &Drv) -> Vec<String> {
+    match &drv.inputs["IMPURE_ENV_VARS"] {
+        Expr::List(vars) => vars
+            .iter()
+            .map(|var| match var {
+                Expr::Str(var) => var.to_string(),
+                _ => panic!("not a variable name"),
+            })
+            .collect(),
+        _ => panic!("IMPURE_ENV_VARS is not a list"),
+    }
+}
+
+// Runs the netrc phase of `drv` in an empty directory, returning the netrc
+fn netrc(drv: &Drv, env: &[(&str, &str)]) -> Result<String, String> {
+    let sandbox = Sandbox::new(&format!("forges-netrc-{}", env.len()));
+    let output = Command::new("bash")
+        .args(["-euc", &input(drv, "NETRC_PHASE")])
+        .current_dir(&sandbox.dir)
+        .env_clear()
+        .env("PATH", "/usr/bin:/bin")
+        .envs(env.iter().copied())
+        .output()
+        .unwrap();
+    match output.status.success() {
+        true => Ok(fs::read_to_string(sandbox.path("netrc")).unwrap()),
+        false => Err(String::from_utf8_lossy(&output.stderr).to_string()),
+    }
+}
+
+fn panics(f: impl FnOnce() -> LazyDrv) -> bool {
+    panic::catch_unwind(AssertUnwindSafe(|| f().into_drv())).is_err()
+}
+
+#[test]
+fn github_archives_and_checkouts() {
+    let forges = forges();
+    assert_eq!(
+        url(forges.fetch_from_github(args("v1.0"))),
+        "https://github.com/owner/repo/archive/v1.0.tar.gz"
+    );
+    assert_eq!(
+        url(forges.fetch_from_github(args(REV))),
+        format!("https://github.com/owner/repo/archive/{REV}.tar.gz")
+    );
+    assert_eq!(
+        url(forges.fetch_from_github(args("v1.0").domain("github.example.org"))),
+        "https://github.example.org/owner/repo/archive/v1.0.tar.gz"
+    );
+
+    let drv = forges
+        .fetch_from_github(args(REV).fetch_submodules())
+        .into_drv();
+    assert_eq!(input(&drv, "url"), "https://github.com/owner/repo.git");
+    assert_eq!(input(&drv, "REV"), REV);
+}
+
+#[test]
+fn github_private_repositories_go_through_the_api() {
+    let forges = forges();
+    let drv = forges.fetch_from_github(args("v1.0").private()).into_drv();
+    assert_eq!(
+        input(&drv, "url"),
+        "https://api.github.com/repos/owner/repo/tarball/v1.0"
+    );
+    assert_eq!(input(&drv, "ARCHIVE_NAME"), "source.tar.gz");
+    assert_eq!(
+        impure_env_vars(&drv),
+        vec![
+            "OXIDE_GITHUB_PRIVATE_USERNAME",
+            "OXIDE_GITHUB_PRIVATE_PASSWORD"
+        ]
+    );
+    assert!(
+        netrc(&drv, &[])
+            .unwrap_err()
+            .contains("need OXIDE_GITHUB_PRIVATE_USERNAME and OXIDE_GITHUB_PRIVATE_PASSWORD")
+    );
+    let netrc = netrc(
+        &drv,
+        &[
+            ("OXIDE_GITHUB_PRIVATE_USERNAME", "me"),
+            ("OXIDE_GITHUB_PRIVATE_PASSWORD", "token"),
+        ],
+    )
+    .unwrap();
+    assert_eq!(
+        netrc.split_whitespace().collect::<Vec<_>>(),
+        vec![
+            "machine",
+            "api.github.com",
+            "login",
+            "me",
+            "password",
+            "token"
+        ]
+    );
+
+    let drv = forges
+        .fetch_from_github(args(REV).private().domain("github.example.org"))
+        .into_drv();
+    assert_eq!(
+        input(&drv, "url"),
+        format!("https://github.example.org/api/v3/repos/owner/repo/tarball/{REV}")
+    );
+    assert!(input(&drv, "NETRC_PHASE").contains("machine github.example.org\n"));
+}
+
+#[test]
+fn gitlab_archives_and_private_checkouts() {
+    let forges = forges();
+    assert_eq!(
+        url(forges.fetch_from_gitlab(args("v1.0"))),
+        "https://gitlab.com/owner/repo/-/archive/v1.0/repo-v1.0.tar.gz"
+    );
+    assert_eq!(
+        url(forges.fetch_from_gitlab(args(REV).domain("gitlab.example.org"))),
+        format!("https://gitlab.example.org/owner/repo/-/archive/{REV}/repo-{REV}.tar.gz")
+    );
+
+    let drv = forges.fetch_from_gitlab(args(REV).private()).into_drv();
+    assert_eq!(input(&drv, "url"), "https://gitlab.com/owner/repo.git");
+    assert_eq!(input(&drv, "REV"), REV);
+    assert_eq!(
+        impure_env_vars(&drv),
+        vec![
+            "OXIDE_GITLAB_PRIVATE_USERNAME",
+            "OXIDE_GITLAB_PRIVATE_PASSWORD"
+        ]
+    );
+    assert!(input(&drv, "NETRC_PHASE").contains("machine gitlab.com\n"));
+}
+
+#[test]
+fn gitea_and_codeberg_archives() {
+    let forges = forges();
+    assert!(panics(|| forges.fetch_from_gitea(args("v1.0"))));
+    assert_eq!(
+        url(forges.fetch_from_gitea(args("v1.0").domain("git.example.org"))),
+        "https://git.example.org/owner/repo/archive/v1.0.tar.gz"
+    );
+    assert_eq!(
+        url(forges.fetch_from_codeberg(args(REV))),
+        format!("https://codeberg.org/owner/repo/archive/{REV}.tar.gz")
+    );
+
+    let drv = forges
+        .fetch_from_codeberg(args("v1.0").private())
+        .into_drv();
+    assert_eq!(
+        input(&drv, "url"),
+        "https://codeberg.org/owner/repo/archive/v1.0.tar.gz"
+    );
+    assert_eq!(
+        impure_env_vars(&drv),
+        vec![
+            "OXIDE_GITEA_PRIVATE_USERNAME",
+            "OXIDE_GITEA_PRIVATE_PASSWORD"
+        ]
+    );
+    assert!(input(&drv, "NETRC_PHASE").contains("machine codeberg.org\n"));
+
+    let drv = forges
+        .fetch_from_codeberg(args(REV).leave_dot_git())
+        .into_drv();
+    assert_eq!(input(&drv, "url"), "https://codeberg.org/owner/repo.git");
+}
+
+#[test]
+fn sourcehut_archives_and_checkouts() {
+    let forges = forges();
+    assert_eq!(
+        url(forges.fetch_from_sourcehut(args("v1.0"))),
+        "https://git.sr.ht/~owner/repo/archive/v1.0.tar.gz"
+    );
+    let drv = forges
+        .fetch_from_sourcehut(args(REV).sparse_checkout("src"))
+        .into_drv();
+    assert_eq!(input(&drv, "url"), "https://git.sr.ht/~owner/repo");
+    assert_eq!(input(&drv, "REV"), REV);
+    assert!(panics(
+        || forges.fetch_from_sourcehut(args("v1.0").private())
+    ));
+}
+
+#[test]
+fn public_archives_have_no_credentials() {
+    let forges = forges();
+    for drv in [
+        forges.fetch_from_github(args("v1.0")),
+        forges.fetch_from_gitlab(args("v1.0")),
+        forges.fetch_from_codeberg(args("v1.0")),
+        forges.fetch_from_sourcehut(args("v1.0")),
+    ] {
+        let drv = drv.into_drv();
+        assert!(!drv.inputs.contains_key("NETRC_PHASE"), "{}", drv.name);
+        assert!(!drv.inputs.contains_key("IMPURE_ENV_VARS"), "{}", drv.name);
+    }
+}
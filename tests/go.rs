@@ -0,0 +1,135 @@
+mod common;
+
+use common::{Sandbox, assert_success, repo_file};
+use std::{fs, process::Output};
+
+// A go stand-in recording how the builders call it, `install` builds
+// a script named after each package
+const FAKE_GO: &str = r#"#!/bin/bash
+echo "$* GOFLAGS=$GOFLAGS" >> "$LOG"
+case "$1" in
+    mod)
+        mkdir -p vendor/example.com/dep
+        echo '# example.com/dep v1.0.0' > vendor/modules.txt
+        ;;
+    install)
+        mkdir -p "$GOPATH/bin"
+        for arg in "${@:2}"; do
+            case "$arg" in
+                ./...) printf '#!/bin/sh\necho %s\n' "$name" > "$GOPATH/bin/$name" ;;
+                ./*) printf '#!/bin/sh\necho %s\n' "${arg##*/}" > "$GOPATH/bin/${arg##*/}" ;;
+            esac
+        done
+        chmod +x "$GOPATH"/bin/*
+        ;;
+esac
+"#;
+
+// A plain directory standing in for `GoBin`
+fn go(sandbox: &Sandbox) -> String {
+    sandbox.write("go/bin/go", FAKE_GO);
+    sandbox.sh("chmod +x go/bin/go");
+    sandbox.path("go").to_str().unwrap().to_string()
+}
+
+fn module(sandbox: &Sandbox) {
+    sandbox.write("src/go.mod", "module example.com/app\n\ngo 1.25\n");
+    sandbox.write("src/cmd/app/main.go", "package main\n\nfunc main() {}\n");
+}
+
+fn vendor(sandbox: &Sandbox) -> Output {
+    let src = sandbox.path("src");
+    let log = sandbox.path("vendor.log");
+    let normalise = repo_file("src/pkgs/build/fetchers/normalise.sh");
+    sandbox.build(
+        &repo_file("src/pkgs/build/go/vendor.sh"),
+        "vendor",
+        &[
+            ("SRC", src.to_str().unwrap()),
+            ("UNPACK", "1"),
+            ("NORMALISE", normalise.to_str().unwrap()),
+            ("DEPS_BUILD_HOST", &go(sandbox)),
+            ("LOG", log.to_str().unwrap()),
+        ],
+    )
+}
+
+fn build(sandbox: &Sandbox, env: &[(&str, &str)]) -> Output {
+    let src = sandbox.path("src");
+    let log = sandbox.path("build.log");
+    let go = go(sandbox);
+    let mut vars = vec![
+        ("SRC", src.to_str().unwrap()),
+        ("UNPACK", "1"),
+        ("CONFIGURE", "1"),
+        ("BUILD", "1"),
+        ("CHECK", "1"),
+        ("INSTALL", "1"),
+        ("DEPS_BUILD_HOST", &go),
+        ("LOG", log.to_str().unwrap()),
+        ("OXIDE_BUILD_CORES", "4"),
+    ];
+    vars.extend_from_slice(env);
+    sandbox.build(&repo_file("src/pkgs/build/go/builder.sh"), "out", &vars)
+}
+
+#[test]
+fn vendors_the_modules() {
+    let sandbox = Sandbox::new("go-vendor");
+    module(&sandbox);
+    assert_success(&vendor(&sandbox));
+    assert!(sandbox.path("vendor/modules.txt").exists());
+    assert!(sandbox.path("vendor/example.com/dep").is_dir());
+    assert_eq!(
+        fs::read_to_string(sandbox.path("vendor.log")).unwrap(),
+        "mod vendor GOFLAGS=-mod=mod\n"
+    );
+}
+
+#[test]
+fn builds_tests_and_installs_with_the_vendored_modules() {
+    let sandbox = Sandbox::new("go-build");
+    module(&sandbox);
+    assert_success(&vendor(&sandbox));
+    let vendor = sandbox.path("vendor");
+    assert_success(&build(
+        &sandbox,
+        &[
+            ("GO_VENDOR", vendor.to_str().unwrap()),
+            ("GO_SUB_PACKAGES", "./cmd/app ./cmd/tool"),
+            ("GO_LDFLAGS", "-s -w"),
+            ("GO_TAGS", "netgo,osusergo"),
+        ],
+    ));
+    assert_eq!(
+        fs::read_to_string(sandbox.path("build.log")).unwrap(),
+        "install -p 4 -tags netgo,osusergo -ldflags -s -w ./cmd/app ./cmd/tool \
+         GOFLAGS=-mod=vendor -trimpath\n\
+         test -p 4 -tags netgo,osusergo ./cmd/app ./cmd/tool GOFLAGS=-mod=vendor -trimpath\n"
+    );
+    assert!(sandbox.path("build-out/src/vendor/modules.txt").exists());
+    let output = sandbox.sh("out/bin/app && out/bin/tool");
+    assert_eq!(String::from_utf8(output.stdout).unwrap(), "app\ntool\n");
+}
+
+#[test]
+fn builds_every_package_by_default() {
+    let sandbox = Sandbox::new("go-all-packages");
+    module(&sandbox);
+    assert_success(&build(&sandbox, &[]));
+    assert_eq!(
+        fs::read_to_string(sandbox.path("build.log")).unwrap(),
+        "install -p 4 ./... GOFLAGS=-mod=vendor -trimpath\n\
+         test -p 4 ./... GOFLAGS=-mod=vendor -trimpath\n"
+    );
+    assert!(sandbox.path("out/bin/out").exists());
+}
+
+#[test]
+fn vendoring_needs_a_go_mod() {
+    let sandbox = Sandbox::new("go-no-go-mod");
+    sandbox.write("src/main.go", "package main\n");
+    let output = vendor(&sandbox);
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stdout).contains("has no go.mod"));
+}
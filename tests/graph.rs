@@ -0,0 +1,221 @@
+mod common;
+
+use common::{Plain, Sandbox, assert_success};
+use oxide_core::prelude::*;
+use oxide_pkgs::top_level::graph::{Edge, EdgeKind, Graph, GraphArgs, build_graph, runtime_graph};
+use serde_json::Value;
+use std::process::Command;
+
+fn drv(name: &str, inputs: &[(&str, Expr)]) -> LazyDrv {
+    let mut builder = DrvBuilder::new().name(name.to_string()).builder("/bin/sh");
+    for (key, value) in inputs {
+        builder = builder.input(key.to_string(), value.clone());
+    }
+    LazyDrv::new(Plain(builder.build()))
+}
+
+// app-1.0 -> tool, lib-2.0 (out and dev), tool -> lib-2.0 -> base
+fn app() -> LazyDrv {
+    let base = drv("base", &[]);
+    let lib = drv("lib-2.0", &[("BASE", Expr::Drv(base, None))]);
+    let tool = drv("tool", &[("LIB", Expr::Drv(LazyDrv::clone(&lib), None))]);
+    drv(
+        "app-1.0",
+        &[
+            ("LIB", Expr::Drv(LazyDrv::clone(&lib), None)),
+            ("LIB_DEV", lib.out("dev")),
+            ("TOOLS", Expr::List(vec![Expr::Drv(tool, None)])),
+        ],
+    )
+}
+
+fn edge(from: &str, to: &str, kind: EdgeKind) -> Edge {
+    Edge {
+        from: from.to_string(),
+        to: to.to_string(),
+        kind,
+    }
+}
+
+// The nodes and edges by label, nodes are identified by their drv hash
+fn nodes(graph: &Graph) -> Vec<(&str, usize)> {
+    let mut nodes: Vec<(&str, usize)> = graph
+        .nodes
+        .iter()
+        .map(|(node, depth)| (graph.label(node), *depth))
+        .collect();
+    nodes.sort();
+    nodes
+}
+
+fn edges(graph: &Graph) -> Vec<Edge> {
+    let mut edges: Vec<Edge> = graph
+        .edges
+        .iter()
+        .map(|e| edge(graph.label(&e.from), graph.label(&e.to), e.kind))
+        .collect();
+    edges.sort();
+    edges
+}
+
+fn why(graph: &Graph) -> Vec<&str> {
+    graph.why.iter().map(|node| graph.label(node)).collect()
+}
+
+#[test]
+fn walks_the_inputs_of_a_derivation() {
+    let graph = build_graph(&app(), &GraphArgs::new());
+    assert_eq!(graph.label(&graph.root), "app-1.0");
+    assert_eq!(
+        nodes(&graph),
+        vec![("app-1.0", 0), ("base", 2), ("lib-2.0", 1), ("tool", 1)]
+    );
+    assert_eq!(
+        edges(&graph),
+        vec![
+            edge("app-1.0", "lib-2.0", EdgeKind::Build),
+            edge("app-1.0", "tool", EdgeKind::Build),
+            edge("lib-2.0", "base", EdgeKind::Build),
+            edge("tool", "lib-2.0", EdgeKind::Build),
+        ]
+    );
+
+    let graph = build_graph(&app(), &GraphArgs::new().depth(1));
+    assert_eq!(
+        nodes(&graph),
+        vec![("app-1.0", 0), ("lib-2.0", 1), ("tool", 1)]
+    );
+    assert!(edges(&graph).iter().all(|edge| edge.to != "base"));
+}
+
+#[test]
+fn groups_the_edges_by_output() {
+    let graph = build_graph(&app(), &GraphArgs::new().by_output().depth(1));
+    assert_eq!(
+        edges(&graph),
+        vec![
+            edge("app-1.0", "lib-2.0^dev", EdgeKind::Build),
+            edge("app-1.0", "lib-2.0^out", EdgeKind::Build),
+            edge("app-1.0", "tool^out", EdgeKind::Build),
+            edge("lib-2.0^dev", "lib-2.0", EdgeKind::Output),
+            edge("lib-2.0^out", "lib-2.0", EdgeKind::Output),
+            edge("tool", "lib-2.0^out", EdgeKind::Build),
+            edge("tool^out", "tool", EdgeKind::Output),
+        ]
+    );
+}
+
+#[test]
+fn highlights_why_a_package_is_depended_on() {
+    // kept past the depth limit, found by name without its version
+    let graph = build_graph(&app(), &GraphArgs::new().depth(0).why("base"));
+    assert_eq!(why(&graph), vec!["app-1.0", "lib-2.0", "base"]);
+    assert_eq!(
+        nodes(&graph),
+        vec![("app-1.0", 0), ("base", 2), ("lib-2.0", 1)]
+    );
+    let dot = graph.to_dot();
+    assert!(dot.starts_with("digraph \"app-1.0\" {\n"), "{dot}");
+    let (lib, base) = (&graph.why[1], &graph.why[2]);
+    assert!(
+        dot.contains(&format!(
+            "  \"{lib}\" -> \"{base}\" [color=red, penwidth=2];\n"
+        )),
+        "{dot}"
+    );
+    assert!(
+        dot.contains(&format!("  \"{base}\" [label=\"base\", color=red];\n")),
+        "{dot}"
+    );
+
+    let graph = build_graph(&app(), &GraphArgs::new().why("unrelated"));
+    assert!(graph.why.is_empty());
+    assert_eq!(graph.to_json()["why"], Value::Null);
+}
+
+#[test]
+fn walks_the_references_of_a_built_output() {
+    let sandbox = Sandbox::new("graph-runtime");
+    let store = sandbox.path("store").display().to_string();
+    sandbox.write("store/c-1.0/lib/libc.so", "");
+    sandbox.write("store/b-1.0/lib/libb.so", &format!("\0{store}/c-1.0/lib\0"));
+    sandbox.write(
+        "store/a-1.0/bin/a",
+        &format!("#!/bin/sh\nexec {store}/a-1.0/bin/real {store}/missing\n"),
+    );
+    sandbox.sh("ln -s ../../b-1.0/lib/libb.so store/a-1.0/bin/libb.so");
+    sandbox.sh(&format!("ln -s {store}/b-1.0/lib/libb.so store/a-1.0/libb"));
+    sandbox.write("store/unrelated/file", "");
+
+    let graph = runtime_graph(&sandbox.path("store/a-1.0"), &GraphArgs::new()).unwrap();
+    assert_eq!(
+        edges(&graph),
+        vec![
+            edge("a-1.0", "b-1.0", EdgeKind::Runtime),
+            edge("b-1.0", "c-1.0", EdgeKind::Runtime),
+        ]
+    );
+    let graph = runtime_graph(&sandbox.path("store/a-1.0"), &GraphArgs::new().why("c")).unwrap();
+    // store paths are their own ids
+    assert_eq!(graph.why, vec!["a-1.0", "b-1.0", "c-1.0"]);
+    assert!(runtime_graph(&sandbox.path("store/missing"), &GraphArgs::new()).is_err());
+}
+
+#[test]
+fn graphs_packages_from_the_cli() {
+    let output = Command::new(env!("CARGO_BIN_EXE_registry"))
+        .args(["--json", "graph", "--depth", "1", "--why", "zlib", "curl"])
+        .output()
+        .unwrap();
+    assert_success(&output);
+    let graph: Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(graph["why"][0], graph["root"]);
+    let nodes = graph["nodes"].as_array().unwrap();
+    let why = graph["why"].as_array().unwrap();
+    let zlib = nodes
+        .iter()
+        .find(|node| node["id"] == *why.last().unwrap())
+        .unwrap();
+    assert!(zlib["name"].as_str().unwrap().starts_with("zlib-"));
+    assert!(
+        nodes
+            .iter()
+            .all(|node| node["depth"].as_u64().unwrap() <= 1 || why.contains(&node["id"]))
+    );
+
+    let output = Command::new(env!("CARGO_BIN_EXE_registry"))
+        .args(["graph", "no-such-package"])
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+}
+
+#[test]
+fn derivations_of_the_same_name_are_different_nodes() {
+    // a bootstrap tool built without lib, and the one built with it
+    let bootstrap_tool = drv("tool", &[]);
+    let lib = drv("lib-2.0", &[("TOOL", Expr::Drv(bootstrap_tool, None))]);
+    let tool = drv("tool", &[("LIB", Expr::Drv(LazyDrv::clone(&lib), None))]);
+    let app = drv("app-1.0", &[("TOOL", Expr::Drv(tool, None))]);
+
+    let graph = build_graph(&app, &GraphArgs::new());
+    assert_eq!(
+        nodes(&graph),
+        vec![("app-1.0", 0), ("lib-2.0", 2), ("tool", 1), ("tool", 3)]
+    );
+    // no cycle between lib and tool
+    assert_eq!(
+        graph
+            .edges
+            .iter()
+            .filter(|edge| graph.label(&edge.from) == "lib-2.0")
+            .count(),
+        1
+    );
+    assert!(
+        graph
+            .edges
+            .iter()
+            .all(|edge| graph.nodes[&edge.from] < graph.nodes[&edge.to])
+    );
+}
@@ -0,0 +1,43 @@
+use oxide_core::prelude::*;
+use oxide_pkgs::{development::libraries::libiconv::LibIConv, top_level::all_packages::all_pkgs};
+
+fn libiconv(r#static: Option<bool>, shared: Option<bool>) -> Drv {
+    let (_, all) = all_pkgs();
+    LibIConv {
+        stdenv: all.stdenv.clone(),
+        fetchurl: all.fetchurl.clone(),
+        update_autotools_gnu_config_scripts_hook: None,
+        r#static,
+        shared,
+    }
+    .into_drv()
+}
+
+fn configure_flags(drv: &Drv) -> &str {
+    match &drv.inputs["CONFIGURE_FLAGS"] {
+        Expr::Str(flags) => flags,
+        _ => panic!("CONFIGURE_FLAGS is not a string"),
+    }
+}
+
+#[test]
+fn static_and_shared_are_independent() {
+    assert_eq!(
+        configure_flags(&libiconv(None, None)),
+        "--disable-static --enable-shared"
+    );
+    assert_eq!(
+        configure_flags(&libiconv(Some(true), None)),
+        "--enable-static --enable-shared"
+    );
+    let static_only = libiconv(Some(true), Some(false));
+    assert_eq!(
+        configure_flags(&static_only),
+        "--enable-static --disable-shared"
+    );
+    // without the shared library the preloadable one is not built either
+    assert!(matches!(
+        &static_only.inputs["POST_PATCH"],
+        Expr::Str(post_patch) if post_patch.contains("/preload/d")
+    ));
+}
@@ -0,0 +1,99 @@
+mod common;
+
+use common::{Sandbox, assert_success, repo_file};
+use std::process::Output;
+
+// A program printing what the wrappers change
+const SHOW: &str = r#"mkdir -p "$out/bin"
+cat > "$out/bin/show" <<'EOF'
+#!/bin/sh
+echo "args=$*"
+echo "pwd=$PWD"
+echo "GREETING=${GREETING-unset}"
+echo "MODE=${MODE-unset}"
+echo "SEARCH=${SEARCH-unset}"
+echo "SECRET=${SECRET-unset}"
+EOF
+chmod +x "$out/bin/show""#;
+
+fn build(sandbox: &Sandbox, wrap_programs: &str) -> Output {
+    let make_wrapper = repo_file("src/pkgs/build/setup-hooks/make-wrapper.sh");
+    sandbox.build(
+        &repo_file("src/pkgs/stdenv/generic/scripts/default-builder.sh"),
+        "out",
+        &[
+            ("INSTALL", "1"),
+            ("INSTALL_PHASE", SHOW),
+            ("FIX", "1"),
+            ("MAKE_WRAPPER", make_wrapper.to_str().unwrap()),
+            ("WRAP_PROGRAMS", wrap_programs),
+        ],
+    )
+}
+
+#[test]
+fn wraps_with_the_environment_and_flags() {
+    let sandbox = Sandbox::new("make-wrapper-wrap");
+    // as rendered by `Wrap`
+    assert_success(&build(
+        &sandbox,
+        r#"wrap_program "$out/bin/show" --set "GREETING" "hello world" --prefix "SEARCH" ":" "$out/share" --suffix "SEARCH" ":" "/last" --add-flags "--first" --chdir "/" --run 'export SECRET="$(echo hidden)"'"#,
+    ));
+    assert!(sandbox.path("out/bin/.show-wrapped").exists());
+    let out = sandbox.path("out");
+    assert_eq!(
+        sandbox.run("SEARCH=/usr out/bin/show a 'b c'"),
+        format!(
+            "args=--first a b c\npwd=/\nGREETING=hello world\nMODE=unset\n\
+             SEARCH={}/share:/usr:/last\nSECRET=hidden\n",
+            out.display()
+        )
+    );
+    assert_eq!(
+        sandbox.run("env -u SEARCH out/bin/show").lines().nth(4),
+        Some(format!("SEARCH={}/share:/last", out.display()).as_str())
+    );
+}
+
+#[test]
+fn set_default_unset_and_argv0() {
+    let sandbox = Sandbox::new("make-wrapper-defaults");
+    assert_success(&build(
+        &sandbox,
+        r#"wrap_program "$out/bin/show" --set-default "MODE" "fast" --unset "SECRET" --argv0 "shown""#,
+    ));
+    let output = sandbox.run("SECRET=1 out/bin/show");
+    assert!(output.contains("MODE=fast\n"));
+    assert!(output.contains("SECRET=unset\n"));
+    assert!(
+        sandbox
+            .run("MODE=slow out/bin/show")
+            .contains("MODE=slow\n")
+    );
+    // the kernel gives scripts their path as $0, only binaries see argv0
+    assert!(sandbox.run("cat out/bin/show").contains("exec -a shown "));
+}
+
+#[test]
+fn wrapping_twice_keeps_both_programs() {
+    let sandbox = Sandbox::new("make-wrapper-twice");
+    assert_success(&build(
+        &sandbox,
+        r#"wrap_program "$out/bin/show" --set "GREETING" "inner"
+wrap_program "$out/bin/show" --add-flags "outer""#,
+    ));
+    assert!(sandbox.path("out/bin/.show-wrapped_").exists());
+    let output = sandbox.run("out/bin/show");
+    assert!(output.contains("args=outer\n"), "{output}");
+    assert!(output.contains("GREETING=inner\n"));
+}
+
+#[test]
+fn refuses_a_missing_program() {
+    let sandbox = Sandbox::new("make-wrapper-missing");
+    let output = build(
+        &sandbox,
+        r#"wrap_program "$out/bin/missing" --set "GREETING" "hello""#,
+    );
+    assert!(!output.status.success());
+}
@@ -0,0 +1,122 @@
+mod common;
+
+use common::{Sandbox, assert_success, repo_file};
+use std::{fs, process::Output};
+
+// meson and ninja stand-ins recording how the setup hooks call them
+const FAKE_MESON: &str = r#"#!/bin/bash
+echo "$PWD $1" >> "$LOG/calls"
+if [ "$1" = setup ]; then
+    mkdir -p "$2"
+    printf '%s\n' "${@:3}" > "$LOG/setup.args"
+    for arg in "$@"; do
+        case "$arg" in
+            --cross-file=*) cp "${arg#--cross-file=}" "$LOG/cross.ini" ;;
+        esac
+    done
+else
+    echo "${*:2}" >> "$LOG/calls"
+fi
+"#;
+
+const FAKE_NINJA: &str = r#"#!/bin/bash
+[ "$1" != -t ] || exit 1
+echo "$PWD ninja $*" >> "$LOG/calls"
+"#;
+
+fn build(sandbox: &Sandbox, env: &[(&str, &str)]) -> Output {
+    let meson = sandbox.tool("meson", FAKE_MESON, "src/pkgs/build/meson/setup-hook.sh");
+    let ninja = sandbox.tool("ninja", FAKE_NINJA, "src/pkgs/build/ninja/setup-hook.sh");
+    let tools = format!("{} {}", meson.display(), ninja.display());
+    sandbox.write("src/meson.build", "project('hello', 'c')\n");
+    fs::create_dir_all(sandbox.path("log")).unwrap();
+    let src = sandbox.path("src");
+    let log = sandbox.path("log");
+    let dev = sandbox.path("dev");
+    let mut vars = vec![
+        ("SRC", src.to_str().unwrap()),
+        ("UNPACK", "1"),
+        ("CONFIGURE", "1"),
+        ("BUILD", "1"),
+        ("CHECK", "1"),
+        ("INSTALL", "1"),
+        ("outputs", "out dev"),
+        ("dev", dev.to_str().unwrap()),
+        ("LOG", log.to_str().unwrap()),
+        ("DEPS_BUILD_HOST", &tools),
+        ("OXIDE_BUILD_CORES", "4"),
+        ("MESON_FLAGS", "-Dtests=true"),
+    ];
+    vars.extend_from_slice(env);
+    sandbox.build(
+        &repo_file("src/pkgs/stdenv/generic/scripts/default-builder.sh"),
+        "out",
+        &vars,
+    )
+}
+
+fn log(sandbox: &Sandbox, file: &str) -> String {
+    let log = fs::read_to_string(sandbox.path(&format!("log/{file}"))).unwrap_or_default();
+    log.replace(sandbox.path("build-out/src").to_str().unwrap(), "$src")
+}
+
+#[test]
+fn meson_and_ninja_replace_the_phases() {
+    let sandbox = Sandbox::new("meson-phases");
+    assert_success(&build(&sandbox, &[]));
+
+    let args: Vec<String> = log(&sandbox, "setup.args")
+        .lines()
+        .map(str::to_string)
+        .collect();
+    for flag in [
+        format!("--prefix={}", sandbox.path("out").display()),
+        format!("--includedir={}/include", sandbox.path("dev").display()),
+        format!("--libdir={}/lib", sandbox.path("out").display()),
+        "--buildtype=plain".to_string(),
+        "--wrap-mode=nodownload".to_string(),
+    ] {
+        assert!(args.contains(&flag), "missing {flag} in {args:?}");
+    }
+    assert_eq!(args.last().unwrap(), "-Dtests=true");
+    assert!(!sandbox.path("log/cross.ini").exists());
+
+    assert_eq!(
+        log(&sandbox, "calls"),
+        "$src setup\n\
+         $src/build ninja -j4\n\
+         $src/build test\n\
+         --no-rebuild --print-errorlogs --num-processes=4\n\
+         $src/build install\n\
+         --no-rebuild\n"
+    );
+}
+
+#[test]
+fn honours_parallel_settings_and_opt_outs() {
+    let sandbox = Sandbox::new("meson-opt-out");
+    assert_success(&build(
+        &sandbox,
+        &[
+            ("ENABLE_PARALLEL_BUILDING", ""),
+            ("ENABLE_PARALLEL_CHECKING", ""),
+            ("DONT_USE_MESON_INSTALL", "1"),
+        ],
+    ));
+    assert_eq!(
+        log(&sandbox, "calls"),
+        "$src setup\n\
+         $src/build ninja -j1\n\
+         $src/build test\n\
+         --no-rebuild --print-errorlogs --num-processes=1\n\
+         $src/build ninja -j4 install\n"
+    );
+}
+
+#[test]
+fn writes_the_cross_file() {
+    let sandbox = Sandbox::new("meson-cross");
+    let cross = "[host_machine]\nsystem = 'linux'\ncpu_family = 'x86'";
+    assert_success(&build(&sandbox, &[("MESON_CROSS_CONFIG", cross)]));
+    assert_eq!(log(&sandbox, "cross.ini"), format!("{cross}\n"));
+}
@@ -0,0 +1,125 @@
+mod common;
+
+use common::{Sandbox, assert_success, repo_file};
+use oxide_core::prelude::*;
+use oxide_pkgs::{
+    build::mk_shell::{MkShell, env_of},
+    stdenv::Stdenv,
+    top_level::all_packages::all_pkgs,
+};
+use std::{fs, path::PathBuf};
+
+fn mk_shell() -> PathBuf {
+    repo_file("src/pkgs/build/mk_shell/mk-shell.sh")
+}
+
+#[test]
+fn the_rc_file_brings_the_packages_and_runs_the_shell_hook() {
+    let sandbox = Sandbox::new("mk-shell-packages");
+    sandbox.write("greeter/bin/greet", "#!/bin/sh\necho hello\n");
+    sandbox.sh("chmod +x greeter/bin/greet && mkdir work");
+    let greeter = sandbox.path("greeter");
+    assert_success(&sandbox.build(
+        &mk_shell(),
+        "shell",
+        &[
+            ("DEPS_BUILD_HOST", greeter.to_str().unwrap()),
+            ("SHELL_HOOK", "echo \"hooked in $PWD\""),
+            ("GREETING", "multi\nline 'quoted'"),
+        ],
+    ));
+    assert!(!sandbox.path("build-shell/outputs").exists());
+    let work = sandbox.path("work");
+    assert_eq!(
+        sandbox.run(
+            r#"cd work && source ../shell/rc > /dev/null
+greet
+echo "out=$out"
+printf '%s\n' "$GREETING"
+declare -F run_phase substituteInPlace"#
+        ),
+        format!(
+            "hello\nout={}/outputs/out\nmulti\nline 'quoted'\nrun_phase\nsubstituteInPlace\n",
+            work.display()
+        )
+    );
+    assert_eq!(
+        sandbox.run("cd work && source ../shell/rc"),
+        format!("hooked in {}\n", work.display())
+    );
+}
+
+#[test]
+fn the_environment_of_a_package_runs_its_phases() {
+    let sandbox = Sandbox::new("mk-shell-env-of");
+    sandbox.write(
+        "src/configure",
+        "#!/bin/sh\necho \"configure $*\" > configured\n",
+    );
+    sandbox.sh("chmod +x src/configure && mkdir work");
+    // a package builder redefining a phase
+    let builder = sandbox.write(
+        "builder.sh",
+        r#"install_phase() {
+    mkdir -p "$out" "$dev"
+    cp configured "$out/"
+    echo "$name" > "$dev/name"
+}
+generic_build
+"#,
+    );
+    let src = sandbox.path("src");
+    assert_success(&sandbox.build(
+        &mk_shell(),
+        "hello-env",
+        &[
+            ("SHELL_BUILDER", builder.to_str().unwrap()),
+            ("SHELL_OUTPUTS", "out dev"),
+            ("SRC", src.to_str().unwrap()),
+            ("UNPACK", "1"),
+            ("CONFIGURE", "1"),
+            ("CONFIGURE_FLAGS", "--enable-feature"),
+            ("INSTALL", "1"),
+        ],
+    ));
+    // nothing was built
+    assert!(!sandbox.path("build-hello-env/src").exists());
+    sandbox.sh(r#"cd work && source ../hello-env/rc
+run_phase UNPACK_PHASE
+run_phase CONFIGURE_PHASE
+run_phase INSTALL_PHASE"#);
+    let work = sandbox.path("work");
+    assert_eq!(
+        fs::read_to_string(work.join("outputs/out/configured")).unwrap(),
+        format!(
+            "configure --prefix={}/outputs/out --enable-feature\n",
+            work.display()
+        )
+    );
+    assert_eq!(
+        fs::read_to_string(work.join("outputs/dev/name")).unwrap(),
+        "hello-env\n"
+    );
+}
+
+fn input(drv: &Drv, key: &str) -> String {
+    match drv.inputs.get(key) {
+        Some(Expr::Str(value)) => value.to_string(),
+        Some(_) => panic!("{key} is not a string"),
+        None => panic!("{} has no {key}", drv.name),
+    }
+}
+
+#[test]
+fn the_environment_of_a_package_keeps_its_name_and_is_built_locally() {
+    let (_, pkgs) = all_pkgs();
+    let hello = LazyDrv::clone(&pkgs.hello).into_drv();
+    let env = env_of(LazyDrv::clone(&pkgs.hello)).into_drv();
+    assert_eq!(env.name, hello.name);
+    assert_eq!(env.outputs, vec!["out"]);
+    // the same flags as a `MkShell`
+    let shell = MkShell::new(Stdenv::clone(&pkgs.stdenv), "shell").into_drv();
+    for key in ["PREFER_LOCAL_BUILD", "ALLOW_SUBSTITUTES"] {
+        assert_eq!(input(&env, key), input(&shell, key), "{key}");
+    }
+}
@@ -0,0 +1,195 @@
+mod common;
+
+use common::{Sandbox, assert_success, repo_file};
+use oxide_core::prelude::*;
+use oxide_pkgs::{lib::fake_hash, top_level::all_packages::all_pkgs};
+use std::{
+    fs,
+    panic::{self, AssertUnwindSafe},
+    process::Output,
+};
+
+// The host perl standing in for `Perl`, with its setup hook
+fn perl(sandbox: &Sandbox) -> String {
+    let hook = repo_file("src/pkgs/development/interpreters/perl/setup-hook.sh");
+    sandbox.sh(&format!(
+        "mkdir -p perl/bin perl/nix-support
+ln -sf /usr/bin/perl perl/bin/perl
+cp {} perl/nix-support/setup-hook",
+        hook.display()
+    ));
+    sandbox.path("perl").to_str().unwrap().to_string()
+}
+
+// A MakeMaker distribution of the module `name` with a test
+fn dist(sandbox: &Sandbox, name: &str, module: &str) {
+    sandbox.write(
+        &format!("{name}-src/Makefile.PL"),
+        &format!("use ExtUtils::MakeMaker;\nWriteMakefile(NAME => '{name}', VERSION => '0.1');\n"),
+    );
+    sandbox.write(&format!("{name}-src/lib/{name}.pm"), module);
+    sandbox.write(
+        &format!("{name}-src/t/load.t"),
+        &format!(
+            "use Test::More tests => 1;\nuse_ok('{name}');\n\
+             open(my $fh, '>', $ENV{{TEST_MARKER}}) or die;\n"
+        ),
+    );
+}
+
+fn build(sandbox: &Sandbox, name: &str, env: &[(&str, &str)]) -> Output {
+    let src = sandbox.path(&format!("{name}-src"));
+    let marker = sandbox.path(&format!("{name}-tested"));
+    let perl = perl(sandbox);
+    let mut vars = vec![
+        ("SRC", src.to_str().unwrap()),
+        ("UNPACK", "1"),
+        ("CONFIGURE", "1"),
+        ("BUILD", "1"),
+        ("CHECK", "1"),
+        ("INSTALL", "1"),
+        ("FIX", "1"),
+        ("DEPS_BUILD_HOST", &perl),
+        ("TEST_MARKER", marker.to_str().unwrap()),
+    ];
+    vars.extend_from_slice(env);
+    sandbox.build(&repo_file("src/pkgs/build/perl/builder.sh"), name, &vars)
+}
+
+const GREET: &str = "package Greet;\nsub hello { 'hello' }\n1;\n";
+const SHOUT: &str = "package Shout;\nuse Greet;\nsub shout { uc Greet::hello() }\n1;\n";
+
+#[test]
+fn builds_tests_and_installs_a_makemaker_dist() {
+    let sandbox = Sandbox::new("perl-makemaker");
+    dist(&sandbox, "Greet", GREET);
+    assert_success(&build(&sandbox, "Greet", &[]));
+    assert!(sandbox.path("Greet-tested").exists());
+    let output = sandbox.sh("find Greet/lib/perl5/site_perl -name Greet.pm
+PERL5LIB=Greet/lib/perl5/site_perl perl -MGreet -e 'print Greet::hello()'");
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let version = String::from_utf8(
+        sandbox
+            .sh("perl -MConfig -e 'print $Config{version}'")
+            .stdout,
+    )
+    .unwrap();
+    assert_eq!(
+        stdout,
+        format!("Greet/lib/perl5/site_perl/{version}/Greet.pm\nhello")
+    );
+}
+
+#[test]
+fn propagates_dependencies_and_wraps_perl() {
+    let sandbox = Sandbox::new("perl-propagate");
+    dist(&sandbox, "Greet", GREET);
+    assert_success(&build(&sandbox, "Greet", &[]));
+    let greet = sandbox.path("Greet");
+
+    // the test of Shout loads Greet through PERL5LIB
+    dist(&sandbox, "Shout", SHOUT);
+    assert_success(&build(
+        &sandbox,
+        "Shout",
+        &[("PROPAGATED_HOST_TARGET", greet.to_str().unwrap())],
+    ));
+    assert!(sandbox.path("Shout-tested").exists());
+
+    // same derivation as `PerlPlatform::with_packages`
+    let shout = sandbox.path("Shout");
+    let perl = perl(&sandbox);
+    assert_success(&sandbox.build(
+        &repo_file("src/pkgs/stdenv/generic/scripts/default-builder.sh"),
+        "env",
+        &[
+            ("DEPS_BUILD_HOST", &perl),
+            ("DEPS_HOST_TARGET", shout.to_str().unwrap()),
+            ("PERL", &perl),
+            ("INSTALL", "1"),
+            (
+                "INSTALL_PHASE",
+                r#"mkdir -p "$out/bin"
+cat > "$out/bin/perl" <<EOF
+#!$SHELL
+export PERL5LIB="$PERL5LIB\${PERL5LIB:+:\$PERL5LIB}"
+exec "$PERL/bin/perl" "\$@"
+EOF
+chmod +x "$out/bin/perl""#,
+            ),
+        ],
+    ));
+    let output = sandbox.sh("env -u PERL5LIB env/bin/perl -MShout -e 'print Shout::shout()'");
+    assert_eq!(String::from_utf8(output.stdout).unwrap(), "HELLO");
+}
+
+#[test]
+fn uses_build_pl_without_makefile_pl() {
+    let sandbox = Sandbox::new("perl-build-pl");
+    // a Module::Build stand-in recording how it is called
+    sandbox.write(
+        "Fake-src/Build.PL",
+        r#"open(my $log, '>', "$ENV{LOG}") or die;
+print $log "@ARGV\n";
+open(my $build, '>', 'Build') or die;
+print $build q{open(my $log, '>>', "$ENV{LOG}") or die; print $log ($ARGV[0] // 'build'), "\n";};
+"#,
+    );
+    let log = sandbox.path("build.log");
+    assert_success(&build(
+        &sandbox,
+        "Fake",
+        &[
+            ("LOG", log.to_str().unwrap()),
+            ("PERL_CONFIGURE_FLAGS", "--extra"),
+        ],
+    ));
+    let log = fs::read_to_string(log).unwrap();
+    let mut lines = log.lines();
+    let args = lines.next().unwrap();
+    assert!(args.starts_with("--installdirs site --install_path lib="));
+    assert!(args.ends_with(" --extra"));
+    assert_eq!(lines.collect::<Vec<_>>(), ["build", "test", "install"]);
+}
+
+#[test]
+fn fails_without_a_build_script() {
+    let sandbox = Sandbox::new("perl-no-build-script");
+    sandbox.write("Empty-src/README", "");
+    let output = build(&sandbox, "Empty", &[]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stdout).contains("neither Makefile.PL nor Build.PL"));
+}
+
+#[test]
+fn fetches_from_the_directory_of_the_author() {
+    let (_, pkgs) = all_pkgs();
+    let url = |author: &str| {
+        let drv = pkgs
+            .perl_platform
+            .fetch_cpan(author, "Try-Tiny-0.31.tar.gz", fake_hash())
+            .into_drv();
+        match &drv.inputs["url"] {
+            Expr::Str(url) => url.to_string(),
+            _ => panic!("url is not a string"),
+        }
+    };
+    assert_eq!(
+        url("ETHER"),
+        "mirror://cpan/authors/id/E/ET/ETHER/Try-Tiny-0.31.tar.gz"
+    );
+    assert_eq!(
+        url("AB"),
+        "mirror://cpan/authors/id/A/AB/AB/Try-Tiny-0.31.tar.gz"
+    );
+    // too short or not ASCII, slicing them used to panic without a reason
+    for author in ["E", "", "ÉTHER"] {
+        let err = panic::catch_unwind(AssertUnwindSafe(|| url(author))).unwrap_err();
+        assert!(
+            err.downcast_ref::<String>()
+                .unwrap()
+                .contains("is not a PAUSE id"),
+            "{author}"
+        );
+    }
+}
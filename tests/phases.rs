@@ -0,0 +1,307 @@
+mod common;
+
+use common::{Plain, assert_snapshot, render_drv, render_drv_changes};
+use oxide_core::prelude::*;
+use oxide_pkgs::{
+    lib::fake_hash,
+    stdenv::{StdenvBuilder, StdenvDrv, Wrap},
+    top_level::{all_packages::all_pkgs, registry::direct_drvs},
+};
+
+type Case = (&'static str, fn(StdenvBuilder) -> StdenvBuilder);
+
+fn stdenv() -> StdenvDrv {
+    let (_, pkgs) = all_pkgs();
+    StdenvDrv::clone(&pkgs.stdenv)
+}
+
+fn base(stdenv: &StdenvDrv) -> StdenvBuilder {
+    stdenv.make_derivation().name("pkg").version("1.0")
+}
+
+// The inputs each case sets on top of a bare derivation of the native
+// stdenv, a section each
+fn golden(name: &str, stdenv: StdenvDrv, cases: &[Case]) {
+    let bare = base(&self::stdenv()).build();
+    let text: Vec<String> = cases
+        .iter()
+        .map(|(case, f)| {
+            let drv = f(base(&stdenv)).build();
+            format!("# {case}\n{}", render_drv_changes(&bare, &drv))
+        })
+        .collect();
+    assert_snapshot(&format!("phases/{name}.txt"), &text.join("\n"));
+}
+
+fn cross(stdenv: StdenvDrv) -> StdenvDrv {
+    StdenvDrv {
+        host_platform: System::i686_linux,
+        target_platform: System::i686_linux,
+        ..stdenv
+    }
+}
+
+fn dep(name: &str) -> LazyDrv {
+    LazyDrv::new(Plain(DrvBuilder::new().name(name.to_string()).build()))
+}
+
+#[test]
+fn defaults() {
+    assert_snapshot("phases/defaults.txt", &render_drv(&base(&stdenv()).build()));
+}
+
+#[test]
+fn deps() {
+    golden(
+        "deps",
+        stdenv(),
+        &[
+            ("dep_*", |b| {
+                b.dep_build_build(dep("build-build"))
+                    .dep_build_host(dep("build-host"))
+                    .dep_build_host(dep("lib-1.0").out("dev"))
+                    .dep_build_target(dep("build-target"))
+                    .dep_host_host(dep("host-host"))
+                    .dep_host_target(dep("host-target"))
+                    .dep_target_target(dep("target-target"))
+            }),
+            ("propagated_*", |b| {
+                b.propagated_build_build(dep("build-build"))
+                    .propagated_build_host(dep("build-host"))
+                    .propagated_build_target(dep("build-target"))
+                    .propagated_host_host(dep("host-host"))
+                    .propagated_host_target(dep("host-target"))
+                    .propagated_target_target(dep("target-target"))
+            }),
+        ],
+    );
+}
+
+#[test]
+fn unpack() {
+    golden(
+        "unpack",
+        stdenv(),
+        &[
+            ("src_root, pre_unpack, unpack_phase, post_unpack", |b| {
+                b.src("pkg-1.0.tar.gz")
+                    .src_root("pkg-1.0/src")
+                    .pre_unpack("echo pre")
+                    .unpack_phase("tar -xf \"$SRC\"\nls")
+                    .post_unpack("echo post")
+            }),
+            ("dont_unpack", |b| b.dont_unpack()),
+        ],
+    );
+}
+
+#[test]
+fn patch() {
+    golden(
+        "patch",
+        stdenv(),
+        &[
+            (
+                "patch, patch_flags, pre_patch, patch_phase, post_patch",
+                |b| {
+                    b.patch("fix-build.patch")
+                        .patch("fix-tests.patch")
+                        .patch_flags("-p0")
+                        .pre_patch("echo pre")
+                        .patch_phase("patch -p1 < fix.patch")
+                        .post_patch("echo post")
+                },
+            ),
+            ("dont_patch", |b| b.dont_patch()),
+        ],
+    );
+}
+
+#[test]
+fn configure() {
+    golden(
+        "configure",
+        stdenv(),
+        &[
+            (
+                "configure_script, configure_flags, pre_configure, configure_phase, post_configure",
+                |b| {
+                    b.configure_script("../configure")
+                        .configure_flags("--disable-static --with-zlib")
+                        .pre_configure("echo pre")
+                        .configure_phase("./configure --prefix=$out")
+                        .post_configure("echo post")
+                },
+            ),
+            ("dont_configure", |b| b.dont_configure()),
+        ],
+    );
+}
+
+#[test]
+fn cmake() {
+    let cases: &[Case] = &[("cmake_flags, cmake_build_type, cmake_dir", |b| {
+        b.cmake_flags("-DBUILD_TESTING=OFF")
+            .cmake_build_type("Debug")
+            .cmake_dir("../llvm")
+    })];
+    golden("cmake", stdenv(), cases);
+    golden("cmake-cross", cross(stdenv()), cases);
+}
+
+#[test]
+fn meson() {
+    let cases: &[Case] = &[("meson_flags", |b| b.meson_flags("-Dtests=false"))];
+    golden("meson", stdenv(), cases);
+    golden("meson-cross", cross(stdenv()), cases);
+}
+
+#[test]
+fn build() {
+    golden(
+        "build",
+        stdenv(),
+        &[
+            (
+                "makefile, make_flags, pre_build, build_phase, post_build",
+                |b| {
+                    b.makefile("GNUmakefile")
+                        .make_flags("PREFIX=$out")
+                        .pre_build("echo pre")
+                        .build_phase("make all")
+                        .post_build("echo post")
+                },
+            ),
+            ("build_flags", |b| b.build_flags("V=1")),
+            ("dont_build", |b| b.dont_build()),
+        ],
+    );
+}
+
+#[test]
+fn check() {
+    golden(
+        "check",
+        stdenv(),
+        &[
+            ("do_check", |b| b.do_check()),
+            ("check_flags, pre_check, check_phase, post_check", |b| {
+                b.do_check()
+                    .check_flags("-j1")
+                    .pre_check("echo pre")
+                    .check_phase("make test")
+                    .post_check("echo post")
+            }),
+        ],
+    );
+}
+
+#[test]
+fn install() {
+    golden(
+        "install",
+        stdenv(),
+        &[
+            (
+                "install_targets, install_flags, pre_install, install_phase, post_install",
+                |b| {
+                    b.install_targets("install-strip")
+                        .install_flags("DESTDIR=")
+                        .pre_install("echo pre")
+                        .install_phase("make install")
+                        .post_install("echo post")
+                },
+            ),
+            ("dont_install", |b| b.dont_install()),
+        ],
+    );
+}
+
+#[test]
+fn fix() {
+    golden(
+        "fix",
+        stdenv(),
+        &[
+            ("pre_fix, fix_phase, post_fix", |b| {
+                b.pre_fix("echo pre")
+                    .fix_phase("strip $out/bin/*")
+                    .post_fix("echo post")
+            }),
+            ("dont_strip, dont_patch_elf", |b| {
+                b.dont_strip().dont_patch_elf()
+            }),
+            ("dont_fix", |b| b.dont_fix()),
+        ],
+    );
+}
+
+#[test]
+fn wrap() {
+    golden(
+        "wrap",
+        stdenv(),
+        &[("wrap_program", |b| {
+            b.wrap_program(
+                "bin/pkg",
+                Wrap::new()
+                    .set("LANG", "C")
+                    .set_default("EDITOR", "vi")
+                    .unset("PYTHONPATH")
+                    .prefix("PATH", "$out/libexec")
+                    .suffix_sep("XDG_DATA_DIRS", ";", "$out/share")
+                    .add_flags("--config \"$out/etc/pkg.conf\"")
+                    .run("echo 'starting'")
+                    .chdir("$out")
+                    .argv0("pkg"),
+            )
+            .wrap_program("bin/pkg-helper", Wrap::new().set("HELPER", "1"))
+        })],
+    );
+}
+
+#[test]
+fn install_check() {
+    golden(
+        "install_check",
+        stdenv(),
+        &[
+            ("do_install_check", |b| b.do_install_check()),
+            (
+                "install_check_flags, pre_install_check, install_check_phase, post_install_check",
+                |b| {
+                    b.do_install_check()
+                        .install_check_flags("-k")
+                        .pre_install_check("echo pre")
+                        .install_check_phase("$out/bin/pkg --version")
+                        .post_install_check("echo post")
+                },
+            ),
+        ],
+    );
+}
+
+// The packages fetchurl is built with, they keep the config scripts of
+// their tarballs and patch them to run in the sandbox
+#[test]
+fn fetchurl_bootstrap() {
+    let (_, pkgs) = all_pkgs();
+    let mut pending = vec![
+        pkgs.fetchurl
+            .fetch("https://example.org/pkg-1.0.tar.gz", fake_hash()),
+    ];
+    let mut drvs = Vec::new();
+    while let Some(drv) = pending.pop() {
+        let drv = drv.into_drv();
+        pending.extend(direct_drvs(&drv).into_iter().map(|(dep, _)| dep));
+        drvs.push(drv);
+    }
+    let text: Vec<String> = ["curl-8.14.1", "pkg-config-0.29.2"]
+        .iter()
+        .map(|name| {
+            let drv = drvs.iter().find(|drv| drv.name == *name).unwrap();
+            format!("# {name}\n{}", render_drv(drv))
+        })
+        .collect();
+    assert_snapshot("phases/fetchurl-bootstrap.txt", &text.join("\n"));
+}
@@ -0,0 +1,148 @@
+mod common;
+
+use common::{Sandbox, assert_success, repo_file};
+use std::fs;
+
+// A pkg-config stand-in printing where it would search
+const FAKE_PKG_CONFIG: &str = "#!/bin/sh\necho \"search=$PKG_CONFIG_PATH\"\n";
+
+// Same derivation as `PkgConfigWrapper`
+fn wrapper(sandbox: &Sandbox, out: &str, target_prefix: &str, suffix_salt: &str) -> String {
+    sandbox.write("pkg-config/bin/pkg-config", FAKE_PKG_CONFIG);
+    sandbox.write("pkg-config/share/aclocal/pkg.m4", "");
+    sandbox.sh("chmod +x pkg-config/bin/pkg-config");
+    let unwrapped = sandbox.path("pkg-config");
+    let hooks = format!(
+        "{} {}",
+        repo_file("src/pkgs/build/setup-hooks/role.bash").display(),
+        repo_file("src/pkgs/build/pkg_config/setup-hook.sh").display()
+    );
+    let wrapper = repo_file("src/pkgs/build/pkg_config/wrapper.sh");
+    assert_success(&sandbox.build(
+        &repo_file("src/pkgs/stdenv/generic/scripts/default-builder.sh"),
+        out,
+        &[
+            ("PKG_CONFIG_UNWRAPPED", unwrapped.to_str().unwrap()),
+            ("TARGET_PREFIX", target_prefix),
+            ("SUFFIX_SALT", suffix_salt),
+            ("WRAPPER", wrapper.to_str().unwrap()),
+            ("SETUP_HOOKS", &hooks),
+            ("INSTALL", "1"),
+            (
+                "INSTALL_PHASE",
+                r#"mkdir -p "$out/bin"
+substitute "$WRAPPER" "$out/bin/${TARGET_PREFIX}pkg-config" \
+    --replace-fail @shell@ "$SHELL" \
+    --replace-fail @pkg_config@ "$PKG_CONFIG_UNWRAPPED" \
+    --replace-fail @suffix_salt@ "$SUFFIX_SALT"
+chmod +x "$out/bin/${TARGET_PREFIX}pkg-config"
+ln -s "$PKG_CONFIG_UNWRAPPED/share" "$out/share""#,
+            ),
+            ("FIX", "1"),
+            (
+                "POST_FIX",
+                r#"substituteInPlace "$out/nix-support/setup-hook" \
+    --replace-fail @suffix_salt@ "$SUFFIX_SALT" \
+    --replace-fail @target_prefix@ "$TARGET_PREFIX""#,
+            ),
+        ],
+    ));
+    sandbox.path(out).to_str().unwrap().to_string()
+}
+
+// A library with a .pc file in `dir`
+fn library(sandbox: &Sandbox, name: &str, dir: &str) -> String {
+    sandbox.write(&format!("{name}/{dir}/{name}.pc"), "");
+    sandbox.path(name).to_str().unwrap().to_string()
+}
+
+// What the build sees of pkg-config
+fn build(sandbox: &Sandbox, env: &[(&str, &str)]) -> String {
+    let mut vars = vec![
+        ("STRICT_DEPS", "1"),
+        (
+            "BUILD_COMMAND",
+            r#"{
+    echo "PKG_CONFIG=${PKG_CONFIG-}"
+    echo "PKG_CONFIG_FOR_BUILD=${PKG_CONFIG_FOR_BUILD-}"
+    "$PKG_CONFIG"
+    [ -z "${PKG_CONFIG_FOR_BUILD:-}" ] || "$PKG_CONFIG_FOR_BUILD"
+} > "$out""#,
+        ),
+    ];
+    vars.extend_from_slice(env);
+    assert_success(&sandbox.build(
+        &repo_file("src/pkgs/stdenv/generic/scripts/default-builder.sh"),
+        "out",
+        &vars,
+    ));
+    fs::read_to_string(sandbox.path("out")).unwrap()
+}
+
+#[test]
+fn finds_the_pc_files_of_the_host_dependencies() {
+    let sandbox = Sandbox::new("pkg-config-native");
+    let pkg_config = wrapper(&sandbox, "wrapper", "", "x86_64_unknown_linux_gnu");
+    let libs = format!(
+        "{} {}",
+        library(&sandbox, "zlib", "lib/pkgconfig"),
+        library(&sandbox, "proto", "share/pkgconfig")
+    );
+    let output = build(
+        &sandbox,
+        &[
+            ("DEPS_BUILD_HOST", &pkg_config),
+            ("DEPS_HOST_TARGET", &libs),
+        ],
+    );
+    assert_eq!(
+        output,
+        format!(
+            "PKG_CONFIG=pkg-config\nPKG_CONFIG_FOR_BUILD=\nsearch={}/lib/pkgconfig:{}/share/pkgconfig\n",
+            sandbox.path("zlib").display(),
+            sandbox.path("proto").display()
+        )
+    );
+    assert!(sandbox.path("wrapper/share/aclocal/pkg.m4").exists());
+}
+
+#[test]
+fn keeps_the_build_and_host_dependencies_apart_when_cross_compiling() {
+    let sandbox = Sandbox::new("pkg-config-cross");
+    let native = wrapper(&sandbox, "native", "", "x86_64_unknown_linux_gnu");
+    let cross = wrapper(
+        &sandbox,
+        "cross",
+        "i686-unknown-linux-gnu-",
+        "i686_unknown_linux_gnu",
+    );
+    let build_lib = library(&sandbox, "build-lib", "lib/pkgconfig");
+    let host_lib = library(&sandbox, "host-lib", "lib/pkgconfig");
+    let build_deps = format!("{cross} {build_lib}");
+    let output = build(
+        &sandbox,
+        &[
+            ("DEPS_BUILD_BUILD", &native),
+            ("DEPS_BUILD_HOST", &build_deps),
+            ("DEPS_HOST_TARGET", &host_lib),
+        ],
+    );
+    assert_eq!(
+        output,
+        format!(
+            "PKG_CONFIG=i686-unknown-linux-gnu-pkg-config\nPKG_CONFIG_FOR_BUILD=pkg-config\n\
+             search={host_lib}/lib/pkgconfig\nsearch={build_lib}/lib/pkgconfig\n"
+        )
+    );
+}
+
+#[test]
+fn the_wrapper_keeps_the_environment_outside_of_builds() {
+    let sandbox = Sandbox::new("pkg-config-outside");
+    wrapper(&sandbox, "wrapper", "", "x86_64_unknown_linux_gnu");
+    let output = sandbox.sh("PKG_CONFIG_PATH=/somewhere wrapper/bin/pkg-config");
+    assert_eq!(
+        String::from_utf8(output.stdout).unwrap(),
+        "search=/somewhere\n"
+    );
+}
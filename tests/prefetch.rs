@@ -0,0 +1,169 @@
+mod common;
+
+use base64::{Engine, engine::general_purpose::URL_SAFE_NO_PAD};
+use common::{Sandbox, assert_success, serve};
+use sha2::{Digest, Sha512};
+use std::{
+    collections::HashMap,
+    fs,
+    process::{Command, Output},
+};
+
+const TARBALL: &[u8] = b"pretend this is hello-2.12.1.tar.gz";
+
+fn expected_hash(content: &[u8]) -> String {
+    format!("sha512:{}", URL_SAFE_NO_PAD.encode(Sha512::digest(content)))
+}
+
+fn prefetch(args: &[&str], env: &[(&str, &str)]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_prefetch"))
+        .args(args)
+        .envs(env.iter().copied())
+        .output()
+        .unwrap()
+}
+
+fn stdout(output: &Output) -> String {
+    String::from_utf8_lossy(&output.stdout).trim().to_string()
+}
+
+fn server() -> String {
+    serve(HashMap::from([(
+        "/gnu/hello/hello-2.12.1.tar.gz".to_string(),
+        TARBALL.to_vec(),
+    )]))
+}
+
+#[test]
+fn prints_the_hash_of_a_url() {
+    let base = server();
+    let output = prefetch(&[&format!("{base}/gnu/hello/hello-2.12.1.tar.gz")], &[]);
+    assert_success(&output);
+    assert_eq!(stdout(&output), expected_hash(TARBALL));
+
+    let output = prefetch(&[&format!("{base}/gnu/missing.tar.gz")], &[]);
+    assert!(!output.status.success());
+}
+
+#[test]
+fn tries_every_mirror_of_a_site() {
+    let base = server();
+    let mirrors = format!("{base}/broken/ {base}/gnu/");
+    let output = prefetch(
+        &["mirror://gnu/hello/hello-2.12.1.tar.gz"],
+        &[("OXIDE_MIRRORS_gnu", &mirrors)],
+    );
+    assert_success(&output);
+    assert_eq!(stdout(&output), expected_hash(TARBALL));
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains(&format!("trying {base}/broken/hello/hello-2.12.1.tar.gz")));
+
+    let output = prefetch(&["mirror://nowhere/hello.tar.gz"], &[]);
+    assert!(!output.status.success());
+}
+
+#[test]
+fn rewrites_the_hash_of_a_recipe() {
+    let sandbox = Sandbox::new("prefetch-rewrite");
+    let url = format!("{}/gnu/hello/hello-2.12.1.tar.gz", server());
+    let recipe = sandbox.write(
+        "hello.rs",
+        "fetchurl.fetch(\n    \"mirror://gnu/hello/hello-2.12.1.tar.gz\",\n    hash!(\n        \"sha512:old\"\n    ),\n)\n",
+    );
+    let output = prefetch(&[&url, "--rewrite", recipe.to_str().unwrap()], &[]);
+    assert_success(&output);
+    let hash = expected_hash(TARBALL);
+    assert_eq!(
+        fs::read_to_string(&recipe).unwrap(),
+        format!(
+            "fetchurl.fetch(\n    \"mirror://gnu/hello/hello-2.12.1.tar.gz\",\n    hash!(\n        \"{hash}\"\n    ),\n)\n"
+        )
+    );
+
+    let recipe = sandbox.write(
+        "two.rs",
+        "hash!(\"sha512:first\")\nhash!(\"sha512:second\")\n",
+    );
+    let recipe = recipe.to_str().unwrap();
+    let output = prefetch(&[&url, "--rewrite", recipe], &[]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("--old"));
+
+    let output = prefetch(&[&url, "--rewrite", recipe, "--old", "sha512:second"], &[]);
+    assert_success(&output);
+    assert_eq!(
+        fs::read_to_string(recipe).unwrap(),
+        format!("hash!(\"sha512:first\")\nhash!(\"{hash}\")\n")
+    );
+}
+
+#[test]
+fn rewrites_fake_hash_into_a_hash_literal() {
+    let sandbox = Sandbox::new("prefetch-rewrite-fake");
+    let url = format!("{}/gnu/hello/hello-2.12.1.tar.gz", server());
+    let hash = expected_hash(TARBALL);
+    let recipe = sandbox.write(
+        "hello.rs",
+        "use crate::lib::fake_hash;\nfetchurl.fetch(url, fake_hash())\n",
+    );
+    let output = prefetch(&[&url, "--rewrite", recipe.to_str().unwrap()], &[]);
+    assert_success(&output);
+    assert_eq!(
+        fs::read_to_string(&recipe).unwrap(),
+        format!("use crate::lib::fake_hash;\nfetchurl.fetch(url, hash!(\"{hash}\"))\n")
+    );
+
+    let recipe = sandbox.write(
+        "two.rs",
+        "pub fn fake_hash() -> Hash {}\nhash!(\"sha512:first\")\nfake_hash()\nmy_fake_hash()\n",
+    );
+    let recipe = recipe.to_str().unwrap();
+    let output = prefetch(&[&url, "--rewrite", recipe], &[]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("--old"));
+
+    let output = prefetch(&[&url, "--rewrite", recipe, "--old", "fake_hash()"], &[]);
+    assert_success(&output);
+    assert_eq!(
+        fs::read_to_string(recipe).unwrap(),
+        format!(
+            "pub fn fake_hash() -> Hash {{}}\nhash!(\"sha512:first\")\nhash!(\"{hash}\")\nmy_fake_hash()\n"
+        )
+    );
+}
+
+#[test]
+fn hashes_git_checkouts_without_dot_git() {
+    let sandbox = Sandbox::new("prefetch-git");
+    sandbox.sh(
+        r#"export GIT_CONFIG_NOSYSTEM=1 HOME="$PWD" GIT_AUTHOR_NAME=test GIT_AUTHOR_EMAIL=test@example.com
+export GIT_COMMITTER_NAME=test GIT_COMMITTER_EMAIL=test@example.com
+git init -q repo
+cd repo
+echo hello > README
+printf '#!/bin/sh\n' > run
+chmod +x run
+ln -s README LINK
+git add -A
+git commit -qm init
+git tag v1.0
+git rev-parse HEAD > ../rev
+chmod -x run
+git commit -qam 'not executable'"#,
+    );
+    let url = format!("file://{}", sandbox.path("repo").display());
+    let rev = fs::read_to_string(sandbox.path("rev")).unwrap();
+    let git = |reference: &[&str]| {
+        let mut args = vec!["--git", url.as_str()];
+        args.extend_from_slice(reference);
+        let output = prefetch(&args, &[]);
+        assert_success(&output);
+        stdout(&output)
+    };
+
+    let by_rev = git(&["--rev", rev.trim()]);
+    assert!(by_rev.starts_with("sha512:"));
+    assert_eq!(by_rev, git(&["--tag", "v1.0"]));
+    // the executable bit is part of the hash
+    assert_ne!(by_rev, git(&["--rev", "HEAD"]));
+}
@@ -0,0 +1,261 @@
+mod common;
+
+use common::{Sandbox, assert_success, repo_file};
+use oxide_core::prelude::*;
+use oxide_pkgs::top_level::all_packages::all_pkgs;
+use std::{
+    fs,
+    os::unix::fs::PermissionsExt,
+    path::PathBuf,
+    process::{Command, Output},
+};
+
+const SITE_PACKAGES: &str = "lib/python3.13/site-packages";
+
+// A PEP 517 backend shipped in the project through backend-path,
+// packs module.py as the package named in NAME
+const BACKEND: &str = r##"import os, zipfile
+
+def build_wheel(wheel_directory, config_settings=None, metadata_directory=None):
+    name, version = open("NAME").read().split()
+    dist_info = f"{name}-{version}.dist-info"
+    wheel = f"{name}-{version}-py3-none-any.whl"
+    files = {
+        f"{name}/__init__.py": open("module.py").read(),
+        f"{dist_info}/METADATA": f"Metadata-Version: 2.1\nName: {name}\nVersion: {version}\n",
+        f"{dist_info}/WHEEL": "Wheel-Version: 1.0\nRoot-Is-Purelib: true\nTag: py3-none-any\n",
+        f"{dist_info}/entry_points.txt": f"[console_scripts]\n{name} = {name}:main\n",
+        f"{name}-{version}.data/scripts/{name}-script": f"#!python\nimport {name}\n{name}.main()\n",
+    }
+    if config_settings:
+        files[f"{name}/settings.txt"] = repr(sorted(config_settings.items()))
+    with zipfile.ZipFile(os.path.join(wheel_directory, wheel), "w") as zf:
+        for path, content in files.items():
+            zf.writestr(path, content)
+        zf.writestr(f"{dist_info}/RECORD", "")
+    return wheel
+"##;
+
+fn project(sandbox: &Sandbox, name: &str, module: &str) -> PathBuf {
+    sandbox.write(
+        &format!("{name}-src/pyproject.toml"),
+        "[build-system]\nrequires = []\nbuild-backend = \"backend\"\nbackend-path = [\"_build\"]\n",
+    );
+    sandbox.write(&format!("{name}-src/_build/backend.py"), BACKEND);
+    sandbox.write(&format!("{name}-src/NAME"), &format!("{name} 0.1.0\n"));
+    sandbox
+        .write(&format!("{name}-src/module.py"), module)
+        .parent()
+        .unwrap()
+        .to_path_buf()
+}
+
+// Same derivation as the hooks of `PythonPlatform::new`
+fn hook(sandbox: &Sandbox, name: &str, script: Option<&str>) -> String {
+    let setup_hook = repo_file(&format!("src/pkgs/build/python/{name}.sh"));
+    let mut env = vec![
+        ("SETUP_HOOK", setup_hook.to_str().unwrap().to_string()),
+        ("FIX", "1".to_string()),
+        (
+            "POST_FIX",
+            r#"substituteInPlace "$out/nix-support/setup-hook" --replace-quiet @out@ "$out""#
+                .to_string(),
+        ),
+    ];
+    if let Some(script) = script {
+        let path = repo_file(&format!("src/pkgs/build/python/{script}"));
+        env.push(("SCRIPT", path.to_str().unwrap().to_string()));
+        env.push(("INSTALL", "1".to_string()));
+        env.push((
+            "INSTALL_PHASE",
+            format!(r#"install -D -m644 "$SCRIPT" "$out/share/python/{script}""#),
+        ));
+    }
+    let env: Vec<(&str, &str)> = env.iter().map(|(k, v)| (*k, v.as_str())).collect();
+    assert_success(&sandbox.build(
+        &repo_file("src/pkgs/stdenv/generic/scripts/default-builder.sh"),
+        name,
+        &env,
+    ));
+    sandbox.path(name).to_str().unwrap().to_string()
+}
+
+// The host python standing in for `Python3`, with its setup hook
+fn python3(sandbox: &Sandbox) -> String {
+    let hook = repo_file("src/pkgs/development/interpreters/python/setup-hook.sh");
+    sandbox.sh(&format!(
+        r#"mkdir -p python3/bin python3/nix-support
+ln -s "$(python3 -c 'import sys; print(sys.executable)')" python3/bin/python3
+sed 's|@site_packages@|{SITE_PACKAGES}|' {} > python3/nix-support/setup-hook"#,
+        hook.display()
+    ));
+    sandbox.path("python3").to_str().unwrap().to_string()
+}
+
+fn tools(sandbox: &Sandbox) -> String {
+    [
+        python3(sandbox),
+        hook(sandbox, "wheel-build-hook", Some("build-wheel.py")),
+        hook(sandbox, "install-hook", Some("install-wheel.py")),
+        hook(sandbox, "imports-check-hook", None),
+    ]
+    .join(" ")
+}
+
+fn build(sandbox: &Sandbox, name: &str, tools: &str, env: &[(&str, &str)]) -> Output {
+    let src = sandbox.path(&format!("{name}-src"));
+    let mut vars = vec![
+        ("SRC", src.to_str().unwrap()),
+        ("UNPACK", "1"),
+        ("BUILD", "1"),
+        ("INSTALL", "1"),
+        ("FIX", "1"),
+        ("DEPS_BUILD_HOST", tools),
+        ("PYTHON_IMPORTS_CHECK", name),
+    ];
+    vars.extend_from_slice(env);
+    sandbox.build(
+        &repo_file("src/pkgs/stdenv/generic/scripts/default-builder.sh"),
+        name,
+        &vars,
+    )
+}
+
+#[test]
+fn builds_and_installs_a_wheel() {
+    let sandbox = Sandbox::new("python-wheel");
+    let tools = tools(&sandbox);
+    project(&sandbox, "greet", "def main():\n    print('hello')\n");
+    assert_success(&build(
+        &sandbox,
+        "greet",
+        &tools,
+        &[("PYTHON_CONFIG_SETTINGS", "mode=fast")],
+    ));
+
+    let site = sandbox.path(&format!("greet/{SITE_PACKAGES}"));
+    assert!(site.join("greet/__pycache__").is_dir());
+    assert!(site.join("greet-0.1.0.dist-info/METADATA").exists());
+    assert_eq!(
+        fs::read_to_string(site.join("greet/settings.txt")).unwrap(),
+        "[('mode', 'fast')]"
+    );
+    // the console script and the script of the .data directory
+    // both run the interpreter that installed them
+    assert_eq!(
+        sandbox.run(&format!("PYTHONPATH=greet/{SITE_PACKAGES} greet/bin/greet")),
+        "hello\n"
+    );
+    assert_eq!(
+        sandbox.run(&format!(
+            "PYTHONPATH=greet/{SITE_PACKAGES} greet/bin/greet-script"
+        )),
+        "hello\n"
+    );
+}
+
+#[test]
+fn propagates_dependencies_to_dependents() {
+    let sandbox = Sandbox::new("python-propagate");
+    let tools = tools(&sandbox);
+    project(&sandbox, "greet", "def main():\n    print('hello')\n");
+    assert_success(&build(&sandbox, "greet", &tools, &[]));
+    let greet = sandbox.path("greet");
+
+    project(
+        &sandbox,
+        "app",
+        "import greet\n\ndef main():\n    greet.main()\n",
+    );
+    assert_success(&build(
+        &sandbox,
+        "app",
+        &tools,
+        &[("PROPAGATED_HOST_TARGET", greet.to_str().unwrap())],
+    ));
+    assert_eq!(
+        fs::read_to_string(sandbox.path("app/nix-support/propagated-build-inputs")).unwrap(),
+        format!("{}\n", greet.display())
+    );
+
+    // depending on app alone is enough to import greet
+    let app = sandbox.path("app");
+    let python = sandbox.path("python3");
+    let output = sandbox.build(
+        &repo_file("src/pkgs/stdenv/generic/scripts/default-builder.sh"),
+        "user",
+        &[
+            ("DEPS_BUILD_HOST", python.to_str().unwrap()),
+            ("DEPS_HOST_TARGET", app.to_str().unwrap()),
+            ("BUILD_COMMAND", "python3 -c 'import app; app.main()'"),
+        ],
+    );
+    assert_success(&output);
+    assert!(String::from_utf8_lossy(&output.stdout).contains("hello\n"));
+}
+
+#[test]
+fn imports_check_catches_missing_dependencies() {
+    let sandbox = Sandbox::new("python-imports-check");
+    let tools = tools(&sandbox);
+    project(&sandbox, "app", "import greet\n");
+    let output = build(&sandbox, "app", &tools, &[]);
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("No module named 'greet'"), "{stderr}");
+}
+
+// A configure script building a python3 linked to libpython3.so with
+// the LDFLAGS of the environment, like the one of CPython
+const CONFIGURE: &str = r#"#!/bin/sh
+prefix=${1#--prefix=}
+cat > Makefile <<MAKEFILE
+install:
+	mkdir -p $prefix/bin $prefix/lib
+	cc -shared -fPIC -o $prefix/lib/libpython3.so lib.c
+	cc -o $prefix/bin/python3 main.c -L$prefix/lib -lpython3 $LDFLAGS
+MAKEFILE
+"#;
+
+fn input<'a>(drv: &'a Drv, key: &str) -> &'a str {
+    match &drv.inputs[key] {
+        Expr::Str(value) => value,
+        _ => panic!("{key} is not a string"),
+    }
+}
+
+#[test]
+fn the_interpreter_finds_libpython_in_its_output() {
+    let (_, pkgs) = all_pkgs();
+    let drv = pkgs.python3.into_drv();
+    let sandbox = Sandbox::new("python-rpath");
+    sandbox.write("cpython-src/lib.c", "int answer(void) { return 42; }\n");
+    sandbox.write(
+        "cpython-src/main.c",
+        "int answer(void);\nint main(void) { return answer() == 42 ? 0 : 1; }\n",
+    );
+    let configure = sandbox.write("cpython-src/configure", CONFIGURE);
+    fs::set_permissions(&configure, fs::Permissions::from_mode(0o755)).unwrap();
+
+    let src = sandbox.path("cpython-src");
+    let output = sandbox.build(
+        &repo_file("src/pkgs/stdenv/generic/scripts/default-builder.sh"),
+        "cpython",
+        &[
+            ("SRC", src.to_str().unwrap()),
+            ("UNPACK", "1"),
+            ("CONFIGURE", "1"),
+            ("INSTALL", "1"),
+            ("PRE_CONFIGURE", input(&drv, "PRE_CONFIGURE")),
+            ("CONFIGURE_FLAGS", input(&drv, "CONFIGURE_FLAGS")),
+            ("DONT_ADD_DISABLED_EPTRACK", "1"),
+        ],
+    );
+    assert_success(&output);
+
+    let status = Command::new(sandbox.path("cpython/bin/python3"))
+        .env_clear()
+        .status()
+        .unwrap();
+    assert!(status.success());
+}
@@ -0,0 +1,114 @@
+mod common;
+
+use common::assert_success;
+use oxide_pkgs::{
+    lib::parse_drv_name,
+    top_level::{all_meta::all_meta, all_packages::all_pkgs, all_tests::split_test_attr},
+};
+use serde_json::Value;
+use std::{
+    collections::BTreeSet,
+    process::{Command, Output},
+};
+
+fn registry(args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_registry"))
+        .args(args)
+        .output()
+        .unwrap()
+}
+
+fn json(args: &[&str]) -> Value {
+    let output = registry(args);
+    assert_success(&output);
+    serde_json::from_slice(&output.stdout).unwrap()
+}
+
+fn attrs(pkgs: &Value) -> Vec<&str> {
+    pkgs.as_array()
+        .unwrap()
+        .iter()
+        .map(|pkg| pkg["attr"].as_str().unwrap())
+        .collect()
+}
+
+#[test]
+fn splits_versions_off_drv_names() {
+    assert_eq!(parse_drv_name("hello-2.12.1"), ("hello", Some("2.12.1")));
+    assert_eq!(
+        parse_drv_name("pkg-config-unwrapped-0.29.2"),
+        ("pkg-config-unwrapped", Some("0.29.2"))
+    );
+    assert_eq!(
+        parse_drv_name("gnu-config-2024-01-01"),
+        ("gnu-config", Some("2024-01-01"))
+    );
+    assert_eq!(parse_drv_name("make-wrapper"), ("make-wrapper", None));
+}
+
+#[test]
+fn every_package_has_meta() {
+    let (pkgs, _) = all_pkgs();
+    let pkgs: BTreeSet<&str> = pkgs
+        .keys()
+        .map(String::as_str)
+        .filter(|attr| split_test_attr(attr).is_none())
+        .collect();
+    let meta = all_meta();
+    let meta: BTreeSet<&str> = meta.keys().copied().collect();
+    assert_eq!(pkgs, meta);
+}
+
+#[test]
+fn lists_every_package_sorted() {
+    let (pkgs, _) = all_pkgs();
+    let mut expected: Vec<&str> = pkgs
+        .keys()
+        .map(String::as_str)
+        .filter(|attr| split_test_attr(attr).is_none())
+        .collect();
+    expected.sort();
+    assert_eq!(attrs(&json(&["--json", "list"])), expected);
+
+    let output = registry(&["list"]);
+    assert_success(&output);
+    let table = String::from_utf8(output.stdout).unwrap();
+    let mut lines = table.lines();
+    assert!(lines.next().unwrap().starts_with("ATTR"));
+    assert_eq!(lines.count(), expected.len());
+}
+
+#[test]
+fn searches_names_and_descriptions() {
+    assert_eq!(attrs(&json(&["--json", "search", "^zli"])), vec!["zlib"]);
+    assert_eq!(
+        attrs(&json(&["--json", "search", "(?i)data compression"])),
+        vec!["zlib"]
+    );
+    assert!(attrs(&json(&["--json", "search", "^no such package$"])).is_empty());
+    assert!(!registry(&["search", "("]).status.success());
+}
+
+#[test]
+fn shows_a_package() {
+    let zlib = json(&["show", "--json", "zlib"]);
+    assert_eq!(zlib["name"], "zlib");
+    assert_eq!(zlib["version"], "1.3.1");
+    assert_eq!(zlib["outputs"], serde_json::json!(["out", "dev", "static"]));
+    assert_eq!(zlib["meta"]["license"], "Zlib");
+    let deps: Vec<&str> = zlib["deps"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|dep| dep["name"].as_str().unwrap())
+        .collect();
+    assert!(deps.contains(&"zlib-1.3.1.tar.gz"), "{deps:?}");
+    assert_eq!(
+        zlib["tests"],
+        serde_json::json!(["zlib.tests.linking", "zlib.tests.pkg-config"])
+    );
+
+    let output = registry(&["show", "no-such-package"]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("no package named"));
+}
@@ -0,0 +1,332 @@
+mod common;
+
+use common::{Sandbox, assert_success, fingerprint, repo_file};
+use oxide_core::prelude::*;
+use oxide_pkgs::{
+    build::rust::RustPackageArgs,
+    lib::fake_hash,
+    top_level::{all_packages::all_pkgs, registry::direct_drvs},
+};
+use std::{
+    fs,
+    panic::{self, AssertUnwindSafe},
+    process::{Command, Output},
+};
+
+// The host toolchain stands in for `RustBin`, passed like a dep_build_host
+fn sysroot() -> String {
+    let output = Command::new("rustc")
+        .args(["--print", "sysroot"])
+        .output()
+        .unwrap();
+    assert_success(&output);
+    String::from_utf8(output.stdout).unwrap().trim().to_string()
+}
+
+fn host() -> String {
+    let output = Command::new("rustc").arg("-vV").output().unwrap();
+    assert_success(&output);
+    String::from_utf8(output.stdout)
+        .unwrap()
+        .lines()
+        .find_map(|line| line.strip_prefix("host: "))
+        .unwrap()
+        .to_string()
+}
+
+// A binary crate with a git dependency served from the sandbox
+fn make_project(sandbox: &Sandbox) {
+    sandbox.write(
+        "greet/Cargo.toml",
+        "[package]\nname = \"greet\"\nversion = \"0.1.0\"\nedition = \"2021\"\n",
+    );
+    sandbox.write(
+        "greet/src/lib.rs",
+        "pub fn greeting() -> &'static str {\n    \"hello\"\n}\n",
+    );
+    sandbox.sh(
+        r#"export GIT_CONFIG_NOSYSTEM=1 HOME="$PWD" GIT_AUTHOR_NAME=test GIT_AUTHOR_EMAIL=test@example.com
+export GIT_COMMITTER_NAME=test GIT_COMMITTER_EMAIL=test@example.com
+git -C greet init -q
+git -C greet add -A
+git -C greet commit -qm init"#,
+    );
+    sandbox.write(
+        "app/Cargo.toml",
+        &format!(
+            "[package]\nname = \"app\"\nversion = \"0.1.0\"\nedition = \"2021\"\n\n\
+             [dependencies]\ngreet = {{ git = \"file://{}\" }}\n\n\
+             [features]\nloud = []\n",
+            sandbox.path("greet").display()
+        ),
+    );
+    sandbox.write(
+        "app/src/main.rs",
+        r#"fn message() -> String {
+    let greeting = greet::greeting();
+    if cfg!(feature = "loud") {
+        greeting.to_uppercase()
+    } else {
+        greeting.to_string()
+    }
+}
+
+fn main() {
+    println!("{}", message());
+}
+
+#[test]
+fn greets() {
+    assert!(message().eq_ignore_ascii_case("hello"));
+    std::fs::write(std::env::var("TEST_MARKER").unwrap(), "").unwrap();
+}
+"#,
+    );
+    sandbox.sh(r#"cd app && CARGO_HOME="$PWD/../cargo-home" cargo generate-lockfile --quiet"#);
+}
+
+fn vendor(sandbox: &Sandbox, out: &str) -> Output {
+    let src = sandbox.path("app");
+    let normalise = repo_file("src/pkgs/build/fetchers/normalise.sh");
+    sandbox.build(
+        &repo_file("src/pkgs/build/rust/vendor.sh"),
+        out,
+        &[
+            ("SRC", src.to_str().unwrap()),
+            ("UNPACK", "1"),
+            ("NORMALISE", normalise.to_str().unwrap()),
+            ("DEPS_BUILD_HOST", &sysroot()),
+        ],
+    )
+}
+
+fn build(sandbox: &Sandbox, vendor: &str, out: &str, features: &str) -> Output {
+    let src = sandbox.path("app");
+    let vendor = sandbox.path(vendor);
+    let marker = sandbox.path(&format!("{out}-tested"));
+    sandbox.build(
+        &repo_file("src/pkgs/build/rust/builder.sh"),
+        out,
+        &[
+            ("SRC", src.to_str().unwrap()),
+            ("UNPACK", "1"),
+            ("CONFIGURE", "1"),
+            ("BUILD", "1"),
+            ("CHECK", "1"),
+            ("INSTALL", "1"),
+            ("CARGO_VENDOR", vendor.to_str().unwrap()),
+            ("CARGO_BUILD_TARGET", &host()),
+            ("CARGO_FEATURES", features),
+            ("TEST_MARKER", marker.to_str().unwrap()),
+            ("DEPS_BUILD_HOST", &sysroot()),
+        ],
+    )
+}
+
+#[test]
+fn builds_tests_and_installs_offline() {
+    let sandbox = Sandbox::new("rust-build");
+    make_project(&sandbox);
+    assert_success(&vendor(&sandbox, "vendor"));
+    assert!(
+        sandbox
+            .path("vendor/vendor/greet-0.1.0/src/lib.rs")
+            .exists()
+    );
+    assert!(sandbox.path("vendor/Cargo.lock").exists());
+
+    // the git dependency can only come from the vendored crates
+    sandbox.sh("rm -rf greet cargo-home");
+    assert_success(&build(&sandbox, "vendor", "out", ""));
+    assert!(sandbox.path("out-tested").exists());
+    assert_eq!(sandbox.run("out/bin/app"), "hello\n");
+
+    assert_success(&build(&sandbox, "vendor", "out-loud", "loud"));
+    assert_eq!(sandbox.run("out-loud/bin/app"), "HELLO\n");
+}
+
+#[test]
+fn vendoring_is_reproducible() {
+    let sandbox = Sandbox::new("rust-vendor");
+    make_project(&sandbox);
+    assert_success(&vendor(&sandbox, "vendor-a"));
+    assert_success(&vendor(&sandbox, "vendor-b"));
+    let vendor_b: Vec<String> = fingerprint(&sandbox.path("vendor-b"))
+        .into_iter()
+        .map(|line| line.replace("vendor-b", "vendor-a"))
+        .collect();
+    assert_eq!(fingerprint(&sandbox.path("vendor-a")), vendor_b);
+}
+
+#[test]
+fn refuses_a_stale_cargo_lock() {
+    let sandbox = Sandbox::new("rust-stale-lock");
+    make_project(&sandbox);
+    assert_success(&vendor(&sandbox, "vendor"));
+    sandbox.sh("echo '# changed' >> app/Cargo.lock");
+    let output = build(&sandbox, "vendor", "out", "");
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stdout).contains("update cargo_hash"));
+}
+
+fn input(drv: &Drv, key: &str) -> String {
+    match drv.inputs.get(key) {
+        Some(Expr::Str(value)) => value.to_string(),
+        Some(_) => panic!("{key} is not a string"),
+        None => panic!("{} has no {key}", drv.name),
+    }
+}
+
+// The toolchain `build_rust_package` builds with for `target`
+fn toolchain(target: Option<&str>) -> Drv {
+    let (_, pkgs) = all_pkgs();
+    let args = RustPackageArgs::new("pkg", "1.0", "src", fake_hash());
+    let args = match target {
+        Some(target) => args.target(target.to_string()),
+        None => args,
+    };
+    let drv = pkgs.rust_platform.build_rust_package(args).build();
+    direct_drvs(&drv)
+        .into_iter()
+        .map(|(dep, _)| dep.into_drv())
+        .find(|dep| dep.name.starts_with("rust-bin-"))
+        .unwrap()
+}
+
+fn rust_stds(drv: &Drv) -> Vec<String> {
+    direct_drvs(drv)
+        .into_iter()
+        .map(|(dep, _)| dep.into_drv().name)
+        .filter(|name| name.starts_with("rust-std-"))
+        .collect()
+}
+
+#[test]
+fn installs_the_standard_library_of_the_target() {
+    let native = toolchain(None);
+    assert_eq!(native.name, "rust-bin-1.90.0");
+    assert!(rust_stds(&native).is_empty());
+    assert_eq!(
+        rust_stds(&toolchain(Some("i686-unknown-linux-gnu"))),
+        vec!["rust-std-1.90.0-i686-unknown-linux-gnu.tar.xz"]
+    );
+    assert!(rust_stds(&toolchain(Some("x86_64-unknown-linux-gnu"))).is_empty());
+    let err = panic::catch_unwind(AssertUnwindSafe(|| {
+        toolchain(Some("riscv64gc-unknown-none-elf"))
+    }))
+    .map(|_| ())
+    .unwrap_err();
+    assert!(
+        err.downcast_ref::<String>()
+            .unwrap()
+            .contains("no rust-std for riscv64gc-unknown-none-elf")
+    );
+}
+
+// Records the changes instead of making them, the scripts of the
+// distribution are not ELF files and the libraries have no interpreter
+const FAKE_PATCHELF: &str = r#"#!/bin/sh
+case "$1" in
+--print-rpath)
+    case "$2" in
+    *rust-gdb) exit 1 ;;
+    *.so) ;;
+    *) echo '$ORIGIN/../lib' ;;
+    esac
+    ;;
+--print-interpreter)
+    case "$2" in
+    *.so) exit 1 ;;
+    *) echo /lib64/ld-linux-x86-64.so.2 ;;
+    esac
+    ;;
+*) echo "$*" >> "$LOG" ;;
+esac
+"#;
+
+// An install.sh of the distribution, installing `files`
+fn installer(sandbox: &Sandbox, dir: &str, files: &[&str]) {
+    let script = files
+        .iter()
+        .map(|file| {
+            format!("mkdir -p \"$(dirname \"$prefix/{file}\")\"\ntouch \"$prefix/{file}\"\n")
+        })
+        .collect::<String>();
+    sandbox.write(
+        &format!("{dir}/install.sh"),
+        &format!(
+            "#!/bin/sh\nfor arg; do\n    case \"$arg\" in\n    --prefix=*) prefix=${{arg#--prefix=}} ;;\n    esac\ndone\n{script}"
+        ),
+    );
+    sandbox.sh(&format!("chmod +x {dir}/install.sh"));
+}
+
+#[test]
+fn patches_the_loader_and_the_libraries_of_the_binaries() {
+    let drv = toolchain(Some("i686-unknown-linux-gnu"));
+    let sandbox = Sandbox::new("rust-bin-patchelf");
+    let target = "x86_64-unknown-linux-gnu";
+    installer(
+        &sandbox,
+        "src",
+        &[
+            "bin/rustc",
+            "bin/rust-gdb",
+            "lib/librustc_driver.so",
+            &format!("lib/rustlib/{target}/bin/rust-lld"),
+            &format!("lib/rustlib/{target}/bin/gcc-ld/ld.lld"),
+        ],
+    );
+    installer(
+        &sandbox,
+        "rust-std-1.90.0-i686-unknown-linux-gnu",
+        &["lib/rustlib/i686-unknown-linux-gnu/lib/libstd.rlib"],
+    );
+    sandbox.sh("tar -cJf rust-std.tar.xz rust-std-1.90.0-i686-unknown-linux-gnu");
+    sandbox.write("patchelf/bin/patchelf", FAKE_PATCHELF);
+    sandbox.sh("chmod +x patchelf/bin/patchelf");
+
+    let (src, rust_std, patchelf, log) = (
+        sandbox.path("src"),
+        sandbox.path("rust-std.tar.xz"),
+        sandbox.path("patchelf"),
+        sandbox.path("patchelf.log"),
+    );
+    assert_success(&sandbox.build(
+        &repo_file("src/pkgs/stdenv/generic/scripts/default-builder.sh"),
+        "rust",
+        &[
+            ("SRC", src.to_str().unwrap()),
+            ("UNPACK", "1"),
+            ("INSTALL", "1"),
+            ("INSTALL_PHASE", &input(&drv, "INSTALL_PHASE")),
+            ("RUST_TARGET", &input(&drv, "RUST_TARGET")),
+            ("RUST_STDS", rust_std.to_str().unwrap()),
+            ("GLIBC", "/glibc"),
+            ("DYNAMIC_LINKER", &input(&drv, "DYNAMIC_LINKER")),
+            ("DEPS_BUILD_HOST", patchelf.to_str().unwrap()),
+            ("LOG", log.to_str().unwrap()),
+        ],
+    ));
+    assert!(
+        sandbox
+            .path("rust/lib/rustlib/i686-unknown-linux-gnu/lib/libstd.rlib")
+            .exists()
+    );
+    let out = sandbox.path("rust").display().to_string();
+    let rustlib = format!("{out}/lib/rustlib/{target}/bin");
+    let loader = "--set-interpreter /glibc/lib/ld-linux-x86-64.so.2";
+    assert_eq!(
+        fs::read_to_string(&log).unwrap(),
+        format!(
+            "{loader} {out}/bin/rustc
+--set-rpath $ORIGIN/../lib:/glibc/lib {out}/bin/rustc
+--set-rpath /glibc/lib {out}/lib/librustc_driver.so
+{loader} {rustlib}/rust-lld
+--set-rpath $ORIGIN/../lib:/glibc/lib {rustlib}/rust-lld
+{loader} {rustlib}/gcc-ld/ld.lld
+--set-rpath $ORIGIN/../lib:/glibc/lib {rustlib}/gcc-ld/ld.lld
+"
+        )
+    );
+}
@@ -0,0 +1,123 @@
+mod common;
+
+use common::{Sandbox, assert_success, repo_file};
+use std::{fs, os::unix::fs::PermissionsExt, path::PathBuf, process::Command};
+
+fn builder(script: &str) -> PathBuf {
+    repo_file(&format!("src/pkgs/build/trivial_builders/{script}"))
+}
+
+#[test]
+fn writes_a_text_file() {
+    let sandbox = Sandbox::new("trivial-write-text");
+    assert_success(&sandbox.build(
+        &builder("write-text-file.sh"),
+        "out",
+        &[("TEXT", "hello $out\n")],
+    ));
+    let out = sandbox.path("out");
+    assert_eq!(fs::read_to_string(&out).unwrap(), "hello $out\n");
+    assert_eq!(fs::metadata(&out).unwrap().permissions().mode() & 0o111, 0);
+}
+
+#[test]
+fn writes_an_executable_at_its_destination_and_checks_it() {
+    let sandbox = Sandbox::new("trivial-write-script");
+    let env = [
+        ("TEXT", "#!/bin/sh\necho hi\n"),
+        ("DESTINATION", "/bin/hi"),
+        ("EXECUTABLE", "1"),
+        ("CHECK_TEXT", r#"[ "$("$target")" = hi ]"#),
+    ];
+    assert_success(&sandbox.build(&builder("write-text-file.sh"), "out", &env));
+    assert_eq!(sandbox.run("out/bin/hi"), "hi\n");
+
+    let mut env = env;
+    env[3] = ("CHECK_TEXT", r#"[ "$("$target")" = bye ]"#);
+    let output = sandbox.build(&builder("write-text-file.sh"), "failing", &env);
+    assert!(!output.status.success());
+}
+
+#[test]
+fn shell_applications_run_strictly_with_their_runtime_inputs() {
+    let sandbox = Sandbox::new("trivial-shell-application");
+    sandbox.write("greeter/bin/greet", "#!/bin/sh\necho \"hello $1\"\n");
+    sandbox.sh("chmod +x greeter/bin/greet");
+    // without a bin directory, left out of PATH
+    sandbox.write("data/share/name", "world\n");
+    let inputs = format!(
+        "{} {}",
+        sandbox.path("greeter").display(),
+        sandbox.path("data").display()
+    );
+    assert_success(&sandbox.build(
+        &builder("write-shell-application.sh"),
+        "out",
+        &[
+            ("APPLICATION", "hello"),
+            ("TEXT", "greet \"${1:-world}\"\necho \"$UNSET_VARIABLE\""),
+            ("RUNTIME_INPUTS", &inputs),
+        ],
+    ));
+    let script = fs::read_to_string(sandbox.path("out/bin/hello")).unwrap();
+    assert!(
+        script.starts_with("#!/bin/bash\nset -o errexit\n"),
+        "{script}"
+    );
+    assert!(script.contains(&format!(
+        "export PATH=\"{}/bin:$PATH\"\n",
+        sandbox.path("greeter").display()
+    )));
+    let output = Command::new(sandbox.path("out/bin/hello"))
+        .arg("you")
+        .output()
+        .unwrap();
+    assert_eq!(String::from_utf8(output.stdout).unwrap(), "hello you\n");
+    // nounset stops it at the unset variable
+    assert!(!output.status.success());
+}
+
+#[test]
+fn shell_applications_must_parse() {
+    let sandbox = Sandbox::new("trivial-shell-application-syntax");
+    let output = sandbox.build(
+        &builder("write-shell-application.sh"),
+        "out",
+        &[
+            ("APPLICATION", "broken"),
+            ("TEXT", "if true; then echo"),
+            ("RUNTIME_INPUTS", ""),
+        ],
+    );
+    assert!(!output.status.success());
+}
+
+#[test]
+fn symlink_join_merges_the_paths() {
+    let sandbox = Sandbox::new("trivial-symlink-join");
+    sandbox.write("first/bin/tool", "first\n");
+    sandbox.write("first/share/doc/first", "");
+    sandbox.write("second/bin/tool", "second\n");
+    sandbox.write("second/bin/other", "other\n");
+    sandbox.write("second/share/doc/second", "");
+    sandbox.write("second/.hidden", "");
+    let paths = format!(
+        "{} {}",
+        sandbox.path("first").display(),
+        sandbox.path("second").display()
+    );
+    assert_success(&sandbox.build(&builder("symlink-join.sh"), "out", &[("PATHS", &paths)]));
+    let out = sandbox.path("out");
+    assert!(!out.join("bin").is_symlink());
+    assert_eq!(
+        fs::read_link(out.join("bin/tool")).unwrap(),
+        sandbox.path("first/bin/tool")
+    );
+    assert_eq!(
+        fs::read_link(out.join("bin/other")).unwrap(),
+        sandbox.path("second/bin/other")
+    );
+    for file in ["share/doc/first", "share/doc/second", ".hidden"] {
+        assert!(out.join(file).is_symlink(), "{file}");
+    }
+}